@@ -0,0 +1,89 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use docx_rust::document::Run;
+use scraper::Html;
+use scrapper::config::config::ExportConfig;
+use scrapper::post::post::{parse_messages, Post};
+use scrapper::report::report::Report;
+use std::hint::black_box;
+
+const MESSAGES: usize = 400;
+
+/// A topic page shaped like the forum's, with `messages` alternating questions and answers.
+fn large_topic(messages: usize) -> String {
+    let posts = (0..messages)
+        .map(|i| {
+            let (author, body) = if i % 2 == 0 {
+                (
+                    "Yossef Cohen",
+                    "Bonjour Rav,<br>Peut-on allumer une bougie <span style=\"font-weight:bold\">juste avant</span> chabbat ?<br>Merci",
+                )
+            } else {
+                (
+                    "Rav Binyamin Wattenberg",
+                    "<div class=\"border-l-4 border-blue-500\"><div>Yossef Cohen a écrit:</div><div>Peut-on allumer une bougie juste avant chabbat ?</div></div>Oui, tant que le soleil ne s'est <span style=\"text-decoration:underline\">pas</span> couché. Voir <a href=\"https://www.sefaria.org\">Choulhan Aroukh</a> 263.",
+                )
+            };
+
+            format!(
+                "<div class=\"flex\"><div class=\"w-1/5 p-2\"><strong class=\"block mb-2\">{author}</strong></div><div class=\"w-4/5 p-2\"><a class=\"text-blue-link\" href=\"viewtopic.php?p={i}\">Posté le: 12/03/2023 10:15</a><div class=\"py-4 postrow-message\">{body}</div></div></div>"
+            )
+        })
+        .collect::<String>();
+
+    format!(
+        "<!DOCTYPE html><html lang=\"fr\"><head><meta charset=\"utf-8\"></head><body><div class=\"container\"><div class=\"overflow-hidden border-blue-500\"><div>{posts}</div></div></div></body></html>"
+    )
+}
+
+fn benchmarks(c: &mut Criterion) {
+    let page = large_topic(MESSAGES);
+    let html = Html::parse_document(&page);
+    let messages = parse_messages(&html).unwrap();
+
+    c.bench_function("parse_topic_html", |b| {
+        b.iter(|| Html::parse_document(black_box(&page)))
+    });
+
+    c.bench_function("extract_messages", |b| {
+        b.iter(|| parse_messages(black_box(&html)).unwrap())
+    });
+
+    c.bench_function("generate_runs", |b| {
+        b.iter(|| {
+            messages
+                .iter()
+                .cloned()
+                .map(Vec::<Run>::from)
+                .collect::<Vec<_>>()
+        })
+    });
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("bench.docx");
+    c.bench_function("write_docx", |b| {
+        b.iter_batched(
+            || {
+                // Start from an empty document every time instead of appending to the previous one
+                let _ = std::fs::remove_file(&path);
+                Post {
+                    title: "allumer une bougie avant chabbat".to_string(),
+                    category: "bench".into(),
+                    messages: Some(messages.clone()),
+                    ..Default::default()
+                }
+            },
+            |mut post| {
+                post.messages_to_word(&path, &ExportConfig::default(), &mut Report::default())
+                    .unwrap()
+            },
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = benchmarks
+}
+criterion_main!(benches);
@@ -0,0 +1,54 @@
+//! Rebuilds the DOCX documents of every topic of a state file from the pages kept in
+//! `scrape.archive_dir`, without requesting the forum.
+//!
+//! ```sh
+//! cargo run --example rebuild_from_archive -- files_generated/state.json archive rebuilt
+//! ```
+use anyhow::{Context, Result};
+use reqwest::Client;
+use scrapper::config::config::Config;
+use scrapper::http::client::{get_page, set_replay};
+use scrapper::post::post::Post;
+use scrapper::report::report::Report;
+use scrapper::state::state::State;
+use scrapper::store::archive::RawArchive;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let mut args = std::env::args().skip(1);
+    let usage = "Usage: rebuild_from_archive <state.json> <archive dir> <output dir>";
+    let state = State::load(args.next().context(usage)?)?;
+    let archive_dir = PathBuf::from(args.next().context(usage)?);
+    let output_dir = PathBuf::from(args.next().context(usage)?);
+    std::fs::create_dir_all(&output_dir)?;
+
+    // Every page is read from the archive from now on
+    set_replay(Some(RawArchive::open(&archive_dir)?));
+    let mut config = Config::default();
+    config.scrape.output_dir = output_dir;
+    let config = Arc::new(config);
+    let client = Client::new();
+
+    let mut report = Report::default();
+    for (url, topic) in &state.topics {
+        let mut post = Post {
+            url: url.clone(),
+            title: topic.title.clone(),
+            category: topic.category.as_str().into(),
+            html: Some(get_page(&client, url.as_str()).await?.0),
+            ..Default::default()
+        };
+        // The stored state keeps the messages since deleted from the forum in the documents
+        if let Some(path) = post
+            .save(&client, &config, &mut report, None, None, Some(&state))
+            .await
+            .with_context(|| format!("Failed to rebuild {}", url))?
+        {
+            println!("{} -> {}", url, path.display());
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Prints the size of the documents of a run per category, from its `report.json`.
+//!
+//! ```sh
+//! cargo run --example run_stats -- files_generated/report.json
+//! ```
+use anyhow::{Context, Result};
+use scrapper::report::report::Report;
+use std::collections::BTreeMap;
+
+#[derive(Default)]
+struct CategoryStats {
+    topics: usize,
+    messages: usize,
+    words: usize,
+    reading_minutes: usize,
+}
+
+fn main() -> Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .context("Usage: run_stats <report.json>")?;
+    let report = Report::load(path)?;
+
+    let mut categories: BTreeMap<&str, CategoryStats> = BTreeMap::new();
+    for topic in &report.topics {
+        let stats = categories.entry(topic.category.as_str()).or_default();
+        stats.topics += 1;
+        stats.messages += topic.messages;
+        stats.words += topic.words;
+        stats.reading_minutes += topic.reading_minutes;
+    }
+
+    println!("category\ttopics\tmessages\twords\treading minutes");
+    for (category, stats) in &categories {
+        println!(
+            "{}\t{}\t{}\t{}\t{}",
+            category, stats.topics, stats.messages, stats.words, stats.reading_minutes
+        );
+    }
+    println!(
+        "\n{} long messages, {} edited and {} deleted since their export, {} protected documents",
+        report.long_messages.len(),
+        report.changed_messages.len(),
+        report.deleted_messages.len(),
+        report.protected_documents.len()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,47 @@
+//! Scrapes one topic of the forum to Markdown, its messages as plain text.
+//!
+//! ```sh
+//! cargo run --example topic_to_markdown -- "https://www.techouvot.com/viewtopic.php?t=101" > topic.md
+//! ```
+use anyhow::{Context, Result};
+use reqwest::Client;
+use scraper::{Html, Selector};
+use scrapper::config::config::DatesConfig;
+use scrapper::http::client::get_page;
+use scrapper::post::post::Post;
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let url = std::env::args()
+        .nth(1)
+        .context("Usage: topic_to_markdown <topic url>")?;
+    let client = Client::new();
+    let (page, _) = get_page(&client, url.as_str()).await?;
+    let title = Html::parse_document(&page)
+        .select(&Selector::parse("title").expect("valid selector"))
+        .next()
+        .map(|title| title.text().collect::<String>().trim().to_owned())
+        .unwrap_or_else(|| url.clone());
+
+    let mut post = Post {
+        url: url.clone(),
+        title,
+        html: Some(page),
+        ..Default::default()
+    };
+    // The next pages of the topic are fetched along
+    post.get_messages(&client).await?;
+
+    let dates = DatesConfig::default();
+    println!("# {}\n\n<{}>\n", post.title, post.url);
+    for message in post.messages.iter().flatten() {
+        println!(
+            "**{}**, {}\n\n{}\n",
+            message.author,
+            dates.format(&message.date),
+            message.text()
+        );
+    }
+
+    Ok(())
+}
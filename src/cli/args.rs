@@ -0,0 +1,155 @@
+use crate::config::config::{ExportFormat, FormattingProfile};
+use crate::post::roles::Role;
+use crate::utils::constants::CONFIG_FILE;
+use clap::{Parser, Subcommand, ValueEnum};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+#[derive(Debug, Parser)]
+#[command(
+    version,
+    about = "Scrapes Rav Binyamin Wattenberg's answers into Word documents"
+)]
+pub struct Args {
+    /// Path to the TOML config file
+    #[arg(long, default_value = CONFIG_FILE)]
+    pub config: PathBuf,
+
+    /// Search the whole archive instead of only what was posted since the last successful run
+    #[arg(long)]
+    pub full: bool,
+
+    /// Bundle the generated documents and the manifest into a single archive
+    #[arg(long, value_enum)]
+    pub package: Option<PackageFormat>,
+
+    /// Output format, defaults to the one of the config file (docx)
+    #[arg(long, value_enum)]
+    pub format: Option<ExportFormat>,
+
+    /// Start each topic of the Word documents on a new page
+    #[arg(long)]
+    pub page_break_between_topics: bool,
+
+    /// Layout of the documents, defaults to the one of the config file (screen)
+    #[arg(long, value_enum)]
+    pub formatting: Option<FormattingProfile>,
+
+    /// Keep scraping at the configured interval, publishing new answers in an Atom feed
+    #[arg(long)]
+    pub watch: bool,
+
+    /// Only export the Rav's answers, each prefixed by a summary of the question
+    #[arg(long)]
+    pub answers_only: bool,
+
+    /// Write the messages not exported yet to a separate `.delta.docx`, leaving the documents
+    /// of the previous runs untouched
+    #[arg(long)]
+    pub delta: bool,
+
+    /// Leave the messages of these roles out of the documents, e.g. "thanks,bump", the state
+    /// keeping them; added to the ones of the config file
+    #[arg(long, value_delimiter = ',', value_enum)]
+    pub drop: Vec<Role>,
+
+    /// Check that every word of the messages made it into the documents, listing the messages
+    /// that lost some in the report
+    #[arg(long)]
+    pub verify: bool,
+
+    /// Write a JSON summary of the run (topics, bytes downloaded, durations, errors) to this file
+    #[arg(long)]
+    pub summary: Option<PathBuf>,
+
+    /// Only process the topics of these categories, e.g. "Halakha,Hachkafa"
+    #[arg(long, value_delimiter = ',')]
+    pub category: Vec<String>,
+
+    /// Stop fetching topics once about this many requests were sent, leaving the other topics
+    /// to the next runs
+    #[arg(long)]
+    pub max_requests: Option<usize>,
+
+    /// Topics fetched first when `--max-requests` does not allow fetching them all
+    #[arg(long, value_enum, default_value_t)]
+    pub strategy: Strategy,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Groups the topics of the last run report into printed volumes and writes the plan as CSV
+    Plan(PlanArgs),
+    /// Lists the topics closest in meaning to a query (needs the `embeddings` feature)
+    SemanticSearch(SemanticSearchArgs),
+    /// Lists the forums of the index with their id and number of topics and posts, as TSV
+    Categories,
+    /// Regenerates the documents of some categories from the archived pages (`scrape.archive_dir`)
+    /// instead of the forum
+    Export(ExportArgs),
+    /// Records the topics of documents generated before the state was kept, so that the next
+    /// runs only fetch what is new
+    Import(ImportArgs),
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ImportArgs {
+    /// Documents to import, every category document of the output directory by default
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct ExportArgs {
+    /// Categories to regenerate, e.g. "Halakha,Hachkafa"
+    #[arg(long, value_delimiter = ',')]
+    pub category: Vec<String>,
+
+    /// Regenerate the documents these topics were exported into
+    #[arg(long)]
+    pub topic_url: Vec<String>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct SemanticSearchArgs {
+    pub query: String,
+
+    #[arg(long, default_value = "10")]
+    pub limit: NonZeroUsize,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct PlanArgs {
+    #[arg(long, default_value = "300")]
+    pub pages_per_volume: NonZeroUsize,
+
+    /// Average number of words fitting on a printed page
+    #[arg(long, default_value = "350")]
+    pub words_per_page: NonZeroUsize,
+
+    /// Report to plan from, defaults to the one of the output directory
+    #[arg(long)]
+    pub report: Option<PathBuf>,
+
+    /// Where to write the CSV, defaults to `volumes.csv` in the output directory
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+}
+
+/// Which topics a run bounded by `--max-requests` fetches first
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Strategy {
+    /// The most recently posted topics
+    #[default]
+    Newest,
+    /// The oldest topics not exported yet, searching the whole archive, to gradually fill in
+    /// the historical corpus
+    Backfill,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum PackageFormat {
+    Zip,
+}
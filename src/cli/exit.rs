@@ -0,0 +1,44 @@
+use std::fmt;
+use std::process::ExitCode;
+
+/// Exit codes of the process, for the cron jobs and scripts running the scraper.
+///
+/// Attached as context to an error (`.context(Exit::DiscoveryFailed)`) to end the process with
+/// that code, any other error exits with [`Exit::Error`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Exit {
+    Success = 0,
+    /// Invalid config, unwritable output directory...
+    Error = 1,
+    /// Some topics could not be fetched or exported, the others were
+    PartialFailure = 2,
+    /// The forum search failed, nothing was exported
+    DiscoveryFailed = 3,
+    /// The documents were generated but could not be uploaded or sent
+    DeliveryFailed = 4,
+}
+
+impl fmt::Display for Exit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Exit::Success => "success",
+            Exit::Error => "error",
+            Exit::PartialFailure => "some topics failed",
+            Exit::DiscoveryFailed => "topic discovery failed",
+            Exit::DeliveryFailed => "delivery failed",
+        })
+    }
+}
+
+impl From<Exit> for ExitCode {
+    fn from(exit: Exit) -> Self {
+        ExitCode::from(exit as u8)
+    }
+}
+
+impl Exit {
+    /// Code to exit with after `error`.
+    pub fn of(error: &anyhow::Error) -> Self {
+        error.downcast_ref::<Exit>().copied().unwrap_or(Exit::Error)
+    }
+}
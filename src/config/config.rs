@@ -0,0 +1,892 @@
+use crate::post::category::CategoryLevel;
+use crate::post::post::Pinned;
+use crate::post::roles::Role;
+use crate::state::pauses::WeekTime;
+use crate::utils::constants::{
+    BASE_URL, DATABASE_FILE, MAX_PAGES, MAX_PAGE_BYTES, MAX_TOPIC_MESSAGES, MAX_TOPIC_PAGES,
+    OUTPUT_DIR, PAGE_SIZE,
+};
+use crate::utils::functions::{french_long_date, parse_post_date};
+use crate::utils::hebrew::HebrewDate;
+use crate::utils::timezone::Zone;
+use anyhow::{Context, Result};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, NaiveDateTime, NaiveTime, SecondsFormat, Utc};
+use clap::ValueEnum;
+use reqwest::Url;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub scrape: ScrapeConfig,
+    pub export: ExportConfig,
+    pub upload: Option<UploadConfig>,
+    pub drive: Option<DriveConfig>,
+    pub email: Option<EmailConfig>,
+    pub summarizer: Option<SummarizerConfig>,
+    pub embeddings: Option<EmbeddingsConfig>,
+    pub translator: Option<TranslatorConfig>,
+    pub profiles: Option<ProfilesConfig>,
+    pub watch: WatchConfig,
+    /// Applied in order to the text of every message before export
+    pub transforms: Vec<TransformConfig>,
+    pub characters: CharactersConfig,
+    pub store: StoreConfig,
+}
+
+/// Where the state is kept between runs, `[store]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum StoreConfig {
+    /// A JSON file, `state.json` in the output directory by default
+    Files { path: Option<PathBuf> },
+    /// Tables of a SQLite database, `ribav.sqlite` in the output directory by default
+    Sqlite { database: Option<PathBuf> },
+}
+
+impl Default for StoreConfig {
+    fn default() -> Self {
+        StoreConfig::Files { path: None }
+    }
+}
+
+/// Characters Word shows as boxes (forum emoticons, stray combining marks), replaced before the
+/// text transforms, e.g. `"\uE001" = ":)"`
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CharactersConfig {
+    pub map: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScrapeConfig {
+    /// Root of the forum, overridable to target a mirror or a local test server
+    pub base_url: String,
+    pub page_size: u32,
+    pub max_pages: u32,
+    pub output_dir: PathBuf,
+    /// Searches run one after the other, the topics they find are merged
+    pub searches: Vec<SearchConfig>,
+    /// Keeps every fetched page, compressed and stored once per distinct content, in this
+    /// directory
+    pub archive_dir: Option<PathBuf>,
+    /// What to do with the announcements and sticky topics found by the searches
+    pub pinned: PinnedTopics,
+    /// Topics fetched ahead of their export, the pages of the next topics downloading while one
+    /// is exported
+    pub fetch_ahead: usize,
+    /// Pages larger than this fail instead of being read, a pathological page would otherwise
+    /// be held whole in memory, several times over while decoded and parsed
+    pub max_page_bytes: u64,
+    /// A topic with more messages than this fails, a wrong selector or a pagination going round
+    /// in circles would otherwise fill the memory with one topic
+    pub max_topic_messages: usize,
+    /// A topic with more pages than this fails, see `max_topic_messages`
+    pub max_topic_pages: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PinnedTopics {
+    #[default]
+    Include,
+    Exclude,
+    /// Only the announcements and sticky topics
+    Only,
+}
+
+impl PinnedTopics {
+    pub fn keeps(self, pinned: Pinned) -> bool {
+        match self {
+            PinnedTopics::Include => true,
+            PinnedTopics::Exclude => pinned == Pinned::No,
+            PinnedTopics::Only => pinned != Pinned::No,
+        }
+    }
+}
+
+impl Default for ScrapeConfig {
+    fn default() -> Self {
+        Self {
+            base_url: BASE_URL.to_string(),
+            page_size: PAGE_SIZE,
+            max_pages: MAX_PAGES,
+            output_dir: PathBuf::from(OUTPUT_DIR),
+            searches: vec![SearchConfig::default()],
+            archive_dir: None,
+            pinned: PinnedTopics::Include,
+            fetch_ahead: 8,
+            max_page_bytes: MAX_PAGE_BYTES,
+            max_topic_messages: MAX_TOPIC_MESSAGES,
+            max_topic_pages: MAX_TOPIC_PAGES,
+        }
+    }
+}
+
+/// One search of the forum, `[[scrape.searches]]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SearchConfig {
+    /// Only the messages of this author, every author when empty
+    pub author: String,
+    /// Only the messages containing any of these words, every message when empty
+    pub keywords: String,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        Self {
+            author: "Rav Binyamin Wattenberg".to_string(),
+            keywords: String::new(),
+        }
+    }
+}
+
+/// Options of `--watch`, scraping again and again
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WatchConfig {
+    pub interval_minutes: u64,
+    pub feed_title: String,
+    /// Number of answers kept in the Atom feed
+    pub feed_entries: usize,
+    pub pauses: PausesConfig,
+}
+
+/// When `--watch` does not scrape, `[watch.pauses]`, e.g. from Friday evening to Saturday
+/// night and on the days of Yom Tov
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PausesConfig {
+    /// Time zone of the times below
+    pub timezone: Zone,
+    /// E.g. `[{ from = "friday 17:00", to = "saturday 22:00" }]`
+    pub weekly: Vec<WeeklyPause>,
+    /// Whether to pause on the days of Yom Tov (as kept outside of Israel), from
+    /// `holiday_start` on their eve to `holiday_end` on the day
+    pub holidays: bool,
+    pub holiday_start: NaiveTime,
+    pub holiday_end: NaiveTime,
+}
+
+impl Default for PausesConfig {
+    fn default() -> Self {
+        Self {
+            timezone: Zone::CentralEurope,
+            weekly: Vec::new(),
+            holidays: false,
+            holiday_start: NaiveTime::from_hms_opt(17, 0, 0).expect("valid time"),
+            holiday_end: NaiveTime::from_hms_opt(22, 0, 0).expect("valid time"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WeeklyPause {
+    pub from: WeekTime,
+    pub to: WeekTime,
+}
+
+impl WeeklyPause {
+    /// Days from the day the pause starts to the day it ends, 0 to 6.
+    pub fn days_until_end(&self) -> u32 {
+        (self.to.weekday.num_days_from_monday() + 7 - self.from.weekday.num_days_from_monday()) % 7
+    }
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            interval_minutes: 60,
+            feed_title: "Réponses du Rav Binyamin Wattenberg".to_string(),
+            feed_entries: 50,
+            pauses: PausesConfig::default(),
+        }
+    }
+}
+
+/// Options of each exporter
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExportConfig {
+    /// Overridden by `--format`
+    pub format: ExportFormat,
+    pub docx: DocxExportConfig,
+    pub glossary: Option<GlossaryConfig>,
+    /// Footnotes giving the url of the topics mentioned by their title in the messages, "voir le
+    /// sujet « … »", in the Word documents
+    pub cross_references: bool,
+    /// Roles of the messages left out of the exports, e.g. `["thanks", "bump"]`, the state
+    /// keeping them
+    pub drop: Vec<Role>,
+    pub annotations: Option<AnnotationsConfig>,
+    pub long_messages: LongMessageConfig,
+    pub front_matter: Option<FrontMatterConfig>,
+    /// Condensed edition: only the answers, each introduced by a summary of the question
+    pub answers_only: bool,
+    /// Only export the messages not exported by a previous run, into `{category}.delta.docx` so
+    /// that manually edited documents are never rewritten. Overridden by `--delta`
+    pub delta: bool,
+    /// Calendar with an event on the day each topic was answered
+    pub ical: bool,
+    /// Ends each topic with a small "Source: {url} (consulté le {date})" line
+    pub source_footer: bool,
+    /// Shows the replies and views of each topic under its title: "3 réponses · 154 vues"
+    pub activity: bool,
+    /// Ends the messages deleted from the forum since they were exported, kept in the documents,
+    /// with "[message supprimé du forum]"
+    pub deleted_note: bool,
+    /// Whether the topics of the subforums go to documents of their own or to the one of their
+    /// forum
+    pub group_by: CategoryLevel,
+    pub notice: Option<NoticeConfig>,
+    pub anki: AnkiConfig,
+    pub question_summary: QuestionSummary,
+    pub font_size: FontSizeConfig,
+    /// Layout of the DOCX and ODT documents, overridden by `--formatting`
+    pub formatting: FormattingProfile,
+    /// How the topics of a DOCX document are separated, `page` with
+    /// `--page-break-between-topics`
+    pub topic_break: TopicBreak,
+    pub paragraphs: ParagraphsConfig,
+    pub dates: DatesConfig,
+    pub titles: TitlesConfig,
+    /// What is done of the topics continuing an earlier one, "… (suite)" or "… 2"
+    pub series: SeriesMode,
+}
+
+/// How the dates of the messages are shown in the exports, `[export.dates]`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatesConfig {
+    /// Time zone of the dates shown by the forum
+    pub forum_timezone: Zone,
+    /// Time zone the dates are shown in
+    pub timezone: Zone,
+    /// strftime pattern, e.g. "%A %d %B %Y à %H:%M", used by the `pattern` style
+    pub format: String,
+    pub style: DateStyle,
+    /// Whether the Hebrew date follows the date of the answers, e.g. "(19 Adar 5783)". The
+    /// civil day is converted: the Hebrew day starting at nightfall is not taken into account.
+    pub hebrew: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DateStyle {
+    /// The `format` pattern
+    #[default]
+    Pattern,
+    /// Written out in French: "3 mars 2021 à 10:15"
+    Long,
+    /// ISO 8601 with the offset of the time zone: "2021-03-03T10:15:00+01:00"
+    Iso,
+}
+
+impl Default for DatesConfig {
+    fn default() -> Self {
+        Self {
+            forum_timezone: Zone::CentralEurope,
+            timezone: Zone::CentralEurope,
+            format: "%d/%m/%Y %H:%M".to_owned(),
+            style: DateStyle::Pattern,
+            hebrew: false,
+        }
+    }
+}
+
+impl DatesConfig {
+    /// Instant of a date of the forum, e.g. "Posté le: 12/03/2023 10:15".
+    pub fn instant(&self, date: &str) -> Option<DateTime<Utc>> {
+        parse_post_date(date).map(|local| self.forum_timezone.to_utc(local))
+    }
+
+    /// A date of the forum in the configured time zone and style, the date as the forum
+    /// shows it (without "Posté le:") when it cannot be parsed.
+    pub fn format(&self, date: &str) -> String {
+        let Some(instant) = self.instant(date) else {
+            return date.replace("Posté le:", "").trim().to_owned();
+        };
+        let local = self.timezone.from_utc(instant);
+        match self.style {
+            DateStyle::Pattern => local.format(&self.format).to_string(),
+            DateStyle::Long => {
+                format!(
+                    "{} à {}",
+                    french_long_date(local.date()),
+                    local.format("%H:%M")
+                )
+            }
+            DateStyle::Iso => instant
+                .with_timezone(&self.timezone.offset_at(instant.naive_utc()))
+                .to_rfc3339_opts(SecondsFormat::Secs, false),
+        }
+    }
+
+    /// Wall-clock time in the time zone of the forum of a date shown by `format`, `None` for the
+    /// dates written out in French.
+    pub fn parse(&self, shown: &str) -> Option<NaiveDateTime> {
+        let local = match self.style {
+            DateStyle::Pattern => NaiveDateTime::parse_from_str(shown.trim(), &self.format).ok()?,
+            DateStyle::Iso => {
+                return DateTime::parse_from_rfc3339(shown.trim())
+                    .ok()
+                    .map(|date| self.forum_timezone.from_utc(date.with_timezone(&Utc)))
+            }
+            DateStyle::Long => return None,
+        };
+        Some(self.forum_timezone.from_utc(self.timezone.to_utc(local)))
+    }
+
+    /// The line above a message: "Le 12/03/2023 10:15", with the Hebrew date for the answers
+    /// when asked for.
+    pub fn heading(&self, date: &str, answer: bool) -> String {
+        let hebrew = self
+            .instant(date)
+            .filter(|_| self.hebrew && answer)
+            .map(|instant| HebrewDate::from_gregorian(self.timezone.from_utc(instant).date()));
+        match hebrew {
+            Some(hebrew) => format!("Le {} ({})", self.format(date), hebrew),
+            None => format!("Le {}", self.format(date)),
+        }
+    }
+}
+
+/// Spacing of the paragraphs of the messages, over the one of the formatting profile,
+/// `[export.paragraphs]`
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ParagraphsConfig {
+    /// Space before and after each paragraph, in points
+    pub before: Option<f32>,
+    pub after: Option<f32>,
+    /// Multiple of single line spacing, e.g. 1.15
+    pub line_spacing: Option<f32>,
+    /// Whether Word avoids leaving the first or last line of a paragraph alone on a page
+    pub widow_control: Option<bool>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeriesMode {
+    /// Exported as the other topics
+    Off,
+    /// Exported as the other topics, under a "Suite de « … »" line naming the first one
+    #[default]
+    Link,
+    /// Exported right after the first topic when the run exports it too, in its chapter
+    Merge,
+}
+
+/// Clean up of the titles of the topics shown as headings, `[export.titles]`. The state, the
+/// reports and the other metadata keep the titles as the forum lists them.
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TitlesConfig {
+    /// The titles written in capitals only are lowercased, their first letter kept a capital
+    pub sentence_case: bool,
+    /// Runs of "?" and "!" reduced to one of each, "????" to "?"
+    pub trim_punctuation: bool,
+    /// Longer titles are cut at a word and end with "…", in characters
+    pub max_length: Option<usize>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TopicBreak {
+    /// Blank paragraphs only, the topics run together
+    #[default]
+    Blank,
+    /// Each topic starts on a new page
+    Page,
+    /// Each topic is a section of its own starting on a new page, so that its headers,
+    /// margins or page numbering can be changed in Word
+    Section,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum FormattingProfile {
+    /// Read in Word: the layout of the first versions
+    #[default]
+    Screen,
+    /// Printed: larger text, spaced paragraphs, italic citations and titles in the outline
+    Print,
+    /// Reviewed: smaller text, no blank lines between messages
+    Compact,
+}
+
+/// How the font sizes of the forum, in px, are converted to Word sizes, `[export.font_size]`
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FontSizeConfig {
+    /// Sizes under this one, in px, are too small to read in print
+    pub threshold: u8,
+    /// Size the small ones are brought to, in px
+    pub minimum: u8,
+    pub mode: FontSizeMode,
+    /// Multiplies every size once converted to points, 1 keeps the CSS ratio of 0.75pt per px
+    pub factor: f32,
+}
+
+impl Default for FontSizeConfig {
+    fn default() -> Self {
+        Self {
+            threshold: 15,
+            minimum: 16,
+            mode: FontSizeMode::Clamp,
+            factor: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FontSizeMode {
+    /// Every small size becomes `minimum`
+    #[default]
+    Clamp,
+    /// Small sizes are kept, e.g. for disclaimers meant to be small
+    Keep,
+    /// Small sizes are multiplied by `minimum / threshold`, still smaller than the text
+    /// around them but readable
+    Scale,
+}
+
+impl FontSizeConfig {
+    /// Size exported for a font of `px`, in px.
+    pub fn apply(&self, px: f32) -> f32 {
+        let threshold = self.threshold as f32;
+        if px >= threshold {
+            return px;
+        }
+        match self.mode {
+            FontSizeMode::Clamp => self.minimum as f32,
+            FontSizeMode::Keep => px,
+            FontSizeMode::Scale => px * self.minimum as f32 / threshold.max(1.0),
+        }
+    }
+
+    /// Word size, in half-points, of a font of `px`.
+    pub fn half_points(&self, px: f32) -> isize {
+        // 96 px per inch, 72 pt per inch
+        let points = self.apply(px) * 0.75 * self.factor;
+        (points * 2.0).round() as isize
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionSummary {
+    /// First question (or sentence) asked in the message
+    #[default]
+    FirstSentence,
+    Title,
+}
+
+/// Attribution and permission notice written to `NOTICE.txt` next to the documents, the
+/// attribution also going into the properties of every DOCX and ODT
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NoticeConfig {
+    pub attribution: String,
+    /// Notice template, may use `{attribution}`, `{source}` and `{date}`
+    pub template: Option<PathBuf>,
+}
+
+impl Default for NoticeConfig {
+    fn default() -> Self {
+        Self {
+            attribution: "Réponses du Rav Binyamin Wattenberg, publiées sur le forum Techouvot"
+                .to_string(),
+            template: None,
+        }
+    }
+}
+
+/// Pages added at the start of a document when it is created.
+///
+/// Template files may use `{category}` and `{date}`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FrontMatterConfig {
+    /// Cover page with the title, the category and the generation date
+    pub cover: bool,
+    pub title: String,
+    /// Copyright/disclaimer page template
+    pub disclaimer: Option<PathBuf>,
+    /// Preface template, paragraphs separated by blank lines
+    pub preface: Option<PathBuf>,
+}
+
+impl Default for FrontMatterConfig {
+    fn default() -> Self {
+        Self {
+            cover: true,
+            title: "Réponses du Rav Binyamin Wattenberg".to_string(),
+            disclaimer: None,
+            preface: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LongMessageConfig {
+    /// Messages with more characters of text are listed in the run report
+    pub max_chars: usize,
+    /// Cut those messages at `max_chars` and point to the topic for the full text
+    pub truncate: bool,
+}
+
+impl Default for LongMessageConfig {
+    fn default() -> Self {
+        Self {
+            max_chars: 20_000,
+            truncate: false,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    /// One Word document per category
+    #[default]
+    Docx,
+    /// One row per message in `messages.csv`, for reviewing in a spreadsheet
+    Csv,
+    /// Question/answer flashcards in `anki.tsv`, importable in Anki
+    Anki,
+    /// One LaTeX `book` per category
+    Latex,
+    /// One OpenDocument text per category, laid out like the Word documents
+    Odt,
+}
+
+impl ExportFormat {
+    /// Extension of the document of each category, `None` for the formats writing every
+    /// category into a single file.
+    pub fn category_extension(self) -> Option<&'static str> {
+        match self {
+            ExportFormat::Docx => Some("docx"),
+            ExportFormat::Latex => Some("tex"),
+            ExportFormat::Odt => Some("odt"),
+            ExportFormat::Csv | ExportFormat::Anki => None,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AnkiConfig {
+    pub front: AnkiFront,
+    /// Only make cards for these categories, all of them when empty
+    pub categories: Vec<String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnkiFront {
+    /// The whole question
+    #[default]
+    Question,
+    /// Its summary, see `question_summary`
+    Summary,
+}
+
+/// Notes of the editors, keyed by message id, added to the DOCX export
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AnnotationsConfig {
+    /// YAML file mapping message ids to their note, e.g. `"5002": "Voir aussi le Michna Beroura"`
+    pub path: PathBuf,
+    #[serde(default)]
+    pub mode: AnnotationMode,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AnnotationMode {
+    /// Margin comment
+    #[default]
+    Comment,
+    Footnote,
+}
+
+/// Expansions added after the first occurrence of each abbreviation in a topic
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GlossaryConfig {
+    #[serde(default)]
+    pub mode: GlossaryMode,
+    pub entries: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlossaryMode {
+    /// "CM (Choul'han Aroukh)"
+    #[default]
+    Brackets,
+    Footnote,
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DocxExportConfig {
+    /// Print typography: non-breaking spaces before ; : ! ?, « » quotes and ellipsis character
+    pub french_typography: bool,
+    /// Prints the word count and reading time of each topic under its title
+    pub word_count: bool,
+    /// Directory of `{message id}.txt` translations, printed next to the French text in a two-column table
+    pub translations: Option<PathBuf>,
+    /// Flags with a Word comment the spots the parser could not fully understand (unknown tag or
+    /// style, suspicious encoding) for manual review
+    pub review_comments: bool,
+    /// Numbers the topics (1.) and their exchanges (1.1.) with a Word numbering definition, so
+    /// that the numbers follow when topics are moved in Word
+    pub numbering: bool,
+}
+
+/// Fetches the public profile (registration date, post count) of the participants into the
+/// private database, never into the exports
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProfilesConfig {
+    /// SQLite database holding the profiles, defaults to `ribav.sqlite` in the output directory
+    pub database: Option<PathBuf>,
+}
+
+impl ProfilesConfig {
+    pub fn database_path(&self, output_dir: &Path) -> PathBuf {
+        self.database
+            .clone()
+            .unwrap_or_else(|| output_dir.join(DATABASE_FILE))
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum UploadConfig {
+    S3(S3Config),
+    WebDav(WebDavConfig),
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// e.g. "https://s3.eu-west-3.amazonaws.com" or a MinIO endpoint
+    pub endpoint: String,
+    pub bucket: String,
+    pub region: String,
+    pub access_key: String,
+    pub secret_key: String,
+    #[serde(default)]
+    pub prefix: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WebDavConfig {
+    /// Collection the files are uploaded into, e.g. "https://cloud.example.org/remote.php/dav/files/me/ribav/"
+    pub url: String,
+    pub username: Option<String>,
+    pub password: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DriveConfig {
+    /// Service-account JSON key downloaded from the Google Cloud console
+    pub credentials: PathBuf,
+    /// Id of the Drive folder (shared with the service account) receiving the documents
+    pub folder_id: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmailConfig {
+    pub smtp_host: String,
+    /// Defaults to the STARTTLS submission port (587)
+    pub smtp_port: Option<u16>,
+    pub username: String,
+    pub password: String,
+    /// e.g. "Ribav Scraper <scraper@example.org>"
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+/// OpenAI-compatible chat completions endpoint summarizing each question
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SummarizerConfig {
+    /// e.g. "https://api.openai.com/v1" or "http://localhost:11434/v1" for a local Ollama
+    pub endpoint: String,
+    pub model: String,
+    pub api_key: Option<String>,
+    /// System prompt, defaults to a French one-sentence summary instruction
+    pub prompt: Option<String>,
+}
+
+/// Machine translation of the messages, printed next to the French text in the DOCX export
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TranslatorConfig {
+    pub service: TranslationService,
+    pub api_key: String,
+    /// Language code understood by the service, e.g. "EN" for DeepL or "en" for Google
+    pub target_lang: String,
+    /// Overrides the public API, e.g. "https://api.deepl.com" for a DeepL Pro account
+    pub endpoint: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TranslationService {
+    DeepL,
+    Google,
+}
+
+/// OpenAI-compatible embeddings endpoint indexing each question/answers exchange
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EmbeddingsConfig {
+    pub endpoint: String,
+    /// e.g. "text-embedding-3-small" or "nomic-embed-text"
+    pub model: String,
+    pub api_key: Option<String>,
+    /// SQLite database holding the vectors, defaults to `ribav.sqlite` in the output directory
+    pub database: Option<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase", deny_unknown_fields)]
+pub enum TransformConfig {
+    Regex {
+        pattern: String,
+        replacement: String,
+    },
+    /// Whole-word replacements, e.g. `CM = "Choul'han Aroukh"`
+    Abbreviations { entries: BTreeMap<String, String> },
+    /// Squeezes repeated spaces, removes spaces before commas and periods, curls apostrophes
+    Typography,
+}
+
+impl EmbeddingsConfig {
+    pub fn database_path(&self, output_dir: &Path) -> PathBuf {
+        self.database
+            .clone()
+            .unwrap_or_else(|| output_dir.join(DATABASE_FILE))
+    }
+}
+
+impl Config {
+    /// Loads the config file, falling back to the defaults when `path` does not exist.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config file {}", path.display()))?;
+
+        let mut config: Self = toml::from_str(&content)
+            .with_context(|| format!("Failed to parse config file {}", path.display()))?;
+
+        // Urls are built by appending relative hrefs to the base url
+        if !config.scrape.base_url.ends_with('/') {
+            config.scrape.base_url.push('/');
+        }
+
+        config
+            .validate()
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        let base_url = Url::parse(&self.scrape.base_url)
+            .with_context(|| format!("Malformed scrape.base_url: {}", self.scrape.base_url))?;
+        if !matches!(base_url.scheme(), "http" | "https") {
+            return Err(anyhow::anyhow!(
+                "scrape.base_url must be an http(s) url, got {}",
+                self.scrape.base_url
+            ));
+        }
+
+        if self.scrape.page_size == 0 {
+            return Err(anyhow::anyhow!("scrape.page_size must be positive"));
+        }
+
+        if self.scrape.max_pages == 0 {
+            return Err(anyhow::anyhow!("scrape.max_pages must be positive"));
+        }
+
+        if self.scrape.max_page_bytes == 0 {
+            return Err(anyhow::anyhow!("scrape.max_page_bytes must be positive"));
+        }
+
+        if self.scrape.max_topic_messages == 0 || self.scrape.max_topic_pages == 0 {
+            return Err(anyhow::anyhow!(
+                "scrape.max_topic_messages and scrape.max_topic_pages must be positive"
+            ));
+        }
+
+        if self.scrape.searches.is_empty() {
+            return Err(anyhow::anyhow!("scrape.searches must not be empty"));
+        }
+        if let Some(search) = self
+            .scrape
+            .searches
+            .iter()
+            .find(|search| search.author.is_empty() && search.keywords.is_empty())
+        {
+            return Err(anyhow::anyhow!(
+                "scrape.searches needs an author or keywords, got {:?}",
+                search
+            ));
+        }
+
+        if self.watch.interval_minutes == 0 {
+            return Err(anyhow::anyhow!("watch.interval_minutes must be positive"));
+        }
+
+        if self.export.long_messages.max_chars == 0 {
+            return Err(anyhow::anyhow!(
+                "export.long_messages.max_chars must be positive"
+            ));
+        }
+
+        // chrono panics on a malformed pattern when formatting, i.e. partway through a run
+        let format = &self.export.dates.format;
+        if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+            return Err(anyhow::anyhow!(
+                "export.dates.format is not a valid strftime pattern, got {:?}",
+                format
+            ));
+        }
+
+        let font_size = &self.export.font_size;
+        if !(font_size.factor.is_finite() && font_size.factor > 0.0) {
+            return Err(anyhow::anyhow!(
+                "export.font_size.factor must be a positive number, got {}",
+                font_size.factor
+            ));
+        }
+        if font_size.threshold == 0 {
+            return Err(anyhow::anyhow!(
+                "export.font_size.threshold must be positive"
+            ));
+        }
+
+        Ok(())
+    }
+}
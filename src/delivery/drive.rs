@@ -0,0 +1,189 @@
+use crate::config::config::DriveConfig;
+use crate::utils::functions::mime_type;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+const DRIVE_SCOPE: &str = "https://www.googleapis.com/auth/drive";
+const FILES_URL: &str = "https://www.googleapis.com/drive/v3/files";
+const UPLOAD_URL: &str = "https://www.googleapis.com/upload/drive/v3/files";
+const BOUNDARY: &str = "ribav-scraper-boundary";
+
+#[derive(Debug, Deserialize)]
+struct ServiceAccountKey {
+    client_email: String,
+    private_key: String,
+    token_uri: String,
+}
+
+#[derive(Debug, Serialize)]
+struct Claims<'a> {
+    iss: &'a str,
+    scope: &'a str,
+    aud: &'a str,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct TokenResponse {
+    access_token: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileList {
+    files: Vec<DriveFile>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DriveFile {
+    id: String,
+}
+
+/// Uploads the files into the configured Drive folder, replacing the content of files that
+/// already exist there under the same name so collaborators keep their links and comments.
+pub async fn upload_to_drive(
+    client: &Client,
+    config: &DriveConfig,
+    files: &[PathBuf],
+) -> Result<()> {
+    let token = access_token(client, &config.credentials).await?;
+
+    for file in files {
+        let name = file
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid file to upload: {}", file.display()))?
+            .to_string_lossy()
+            .to_string();
+        let bytes = std::fs::read(file)
+            .with_context(|| format!("Failed to read {} for upload", file.display()))?;
+
+        let request = match find_file(client, &token, &config.folder_id, &name).await? {
+            Some(id) => client
+                .patch(format!("{}/{}", UPLOAD_URL, id))
+                .query(&[("uploadType", "media"), ("supportsAllDrives", "true")])
+                .header("content-type", mime_type(file))
+                .body(bytes),
+            None => {
+                let metadata = serde_json::json!({
+                    "name": name,
+                    "parents": [config.folder_id],
+                });
+
+                let mut body = format!(
+                    "--{BOUNDARY}\r\nContent-Type: application/json; charset=UTF-8\r\n\r\n{metadata}\r\n--{BOUNDARY}\r\nContent-Type: {}\r\n\r\n",
+                    mime_type(file)
+                )
+                .into_bytes();
+                body.extend(bytes);
+                body.extend(format!("\r\n--{BOUNDARY}--").into_bytes());
+
+                client
+                    .post(UPLOAD_URL)
+                    .query(&[("uploadType", "multipart"), ("supportsAllDrives", "true")])
+                    .header(
+                        "content-type",
+                        format!("multipart/related; boundary={BOUNDARY}"),
+                    )
+                    .body(body)
+            }
+        };
+
+        let response = request.bearer_auth(&token).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to upload {} to Drive: {}",
+                name,
+                response.status()
+            ));
+        }
+
+        info!("Uploaded {} to Drive", name);
+    }
+
+    Ok(())
+}
+
+async fn access_token(client: &Client, credentials: &Path) -> Result<String> {
+    let key: ServiceAccountKey =
+        serde_json::from_str(&std::fs::read_to_string(credentials).with_context(|| {
+            format!("Failed to read Drive credentials {}", credentials.display())
+        })?)
+        .context("Invalid service-account key file")?;
+
+    let now = Utc::now().timestamp();
+    let claims = Claims {
+        iss: &key.client_email,
+        scope: DRIVE_SCOPE,
+        aud: &key.token_uri,
+        iat: now,
+        exp: now + 3600,
+    };
+    let assertion = encode(
+        &Header::new(Algorithm::RS256),
+        &claims,
+        &EncodingKey::from_rsa_pem(key.private_key.as_bytes())?,
+    )?;
+
+    let response = client
+        .post(&key.token_uri)
+        .form(&[
+            ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+            ("assertion", assertion.as_str()),
+        ])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to obtain a Drive access token: {}",
+            response.status()
+        ));
+    }
+
+    Ok(response.json::<TokenResponse>().await?.access_token)
+}
+
+async fn find_file(
+    client: &Client,
+    token: &str,
+    folder_id: &str,
+    name: &str,
+) -> Result<Option<String>> {
+    let query = format!(
+        "name = '{}' and '{}' in parents and trashed = false",
+        name.replace('\\', "\\\\").replace('\'', "\\'"),
+        folder_id
+    );
+
+    let response = client
+        .get(FILES_URL)
+        .query(&[
+            ("q", query.as_str()),
+            ("fields", "files(id)"),
+            ("supportsAllDrives", "true"),
+            ("includeItemsFromAllDrives", "true"),
+        ])
+        .bearer_auth(token)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Failed to search the Drive folder: {}",
+            response.status()
+        ));
+    }
+
+    Ok(response
+        .json::<FileList>()
+        .await?
+        .files
+        .into_iter()
+        .next()
+        .map(|file| file.id))
+}
@@ -0,0 +1,64 @@
+use crate::config::config::EmailConfig;
+use crate::utils::functions::mime_type;
+use anyhow::{Context, Result};
+use lettre::message::header::ContentType;
+use lettre::message::{Attachment, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use std::path::PathBuf;
+use tracing::info;
+
+/// Sends the documents generated or updated during the run as attachments of a single email.
+pub async fn send_documents(config: &EmailConfig, files: &[PathBuf]) -> Result<()> {
+    let mut builder = Message::builder()
+        .from(config.from.parse().context("Invalid email sender")?)
+        .subject(format!("Ribav : {} document(s) mis à jour", files.len()));
+    for recipient in &config.to {
+        builder = builder.to(recipient
+            .parse()
+            .with_context(|| format!("Invalid email recipient {}", recipient))?);
+    }
+
+    let names = files
+        .iter()
+        .filter_map(|file| file.file_name())
+        .map(|name| format!("- {}", name.to_string_lossy()))
+        .collect::<Vec<_>>()
+        .join("\n");
+    let mut multipart = MultiPart::mixed().singlepart(SinglePart::plain(format!(
+        "Documents générés lors de ce passage :\n\n{}\n",
+        names
+    )));
+
+    for file in files {
+        let name = file
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid file to attach: {}", file.display()))?
+            .to_string_lossy()
+            .to_string();
+        let bytes = std::fs::read(file)
+            .with_context(|| format!("Failed to read {} for email", file.display()))?;
+
+        multipart = multipart
+            .singlepart(Attachment::new(name).body(bytes, ContentType::parse(mime_type(file))?));
+    }
+
+    let email = builder.multipart(multipart)?;
+
+    let mailer = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?
+        .port(config.smtp_port.unwrap_or(587))
+        .credentials(Credentials::new(
+            config.username.to_owned(),
+            config.password.to_owned(),
+        ))
+        .build();
+
+    mailer.send(email).await.context("Failed to send email")?;
+    info!(
+        "Emailed {} document(s) to {}",
+        files.len(),
+        config.to.join(", ")
+    );
+
+    Ok(())
+}
@@ -0,0 +1,10 @@
+#[cfg(feature = "drive")]
+pub mod drive;
+#[cfg(feature = "email")]
+pub mod email;
+#[cfg(feature = "upload")]
+pub mod s3;
+#[cfg(feature = "upload")]
+pub mod upload;
+#[cfg(feature = "upload")]
+pub mod webdav;
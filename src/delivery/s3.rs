@@ -0,0 +1,102 @@
+use crate::config::config::S3Config;
+use crate::export::manifest::sha256_hex;
+use anyhow::Result;
+use chrono::Utc;
+use hmac::{Hmac, KeyInit, Mac};
+use reqwest::{Client, Url};
+use sha2::Sha256;
+
+/// Uploads one object with a path-style, SigV4-signed PUT request, which works for AWS and
+/// S3-compatible providers (MinIO, Scaleway, OVH...).
+pub async fn put_object(
+    client: &Client,
+    config: &S3Config,
+    name: &str,
+    bytes: Vec<u8>,
+) -> Result<()> {
+    let endpoint = Url::parse(&config.endpoint)?;
+    let host = match endpoint.port() {
+        Some(port) => format!("{}:{}", endpoint.host_str().unwrap_or_default(), port),
+        None => endpoint.host_str().unwrap_or_default().to_string(),
+    };
+
+    let key = format!("{}{}", config.prefix, name);
+    let canonical_uri = format!("/{}/{}", uri_encode(&config.bucket), uri_encode(&key));
+
+    let now = Utc::now();
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date_stamp = now.format("%Y%m%d").to_string();
+    let payload_hash = sha256_hex(&bytes);
+
+    let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+    let canonical_request = format!(
+        "PUT\n{canonical_uri}\n\nhost:{host}\nx-amz-content-sha256:{payload_hash}\nx-amz-date:{amz_date}\n\n{signed_headers}\n{payload_hash}"
+    );
+
+    let scope = format!("{}/{}/s3/aws4_request", date_stamp, config.region);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+        sha256_hex(canonical_request.as_bytes())
+    );
+
+    let signing_key = [config.region.as_str(), "s3", "aws4_request"].iter().fold(
+        hmac_sha256(
+            format!("AWS4{}", config.secret_key).as_bytes(),
+            date_stamp.as_bytes(),
+        ),
+        |key, part| hmac_sha256(&key, part.as_bytes()),
+    );
+    let signature = hmac_sha256(&signing_key, string_to_sign.as_bytes())
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+
+    let authorization = format!(
+        "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+        config.access_key
+    );
+
+    let url = Url::parse(&format!(
+        "{}://{}{}",
+        endpoint.scheme(),
+        host,
+        canonical_uri
+    ))?;
+
+    let response = client
+        .put(url)
+        .header("x-amz-content-sha256", payload_hash)
+        .header("x-amz-date", amz_date)
+        .header("authorization", authorization)
+        .body(bytes)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Non-success HTTP status: {}",
+            response.status()
+        ));
+    }
+
+    Ok(())
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+// SigV4 requires every byte but the unreserved characters (and '/') to be percent-encoded
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
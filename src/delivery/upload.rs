@@ -0,0 +1,31 @@
+use crate::config::config::UploadConfig;
+use crate::delivery::{s3, webdav};
+use anyhow::{Context, Result};
+use reqwest::Client;
+use std::path::PathBuf;
+use tracing::info;
+
+/// Pushes every file to the configured remote storage.
+pub async fn upload_files(client: &Client, config: &UploadConfig, files: &[PathBuf]) -> Result<()> {
+    for file in files {
+        let name = file
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid file to upload: {}", file.display()))?
+            .to_string_lossy()
+            .to_string();
+        let bytes = std::fs::read(file)
+            .with_context(|| format!("Failed to read {} for upload", file.display()))?;
+
+        match config {
+            UploadConfig::S3(s3_config) => s3::put_object(client, s3_config, &name, bytes).await,
+            UploadConfig::WebDav(webdav_config) => {
+                webdav::put_file(client, webdav_config, &name, bytes).await
+            }
+        }
+        .with_context(|| format!("Failed to upload {}", file.display()))?;
+
+        info!("Uploaded {}", name);
+    }
+
+    Ok(())
+}
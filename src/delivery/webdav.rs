@@ -0,0 +1,31 @@
+use crate::config::config::WebDavConfig;
+use anyhow::Result;
+use reqwest::{Client, Url};
+
+pub async fn put_file(
+    client: &Client,
+    config: &WebDavConfig,
+    name: &str,
+    bytes: Vec<u8>,
+) -> Result<()> {
+    let mut url = Url::parse(&config.url)?;
+    url.path_segments_mut()
+        .map_err(|_| anyhow::anyhow!("WebDAV url cannot be a base: {}", config.url))?
+        .pop_if_empty()
+        .push(name);
+
+    let mut request = client.put(url).body(bytes);
+    if let Some(ref username) = config.username {
+        request = request.basic_auth(username, config.password.as_ref());
+    }
+
+    let response = request.send().await?;
+    if !response.status().is_success() {
+        return Err(anyhow::anyhow!(
+            "Non-success HTTP status: {}",
+            response.status()
+        ));
+    }
+
+    Ok(())
+}
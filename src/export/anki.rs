@@ -0,0 +1,74 @@
+use crate::config::config::{AnkiFront, ExportConfig};
+use crate::export::summary::summarize_question;
+use crate::post::post::{Post, PostMessage};
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+/// File headers understood by Anki's text importer.
+const HEADER: &str = "#separator:tab\n#html:true\n#tags column:3\n";
+
+/// Appends one card per answered exchange of the topic: the question on the front, the answers
+/// on the back and the category as tag.
+pub fn append_cards(post: &Post, path: &Path, options: &ExportConfig) -> Result<()> {
+    let mut tsv = String::new();
+    if !path.exists() {
+        tsv.push_str(HEADER);
+    }
+
+    // Hierarchical in Anki: "Halakha::Chabbat"
+    let tag = [Some(&post.category.forum), post.category.subforum.as_ref()]
+        .into_iter()
+        .flatten()
+        .map(|part| part.split_whitespace().collect::<Vec<_>>().join("_"))
+        .collect::<Vec<_>>()
+        .join("::");
+    for group in post.exchange_groups() {
+        let (answers, questions): (Vec<&PostMessage>, Vec<&PostMessage>) =
+            group.into_iter().partition(|message| message.is_answer());
+        if answers.is_empty() || questions.is_empty() {
+            continue;
+        }
+
+        let front = match options.anki.front {
+            AnkiFront::Question => questions
+                .iter()
+                .map(|question| html(&question.text()))
+                .collect::<Vec<_>>()
+                .join("<br><br>"),
+            AnkiFront::Summary => {
+                let question = questions[0];
+                html(&summarize_question(
+                    &question.runs(),
+                    question.summary.as_deref(),
+                    &post.title,
+                    options.question_summary,
+                ))
+            }
+        };
+        let back = answers
+            .iter()
+            .map(|answer| html(&answer.text()))
+            .collect::<Vec<_>>()
+            .join("<br><br>");
+
+        tsv.push_str(&format!("{}\t{}\t{}\n", front, back, tag));
+    }
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(tsv.as_bytes()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// A field of the deck: escaped HTML on a single line.
+fn html(text: &str) -> String {
+    text.trim()
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('\t', " ")
+        .replace('\n', "<br>")
+}
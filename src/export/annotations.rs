@@ -0,0 +1,50 @@
+use crate::config::config::{AnnotationMode, AnnotationsConfig};
+use crate::export::docx::{push_comment, push_footnote};
+use anyhow::{Context, Result};
+use docx_rust::document::Run;
+use docx_rust::Docx;
+use std::collections::BTreeMap;
+
+/// Author shown on the margin comments holding the notes.
+const ANNOTATION_AUTHOR: &str = "Annotations";
+
+/// Editor notes kept next to the generated documents, keyed by forum message id.
+#[derive(Debug, Clone)]
+pub struct Annotations {
+    mode: AnnotationMode,
+    notes: BTreeMap<String, String>,
+}
+
+impl Annotations {
+    pub fn load(config: &AnnotationsConfig) -> Result<Self> {
+        let content = std::fs::read_to_string(&config.path).with_context(|| {
+            format!("Failed to read annotations file {}", config.path.display())
+        })?;
+        let notes = serde_yaml::from_str(&content).with_context(|| {
+            format!("Failed to parse annotations file {}", config.path.display())
+        })?;
+
+        Ok(Self {
+            mode: config.mode,
+            notes,
+        })
+    }
+
+    /// Appends the note of the message, if any, at the end of its runs.
+    pub fn annotate<'a>(
+        &self,
+        id: Option<&str>,
+        mut runs: Vec<Run<'a>>,
+        docx: &mut Docx<'_>,
+    ) -> Vec<Run<'a>> {
+        let Some(note) = id.and_then(|id| self.notes.get(id)) else {
+            return runs;
+        };
+
+        runs.push(match self.mode {
+            AnnotationMode::Comment => push_comment(docx, ANNOTATION_AUTHOR, note.to_owned()),
+            AnnotationMode::Footnote => push_footnote(docx, note.to_owned()),
+        });
+        runs
+    }
+}
@@ -0,0 +1,63 @@
+use crate::export::custom_properties::{read_part, replace_part, xml_unescape};
+use crate::utils::functions::xml_escape;
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use regex::Regex;
+use std::path::Path;
+
+const CORE_PART: &str = "docProps/core.xml";
+const CONTENT_TYPE_CORE: &str = "application/vnd.openxmlformats-package.core-properties+xml";
+const REL_CORE: &str =
+    "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties";
+
+/// Metadata shown by Word (File > Info) and the file managers.
+#[derive(Debug, Clone)]
+pub struct CoreProperties {
+    pub title: String,
+    pub creator: String,
+    pub keywords: Vec<String>,
+    /// W3CDTF creation date of the document, now when it is new
+    pub created: Option<String>,
+}
+
+/// Creation date recorded in the docx at `path`.
+///
+/// docx-rust drops the dates when it rewrites a document, so it has to be read before.
+pub fn read_created(path: &Path) -> Result<Option<String>> {
+    let created = Regex::new(r"<dcterms:created[^>]*>([^<]+)</dcterms:created>")?;
+    Ok(read_part(path, CORE_PART)?.and_then(|xml| {
+        created
+            .captures(&xml)
+            .map(|captures| captures[1].to_owned())
+    }))
+}
+
+/// Title recorded in the docx at `path`, the category of the generated documents.
+pub fn read_title(path: &Path) -> Result<Option<String>> {
+    let title = Regex::new(r"<dc:title>([^<]*)</dc:title>")?;
+    Ok(read_part(path, CORE_PART)?.and_then(|xml| {
+        title
+            .captures(&xml)
+            .map(|captures| xml_unescape(&captures[1]))
+    }))
+}
+
+/// Writes the core properties of the docx at `path`, the modification date being now.
+///
+/// docx-rust knows neither of the dates, so the part is written after it.
+pub fn set_core_properties(path: &Path, properties: &CoreProperties) -> Result<()> {
+    let now = Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true);
+    let created = properties.created.as_deref().unwrap_or(&now);
+
+    let xml = format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<cp:coreProperties xmlns:cp="http://schemas.openxmlformats.org/package/2006/metadata/core-properties" xmlns:dc="http://purl.org/dc/elements/1.1/" xmlns:dcterms="http://purl.org/dc/terms/" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance"><dc:title>{}</dc:title><dc:creator>{}</dc:creator><cp:keywords>{}</cp:keywords><cp:lastModifiedBy>ribav-scraper</cp:lastModifiedBy><dcterms:created xsi:type="dcterms:W3CDTF">{}</dcterms:created><dcterms:modified xsi:type="dcterms:W3CDTF">{}</dcterms:modified></cp:coreProperties>"#,
+        xml_escape(&properties.title),
+        xml_escape(&properties.creator),
+        xml_escape(&properties.keywords.join(", ")),
+        xml_escape(created),
+        now
+    );
+
+    replace_part(path, CORE_PART, &xml, CONTENT_TYPE_CORE, REL_CORE)
+}
@@ -0,0 +1,199 @@
+use crate::export::docx::push_footnote;
+use docx_rust::document::{Run, RunContent, TextSpace};
+use docx_rust::Docx;
+use regex::Regex;
+use std::collections::HashSet;
+use std::sync::{Arc, LazyLock, RwLock};
+
+/// "voir le sujet « Le libre arbitre »", "cf. le fil allumer une bougie avant chabbat", the
+/// title quoted or running to the end of the sentence
+static MENTION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)\b(?:voir|voyez|cf\.?|consulter|consultez)\s+(?:aussi\s+|également\s+)?(?:le|mon|notre|ce|un)\s+(?:sujet|fil|topic)\s*(?:intitulé\s*)?:?\s*(?:[«"“]\s*(?P<quoted>[^»"”]+?)\s*[»"”]|(?P<bare>[^.;!?\n()«»"“”]+))"#,
+    )
+    .expect("valid regex")
+});
+
+/// Words left out of the comparison of the titles
+const STOP_WORDS: [&str; 17] = [
+    "le", "la", "les", "l", "de", "du", "des", "d", "un", "une", "et", "a", "à", "au", "aux", "en",
+    "sur",
+];
+
+/// Share of the words of a title a mention must have for the title to be the one referenced
+const MIN_SCORE: f64 = 0.75;
+/// Share of the words of a mention the title must have, a mention running to the end of its
+/// sentence having a few more words than the title
+const MIN_MENTION_SHARE: f64 = 0.5;
+
+// Topics the mentions are resolved to, see `set_known_topics`
+static KNOWN_TOPICS: RwLock<Option<Arc<Vec<KnownTopic>>>> = RwLock::new(None);
+
+#[derive(Debug, Clone)]
+struct KnownTopic {
+    title: String,
+    url: String,
+    words: Vec<String>,
+}
+
+/// Resolves the topics from now on referenced by title, `export.cross_references`, to the
+/// `topics` given by title and url: the ones of the archive and of the run.
+pub fn set_known_topics<I, S>(topics: I)
+where
+    I: IntoIterator<Item = (S, S)>,
+    S: Into<String>,
+{
+    let topics = topics
+        .into_iter()
+        .map(|(title, url)| {
+            let title = title.into();
+            KnownTopic {
+                words: significant_words(&title),
+                title,
+                url: url.into(),
+            }
+        })
+        .filter(|topic| !topic.words.is_empty())
+        .collect();
+    *KNOWN_TOPICS.write().unwrap() = Some(Arc::new(topics));
+}
+
+/// Adds a footnote with the url of the topic after each mention of another topic by its title,
+/// once per topic referenced.
+#[derive(Debug, Clone)]
+pub struct CrossReferences {
+    topics: Arc<Vec<KnownTopic>>,
+    /// Url of the topic being exported, its mentions of itself being left alone
+    own_url: String,
+    seen: HashSet<String>,
+}
+
+impl CrossReferences {
+    /// The references of the topic at `own_url` to the known topics, `None` when none are known.
+    pub fn new(own_url: &str) -> Option<Self> {
+        let topics = KNOWN_TOPICS.read().unwrap().clone()?;
+        Some(Self {
+            topics,
+            own_url: own_url.to_owned(),
+            seen: HashSet::new(),
+        })
+    }
+
+    /// Title and url of the known topic `mention` refers to, the one with the most of its words.
+    pub fn resolve(&self, mention: &str) -> Option<(&str, &str)> {
+        let mention = significant_words(mention);
+        if mention.is_empty() {
+            return None;
+        }
+        self.topics
+            .iter()
+            .filter(|topic| topic.url != self.own_url)
+            .filter_map(|topic| {
+                let found = found_words(&topic.words, &mention) as f64;
+                let score = found / topic.words.len() as f64;
+                (score >= MIN_SCORE && found / mention.len() as f64 >= MIN_MENTION_SHARE)
+                    .then_some((score, topic))
+            })
+            // The longest title among the best, "Le libre arbitre (suite)" over "Le libre arbitre"
+            .max_by(|(a, first), (b, second)| {
+                a.total_cmp(b)
+                    .then(first.words.len().cmp(&second.words.len()))
+            })
+            .map(|(_, topic)| (topic.title.as_str(), topic.url.as_str()))
+    }
+
+    pub fn annotate<'a>(&mut self, runs: Vec<Run<'a>>, docx: &mut Docx<'_>) -> Vec<Run<'a>> {
+        let mut annotated = Vec::with_capacity(runs.len());
+
+        for run in runs {
+            // Only plain text runs are split, breaks and references are kept as is
+            let mut text = match run.content.as_slice() {
+                [RunContent::Text(text)] => text.text.to_string(),
+                _ => {
+                    annotated.push(run);
+                    continue;
+                }
+            };
+
+            while let Some((end, title, url)) = self.next_reference(&text) {
+                let rest = text.split_off(end);
+                annotated.push(with_text(&run, text));
+                annotated.push(push_footnote(
+                    docx,
+                    format!("Voir le sujet «\u{A0}{}\u{A0}» : {}", title, url),
+                ));
+                self.seen.insert(url);
+                text = rest;
+            }
+
+            annotated.push(with_text(&run, text));
+        }
+
+        annotated
+    }
+
+    /// End of the first mention of a topic not referenced yet, after its closing quote, with
+    /// the title and url of the topic.
+    fn next_reference(&self, text: &str) -> Option<(usize, String, String)> {
+        MENTION.captures_iter(text).find_map(|captures| {
+            let (mention, end) = match (captures.name("quoted"), captures.name("bare")) {
+                (Some(quoted), _) => (quoted.as_str(), captures.get(0)?.end()),
+                (None, Some(bare)) => {
+                    let mention = bare.as_str().trim_end();
+                    (mention, bare.start() + mention.len())
+                }
+                (None, None) => return None,
+            };
+            let (title, url) = self.resolve(mention)?;
+            (!self.seen.contains(url)).then(|| (end, title.to_owned(), url.to_owned()))
+        })
+    }
+}
+
+/// Lowercased words of `text` that tell titles apart, elisions split: "l'âme" gives "âme".
+fn significant_words(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .filter(|word| !word.is_empty() && !STOP_WORDS.contains(&word.as_str()))
+        .collect()
+}
+
+/// Words of the title found in the mention, a letter of difference allowed in the longer words.
+fn found_words(title: &[String], mention: &[String]) -> usize {
+    title
+        .iter()
+        .filter(|word| {
+            mention
+                .iter()
+                .any(|other| other == *word || (word.chars().count() >= 5 && one_edit(word, other)))
+        })
+        .count()
+}
+
+/// Whether `a` and `b` differ by one letter substituted, added or removed.
+fn one_edit(a: &str, b: &str) -> bool {
+    let (a, b) = (a.chars().collect::<Vec<_>>(), b.chars().collect::<Vec<_>>());
+    let (shorter, longer) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    if longer.len() - shorter.len() > 1 {
+        return false;
+    }
+    let prefix = shorter
+        .iter()
+        .zip(&longer)
+        .take_while(|(a, b)| a == b)
+        .count();
+    if prefix == longer.len() {
+        return true;
+    }
+    // The letter after the common start substituted, or added to the longer word
+    let skipped = usize::from(shorter.len() == longer.len());
+    shorter[(prefix + skipped).min(shorter.len())..] == longer[prefix + 1..]
+}
+
+fn with_text<'a>(run: &Run<'a>, text: String) -> Run<'a> {
+    Run {
+        content: Vec::new(),
+        ..run.clone()
+    }
+    .push_text((text, TextSpace::Preserve))
+}
@@ -0,0 +1,62 @@
+use crate::config::config::ExportConfig;
+use crate::post::post::Post;
+use crate::utils::functions::anonymize_author;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::path::Path;
+
+const MESSAGES_HEADER: [&str; 7] = ["topic", "url", "category", "date", "author", "role", "text"];
+
+/// Appends one CSV line to `out`, quoting the fields that need it (RFC 4180).
+pub fn push_record<S: AsRef<str>>(out: &mut String, fields: &[S]) {
+    for (i, field) in fields.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        let field = field.as_ref();
+        if field.contains([',', '"', '\n', '\r']) {
+            out.push('"');
+            out.push_str(&field.replace('"', "\"\""));
+            out.push('"');
+        } else {
+            out.push_str(field);
+        }
+    }
+    out.push_str("\r\n");
+}
+
+/// Appends one row per message of the topic to the CSV at `path`, writing the header first when
+/// the file is new.
+pub fn append_messages(post: &Post, path: &Path, options: &ExportConfig) -> Result<()> {
+    let mut csv = String::new();
+    if !path.exists() {
+        push_record(&mut csv, &MESSAGES_HEADER);
+    }
+
+    for message in post.messages.iter().flatten() {
+        let role = if message.is_answer() {
+            "answer"
+        } else {
+            "question"
+        };
+        push_record(
+            &mut csv,
+            &[
+                post.title.as_str(),
+                post.url.as_str(),
+                post.category.to_string().as_str(),
+                options.dates.format(&message.date).as_str(),
+                anonymize_author(&message.author).as_str(),
+                role,
+                message.text().trim(),
+            ],
+        );
+    }
+
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(csv.as_bytes()))
+        .with_context(|| format!("Failed to write {}", path.display()))
+}
@@ -0,0 +1,207 @@
+use crate::utils::functions::xml_escape;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{ZipArchive, ZipWriter};
+
+/// Set on every document written by the scraper.
+pub const GENERATED_PROPERTY: &str = "RibavGenerated";
+/// Attribution line of `export.notice`.
+pub const ATTRIBUTION_PROPERTY: &str = "Attribution";
+/// Set to true by the editors in Word (File > Properties > Custom) to protect a document.
+pub const EDITED_PROPERTY: &str = "ManuallyEdited";
+
+const CUSTOM_PART: &str = "docProps/custom.xml";
+const CONTENT_TYPES_PART: &str = "[Content_Types].xml";
+const RELS_PART: &str = "_rels/.rels";
+
+const CONTENT_TYPE_CUSTOM: &str =
+    "application/vnd.openxmlformats-officedocument.custom-properties+xml";
+const REL_CUSTOM: &str =
+    "http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties";
+
+/// Custom properties of the docx at `path`, empty when it has none.
+pub fn read_custom_properties(path: &Path) -> Result<BTreeMap<String, String>> {
+    let Some(xml) = read_part(path, CUSTOM_PART)? else {
+        return Ok(BTreeMap::new());
+    };
+
+    let property =
+        Regex::new(r#"(?s)<property\b[^>]*\bname="([^"]*)"[^>]*>\s*<vt:\w+>([^<]*)</vt:\w+>"#)?;
+    Ok(property
+        .captures_iter(&xml)
+        .map(|captures| (xml_unescape(&captures[1]), xml_unescape(captures[2].trim())))
+        .collect())
+}
+
+/// Why a document with these custom properties must not be rewritten: not generated by the
+/// scraper, or flagged as manually edited.
+pub fn protection_reason(properties: &BTreeMap<String, String>) -> Option<String> {
+    let flag = |name: &str| {
+        properties
+            .get(name)
+            .is_some_and(|value| value.eq_ignore_ascii_case("true") || value == "1")
+    };
+
+    if !flag(GENERATED_PROPERTY) {
+        Some(format!(
+            "no {} property, not generated by the scraper",
+            GENERATED_PROPERTY
+        ))
+    } else if flag(EDITED_PROPERTY) {
+        Some(format!("{} is set", EDITED_PROPERTY))
+    } else {
+        None
+    }
+}
+
+/// Writes `properties` into the docx at `path`, marked as generated.
+///
+/// docx-rust drops the custom properties part when it rewrites a document, so the properties
+/// have to be read before and written back after.
+pub fn mark_generated(path: &Path, mut properties: BTreeMap<String, String>) -> Result<()> {
+    properties.insert(GENERATED_PROPERTY.to_owned(), "true".to_owned());
+    properties
+        .entry(EDITED_PROPERTY.to_owned())
+        .or_insert_with(|| "false".to_owned());
+
+    replace_part(
+        path,
+        CUSTOM_PART,
+        &custom_xml(&properties),
+        CONTENT_TYPE_CUSTOM,
+        REL_CUSTOM,
+    )
+}
+
+/// Content of the part `name` of the docx at `path`, `None` when it has no such part.
+pub(crate) fn read_part(path: &Path, name: &str) -> Result<Option<String>> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive =
+        ZipArchive::new(file).with_context(|| format!("Invalid docx {}", path.display()))?;
+
+    let mut xml = String::new();
+    match archive.by_name(name) {
+        Ok(mut part) => part
+            .read_to_string(&mut xml)
+            .with_context(|| format!("Failed to read {} of {}", name, path.display()))?,
+        Err(_) => return Ok(None),
+    };
+
+    Ok(Some(xml))
+}
+
+/// Writes `xml` as the part `name` of the docx at `path`, declaring its content type and its
+/// package relationship when missing.
+pub(crate) fn replace_part(
+    path: &Path,
+    name: &str,
+    xml: &str,
+    content_type: &str,
+    relationship: &str,
+) -> Result<()> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive =
+        ZipArchive::new(file).with_context(|| format!("Invalid docx {}", path.display()))?;
+
+    let tmp_path = path.with_extension("docx.tmp");
+    let mut zip = ZipWriter::new(
+        File::create(&tmp_path)
+            .with_context(|| format!("Failed to create {}", tmp_path.display()))?,
+    );
+    let options = SimpleFileOptions::default();
+
+    for i in 0..archive.len() {
+        let mut entry = archive.by_index(i)?;
+        let entry_name = entry.name().to_owned();
+        match entry_name.as_str() {
+            part if part == name => continue,
+            CONTENT_TYPES_PART | RELS_PART => {
+                let mut entry_xml = String::new();
+                entry.read_to_string(&mut entry_xml)?;
+                let entry_xml = if entry_name == CONTENT_TYPES_PART {
+                    with_content_type(entry_xml, name, content_type)
+                } else {
+                    with_relationship(entry_xml, name, relationship)
+                };
+                zip.start_file(entry_name, options)?;
+                zip.write_all(entry_xml.as_bytes())?;
+            }
+            _ => zip.raw_copy_file(entry)?,
+        }
+    }
+
+    zip.start_file(name, options)?;
+    zip.write_all(xml.as_bytes())?;
+    zip.finish()
+        .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+
+    std::fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to replace {}", path.display()))
+}
+
+pub(crate) fn xml_unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&amp;", "&")
+}
+
+fn with_content_type(xml: String, part: &str, content_type: &str) -> String {
+    if xml.contains(&format!("/{}", part)) {
+        return xml;
+    }
+    xml.replace(
+        "</Types>",
+        &format!(
+            r#"<Override PartName="/{}" ContentType="{}"/></Types>"#,
+            part, content_type
+        ),
+    )
+}
+
+fn with_relationship(xml: String, part: &str, relationship: &str) -> String {
+    if xml.contains(relationship) {
+        return xml;
+    }
+    // e.g. rIdcustom for docProps/custom.xml
+    let id = part
+        .rsplit('/')
+        .next()
+        .and_then(|file| file.split('.').next())
+        .unwrap_or(part);
+    xml.replace(
+        "</Relationships>",
+        &format!(
+            r#"<Relationship Id="rId{}" Type="{}" Target="{}"/></Relationships>"#,
+            id, relationship, part
+        ),
+    )
+}
+
+fn custom_xml(properties: &BTreeMap<String, String>) -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<Properties xmlns="http://schemas.openxmlformats.org/officeDocument/2006/custom-properties" xmlns:vt="http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes">"#,
+    );
+    // Word numbers the custom properties from 2
+    for (pid, (name, value)) in properties.iter().enumerate() {
+        let value = match value.as_str() {
+            "true" | "false" => format!("<vt:bool>{}</vt:bool>", value),
+            _ => format!("<vt:lpwstr>{}</vt:lpwstr>", xml_escape(value)),
+        };
+        xml.push_str(&format!(
+            r#"<property fmtid="{{D5CDD505-2E9C-101B-9397-08002B2CF9AE}}" pid="{}" name="{}">{}</property>"#,
+            pid + 2,
+            xml_escape(name),
+            value
+        ));
+    }
+    xml.push_str("</Properties>");
+    xml
+}
@@ -0,0 +1,367 @@
+use crate::parser::parser::MessageItem;
+use docx_rust::content_type::OverrideContentType;
+use docx_rust::document::{
+    AbstractNum, AbstractNumId, AnnotationRef, BodyContent, BreakType, Comment, CommentReference,
+    Comments, FootNote, FootNotes, FootnoteRef, FootnoteReference, Level, LevelStart, LevelText,
+    MultiLevelType, NoteSeparator, Nsid, Num, NumFmt, Numbering, Paragraph, ParagraphContent, Run,
+    RunContent, Table, TableCell, TableCellContent, TableGrid, TableRow, TextSpace,
+};
+use docx_rust::formatting::{
+    CharacterProperty, IndentLevel, KeepLines, KeepNext, NumberingId, NumberingProperty, VertAlign,
+    VertAlignType,
+};
+use docx_rust::{Docx, DocxError};
+use std::path::Path;
+
+/// Half of the text width of an A4 page with the default margins, in twips
+const TABLE_HALF_WIDTH: isize = 4819;
+
+const CONTENT_TYPE_FOOTNOTES: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml";
+const CONTENT_TYPE_COMMENTS: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.comments+xml";
+const CONTENT_TYPE_NUMBERING: &str =
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.numbering+xml";
+
+/// Abstract and instance id of the numbering of the topics and exchanges
+const HEADING_NUMBERING_ID: isize = 965;
+
+/// Whether `error` is the file being open in another program: Word locks the documents it has
+/// open on Windows.
+pub fn is_file_in_use(error: &std::io::Error) -> bool {
+    // ERROR_SHARING_VIOLATION and ERROR_LOCK_VIOLATION, any other denial is a real one
+    cfg!(windows) && matches!(error.raw_os_error(), Some(32) | Some(33))
+}
+
+/// Whether `error` comes from a file open in another program, see [`is_file_in_use`].
+pub fn is_in_use_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        let io = match cause.downcast_ref::<DocxError>() {
+            Some(DocxError::IO(io)) => Some(io),
+            _ => cause.downcast_ref::<std::io::Error>(),
+        };
+        io.is_some_and(is_file_in_use)
+    })
+}
+
+/// `error` of docx-rust on the document at `path`, with what to do about it.
+pub fn docx_error(error: DocxError, action: &str, path: &Path) -> anyhow::Error {
+    let hint = match error {
+        DocxError::IO(ref e) if is_file_in_use(e) => "close the file in Word and re-run export",
+        DocxError::IO(_) => "check that the output directory is writable",
+        DocxError::Xml(_) | DocxError::Zip(_) => {
+            "the document is corrupted, the next run moves it to the quarantine directory"
+        }
+    };
+    anyhow::Error::new(error).context(format!("Failed to {} {}, {}", action, path.display(), hint))
+}
+
+/// Declares the content type of an optional part (footnotes, comments...) unless already there.
+pub fn ensure_content_type(docx: &mut Docx<'_>, part: &str, ty: &'static str) {
+    if !docx.content_types.overrides.iter().any(|o| o.part == part) {
+        docx.content_types.overrides.push(OverrideContentType {
+            part: part.to_owned().into(),
+            ty: ty.into(),
+        });
+    }
+}
+
+fn superscript() -> CharacterProperty<'static> {
+    CharacterProperty {
+        vertical_align: Some(VertAlign {
+            value: Some(VertAlignType::Superscript),
+        }),
+        ..Default::default()
+    }
+}
+
+/// Adds a footnote holding `text` and returns the run referencing it from the document.
+pub fn push_footnote(docx: &mut Docx<'_>, text: String) -> Run<'static> {
+    ensure_content_type(docx, "/word/footnotes.xml", CONTENT_TYPE_FOOTNOTES);
+
+    let footnotes = docx.footnotes.get_or_insert_with(|| FootNotes {
+        // Word expects the separator lines as the first two notes
+        content: vec![
+            FootNote {
+                ty: Some(NoteSeparator::Separator),
+                id: Some(-1),
+                content: vec![BodyContent::Paragraph(Paragraph::default().push(Run {
+                    content: vec![RunContent::Separator(Default::default())],
+                    ..Default::default()
+                }))],
+            },
+            FootNote {
+                ty: Some(NoteSeparator::ContinuationSeparator),
+                id: Some(0),
+                content: vec![BodyContent::Paragraph(Paragraph::default().push(Run {
+                    content: vec![RunContent::ContinuationSeparator(Default::default())],
+                    ..Default::default()
+                }))],
+            },
+        ],
+    });
+
+    let id = footnotes
+        .content
+        .iter()
+        .filter_map(|note| note.id)
+        .max()
+        .unwrap_or(0)
+        .max(0)
+        + 1;
+
+    footnotes.content.push(FootNote {
+        ty: None,
+        id: Some(id),
+        content: vec![BodyContent::Paragraph(
+            Paragraph::default()
+                .push(Run {
+                    property: Some(superscript()),
+                    content: vec![RunContent::FootnoteRef(FootnoteRef)],
+                    ..Default::default()
+                })
+                .push(Run::default().push_text((format!(" {}", text), TextSpace::Preserve))),
+        )],
+    });
+
+    Run {
+        property: Some(superscript()),
+        content: vec![RunContent::FootnoteReference(FootnoteReference {
+            supress_reference_mark: None,
+            id: Some(id.to_string().into()),
+        })],
+        ..Default::default()
+    }
+}
+
+/// Adds a margin comment by `author` holding `text` and returns the run anchoring it in the document.
+pub fn push_comment(docx: &mut Docx<'_>, author: &str, text: String) -> Run<'static> {
+    ensure_content_type(docx, "/word/comments.xml", CONTENT_TYPE_COMMENTS);
+
+    let comments = docx.comments.get_or_insert_with(Comments::default);
+    let id = comments
+        .comments
+        .iter()
+        .filter_map(|comment| comment.id)
+        .max()
+        .map_or(0, |id| id + 1);
+
+    comments.comments.push(Comment {
+        id: Some(id),
+        author: author.to_owned().into(),
+        content: Paragraph::default()
+            .push(Run {
+                content: vec![RunContent::AnnotationRef(AnnotationRef)],
+                ..Default::default()
+            })
+            .push(Run::default().push_text((format!(" {}", text), TextSpace::Preserve))),
+    });
+
+    Run {
+        content: vec![RunContent::CommentReference(CommentReference {
+            id: Some(id.to_string().into()),
+        })],
+        ..Default::default()
+    }
+}
+
+/// Numbering of the paragraph heading a topic (level 0, "1.") or an exchange (level 1, "1.1."),
+/// declaring the numbering definition in `docx` unless already there.
+pub fn heading_numbering<'a>(docx: &mut Docx<'_>, level: isize) -> NumberingProperty<'a> {
+    ensure_content_type(docx, "/word/numbering.xml", CONTENT_TYPE_NUMBERING);
+
+    let numbering = docx.numbering.get_or_insert_with(Numbering::default);
+    if !numbering
+        .numberings
+        .iter()
+        .any(|num| num.num_id == Some(HEADING_NUMBERING_ID))
+    {
+        let level = |i_level: isize, text: &'static str| Level {
+            i_level: Some(i_level),
+            start: Some(LevelStart { value: Some(1) }),
+            number_format: Some(NumFmt {
+                value: "decimal".into(),
+            }),
+            level_text: Some(LevelText { value: text.into() }),
+            ..Default::default()
+        };
+        numbering.abstract_numberings.push(AbstractNum {
+            abstract_num_id: Some(HEADING_NUMBERING_ID),
+            nsid: Nsid {
+                value: format!("{:08X}", HEADING_NUMBERING_ID).into(),
+            },
+            multi_level_type: MultiLevelType {
+                value: "multilevel".into(),
+            },
+            // The exchanges start again from 1 in every topic
+            levels: vec![level(0, "%1."), level(1, "%1.%2.")],
+        });
+        numbering.numberings.push(Num {
+            num_id: Some(HEADING_NUMBERING_ID),
+            abstract_num_id: Some(AbstractNumId {
+                value: Some(HEADING_NUMBERING_ID),
+            }),
+            ..Default::default()
+        });
+    }
+
+    NumberingProperty {
+        level: Some(IndentLevel::from(level)),
+        id: Some(NumberingId::from(HEADING_NUMBERING_ID)),
+        ..Default::default()
+    }
+}
+
+/// `paragraph` numbered with `numbering`, its leading line breaks turned into space before it so
+/// that the number stays on the line of the text.
+pub fn numbered<'a>(
+    mut paragraph: Paragraph<'a>,
+    numbering: NumberingProperty<'a>,
+) -> Paragraph<'a> {
+    let mut breaks = 0;
+    if let Some(ParagraphContent::Run(run)) = paragraph.content.first_mut() {
+        breaks = run
+            .content
+            .iter()
+            .take_while(|content| matches!(content, RunContent::Break(_)))
+            .count();
+        run.content.drain(..breaks);
+    }
+
+    let property = paragraph.property.get_or_insert_with(Default::default);
+    property.numbering = Some(numbering);
+    if breaks > 0 {
+        // A line of 12pt, in twentieths of a point
+        let spacing = property.spacing.get_or_insert_with(Default::default);
+        spacing.before = Some(breaks as isize * 240);
+    }
+    paragraph
+}
+
+/// `paragraph` kept whole on the page of the next one, so that a heading never ends a page
+/// without the text it introduces.
+pub fn keep_with_next(mut paragraph: Paragraph<'_>) -> Paragraph<'_> {
+    let property = paragraph.property.get_or_insert_with(Default::default);
+    property.keep_next = Some(KeepNext { value: Some(true) });
+    property.keep_lines = Some(KeepLines { value: Some(true) });
+    paragraph
+}
+
+/// Two-column row holding the original paragraphs on the left and the translation on the right.
+pub fn bilingual_table<'a>(original: Vec<Paragraph<'a>>, translation: Option<&str>) -> Table<'a> {
+    let left = TableCell {
+        content: original
+            .into_iter()
+            .map(TableCellContent::Paragraph)
+            .collect(),
+        ..Default::default()
+    };
+
+    let mut right_content = translation
+        .unwrap_or_default()
+        .lines()
+        .map(|line| TableCellContent::Paragraph(Paragraph::default().push_text(line.to_owned())))
+        .collect::<Vec<_>>();
+    // A cell without paragraph is invalid
+    if right_content.is_empty() {
+        right_content.push(TableCellContent::Paragraph(Paragraph::default()));
+    }
+    let right = TableCell {
+        content: right_content,
+        ..Default::default()
+    };
+
+    Table {
+        grids: TableGrid::default()
+            .push_column(TABLE_HALF_WIDTH)
+            .push_column(TABLE_HALF_WIDTH),
+        ..Default::default()
+    }
+    .push_row(TableRow::default().push_cell(left).push_cell(right))
+}
+
+/// Number of characters of text held by `runs`.
+pub fn text_len<'r, 'a: 'r>(runs: impl IntoIterator<Item = &'r Run<'a>>) -> usize {
+    runs.into_iter()
+        .flat_map(|run| run.content.iter())
+        .map(|content| match content {
+            RunContent::Text(text) => text.text.chars().count(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Text of `runs` with the line breaks kept as newlines.
+pub fn plain_text(runs: &[Run<'_>]) -> String {
+    let mut text = String::new();
+    for content in runs.iter().flat_map(|run| run.content.iter()) {
+        match content {
+            RunContent::Text(t) => text.push_str(&t.text),
+            RunContent::Break(_) => text.push('\n'),
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Number of words of `runs`, counted run by run as the parser trims the text between tags.
+pub fn word_count<'r, 'a: 'r>(runs: impl IntoIterator<Item = &'r Run<'a>>) -> usize {
+    runs.into_iter()
+        .flat_map(|run| run.content.iter())
+        .map(|content| match content {
+            RunContent::Text(text) => text.text.split_whitespace().count(),
+            _ => 0,
+        })
+        .sum()
+}
+
+/// Keeps the first `max_chars` characters of text of the runs of `items`, followed by a pointer
+/// to the full message at `source_url`.
+pub fn truncate_runs<'a>(
+    items: Vec<MessageItem<'a>>,
+    max_chars: usize,
+    source_url: &str,
+) -> Vec<MessageItem<'a>> {
+    if text_len(items.iter().filter_map(MessageItem::as_run)) <= max_chars {
+        return items;
+    }
+
+    let mut remaining = max_chars;
+    let mut truncated = Vec::new();
+    for item in items {
+        if remaining == 0 {
+            break;
+        }
+        let MessageItem::Run(mut run) = item else {
+            truncated.push(item);
+            continue;
+        };
+        run.content.retain_mut(|content| match content {
+            RunContent::Text(text) => {
+                if remaining == 0 {
+                    return false;
+                }
+                let len = text.text.chars().count();
+                if len > remaining {
+                    text.text = text.text.chars().take(remaining).collect::<String>().into();
+                }
+                remaining = remaining.saturating_sub(len);
+                true
+            }
+            _ => remaining > 0,
+        });
+        truncated.push(MessageItem::Run(run));
+    }
+
+    truncated.push(MessageItem::Run(
+        Run::default().push_text((" […]", TextSpace::Preserve)),
+    ));
+    truncated.push(MessageItem::Run(
+        Run::default()
+            .push_break(BreakType::TextWrapping)
+            .push_text(format!("Message tronqué, texte complet : {}", source_url))
+            .property(CharacterProperty::default().italics(true)),
+    ));
+
+    truncated
+}
@@ -0,0 +1,107 @@
+use crate::config::config::DatesConfig;
+use crate::post::post::Post;
+use crate::utils::functions::xml_escape;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const SUMMARY_CHARS: usize = 280;
+
+/// An answer published in the Atom feed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeedEntry {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    pub updated: DateTime<Utc>,
+    pub summary: String,
+}
+
+/// One entry per answer of the topic, dated in the time zone of the forum.
+pub fn entries_from_post(post: &Post, dates: &DatesConfig) -> Vec<FeedEntry> {
+    post.messages
+        .iter()
+        .flatten()
+        .enumerate()
+        .filter(|(_, message)| message.is_answer() && !message.deleted)
+        .map(|(i, message)| {
+            let text = message
+                .text()
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            let summary = if text.chars().count() > SUMMARY_CHARS {
+                text.chars().take(SUMMARY_CHARS).collect::<String>() + "…"
+            } else {
+                text
+            };
+
+            FeedEntry {
+                id: format!(
+                    "{}#{}",
+                    post.url,
+                    message.id.clone().unwrap_or_else(|| i.to_string())
+                ),
+                title: post.title.clone(),
+                link: post.url.clone(),
+                updated: dates.instant(&message.date).unwrap_or_else(Utc::now),
+                summary,
+            }
+        })
+        .collect()
+}
+
+/// Adds the entries not already in `feed`, keeping the `max` most recent ones.
+pub fn merge_entries(feed: &mut Vec<FeedEntry>, entries: Vec<FeedEntry>, max: usize) -> usize {
+    let mut added = 0;
+    for entry in entries {
+        if !feed.iter().any(|existing| existing.id == entry.id) {
+            feed.push(entry);
+            added += 1;
+        }
+    }
+    feed.sort_by_key(|entry| std::cmp::Reverse(entry.updated));
+    feed.truncate(max);
+
+    added
+}
+
+pub fn write_atom<P: AsRef<Path>>(entries: &[FeedEntry], title: &str, path: P) -> Result<()> {
+    let updated = entries
+        .iter()
+        .map(|entry| entry.updated)
+        .max()
+        .unwrap_or_else(Utc::now);
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+    xml.push_str("<feed xmlns=\"http://www.w3.org/2005/Atom\">\n");
+    xml.push_str(&format!("  <title>{}</title>\n", xml_escape(title)));
+    xml.push_str("  <id>urn:ribav-scraper:feed</id>\n");
+    xml.push_str(&format!("  <updated>{}</updated>\n", updated.to_rfc3339()));
+    for entry in entries {
+        xml.push_str("  <entry>\n");
+        xml.push_str(&format!(
+            "    <title>{}</title>\n",
+            xml_escape(&entry.title)
+        ));
+        xml.push_str(&format!("    <id>{}</id>\n", xml_escape(&entry.id)));
+        xml.push_str(&format!(
+            "    <link href=\"{}\"/>\n",
+            xml_escape(&entry.link)
+        ));
+        xml.push_str(&format!(
+            "    <updated>{}</updated>\n",
+            entry.updated.to_rfc3339()
+        ));
+        xml.push_str(&format!(
+            "    <summary>{}</summary>\n",
+            xml_escape(&entry.summary)
+        ));
+        xml.push_str("  </entry>\n");
+    }
+    xml.push_str("</feed>\n");
+
+    std::fs::write(path.as_ref(), xml)
+        .with_context(|| format!("Failed to write feed {}", path.as_ref().display()))
+}
@@ -0,0 +1,178 @@
+use crate::config::config::{ExportConfig, FormattingProfile};
+use crate::utils::functions::is_citation;
+use docx_rust::document::Run;
+use docx_rust::formatting::{
+    CharacterProperty, Indent, OutlineLvl, ParagraphProperty, Spacing, WidowControl,
+};
+
+/// Layout of the documents bundled by a [`FormattingProfile`]. Sizes are in half-points,
+/// spacing and indents in twentieths of a point.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Formatting {
+    pub title_size: u8,
+    /// "Réponse:", "Question par ..."
+    pub heading_size: u8,
+    pub footer_size: u8,
+    /// Size of the message text left at its default size by the forum, Word's default when `None`
+    pub body_size: Option<u8>,
+    /// Space before and after each paragraph of a message
+    pub spacing_before: isize,
+    pub spacing_after: isize,
+    /// Line spacing of the messages in 240ths of a line, Word's default when `None`
+    pub line_spacing: Option<isize>,
+    /// Whether Word avoids leaving a single line of a paragraph alone on a page, Word's default
+    /// when `None`
+    pub widow_control: Option<bool>,
+    /// Empty lines before the title of each topic
+    pub title_breaks: usize,
+    /// Empty paragraph between messages
+    pub message_gap: bool,
+    pub citation_indent: isize,
+    pub citation_italics: bool,
+    /// Outline levels of the titles and headings, listed in Word's navigation pane and tables
+    /// of contents, body text when `None`
+    pub title_level: Option<isize>,
+    pub heading_level: Option<isize>,
+}
+
+impl Formatting {
+    pub fn of(profile: FormattingProfile) -> Self {
+        match profile {
+            FormattingProfile::Screen => Self {
+                title_size: 32,
+                heading_size: 24,
+                footer_size: 16,
+                body_size: None,
+                spacing_before: 0,
+                spacing_after: 0,
+                line_spacing: None,
+                widow_control: None,
+                title_breaks: 2,
+                message_gap: true,
+                citation_indent: 300,
+                citation_italics: false,
+                title_level: None,
+                heading_level: None,
+            },
+            FormattingProfile::Print => Self {
+                title_size: 36,
+                heading_size: 26,
+                footer_size: 16,
+                body_size: Some(24),
+                spacing_before: 0,
+                spacing_after: 120,
+                line_spacing: None,
+                widow_control: None,
+                title_breaks: 2,
+                message_gap: true,
+                citation_indent: 567,
+                citation_italics: true,
+                title_level: Some(0),
+                heading_level: Some(1),
+            },
+            FormattingProfile::Compact => Self {
+                title_size: 28,
+                heading_size: 22,
+                footer_size: 14,
+                body_size: Some(20),
+                spacing_before: 0,
+                spacing_after: 0,
+                line_spacing: None,
+                widow_control: None,
+                title_breaks: 0,
+                message_gap: false,
+                citation_indent: 200,
+                citation_italics: false,
+                title_level: Some(0),
+                heading_level: None,
+            },
+        }
+    }
+
+    /// Layout of `options.formatting`, with the spacing of `options.paragraphs` over it.
+    pub fn from_config(options: &ExportConfig) -> Self {
+        let mut formatting = Self::of(options.formatting);
+        let paragraphs = &options.paragraphs;
+        // Points to twentieths of a point
+        if let Some(before) = paragraphs.before {
+            formatting.spacing_before = (before * 20.0).round() as isize;
+        }
+        if let Some(after) = paragraphs.after {
+            formatting.spacing_after = (after * 20.0).round() as isize;
+        }
+        if let Some(line_spacing) = paragraphs.line_spacing {
+            formatting.line_spacing = Some((line_spacing * 240.0).round() as isize);
+        }
+        if paragraphs.widow_control.is_some() {
+            formatting.widow_control = paragraphs.widow_control;
+        }
+        formatting
+    }
+
+    /// `run` of a message with the body size and citation style, under its own formatting.
+    pub fn style_run<'a>(&self, run: Run<'a>) -> Run<'a> {
+        let citation = is_citation(&run);
+        if self.body_size.is_none() && !(citation && self.citation_italics) {
+            return run;
+        }
+
+        let mut cp = run.property.clone().unwrap_or_default();
+        if cp.size.is_none() {
+            cp.size = self.body_size.map(Into::into);
+        }
+        if citation && self.citation_italics && cp.italics.is_none() {
+            cp = cp.italics(true);
+        }
+        run.property(cp)
+    }
+
+    /// Property of a paragraph of a message, `None` when it has nothing to set.
+    pub fn body_property<'a>(&self, citation: bool) -> Option<ParagraphProperty<'a>> {
+        let spaced =
+            self.spacing_before > 0 || self.spacing_after > 0 || self.line_spacing.is_some();
+        if !citation && !spaced && self.widow_control.is_none() {
+            return None;
+        }
+
+        let mut property = ParagraphProperty::default();
+        if citation {
+            property = property.indent(Indent {
+                left: Some(self.citation_indent),
+                ..Default::default()
+            });
+        }
+        if spaced {
+            property = property.spacing(Spacing {
+                before: (self.spacing_before > 0).then_some(self.spacing_before),
+                after: (self.spacing_after > 0).then_some(self.spacing_after),
+                // In 240ths of a line, the rule defaulting to auto
+                line: self.line_spacing,
+                ..Default::default()
+            });
+        }
+        if let Some(widow_control) = self.widow_control {
+            property.widow_control = Some(WidowControl {
+                value: Some(widow_control),
+            });
+        }
+        Some(property)
+    }
+
+    /// Character property of the headings introducing each message.
+    pub fn heading_property<'a>(&self) -> CharacterProperty<'a> {
+        CharacterProperty::default()
+            .bold(true)
+            .size(self.heading_size)
+    }
+}
+
+/// `property` at the outline `level`, if any.
+pub fn with_outline_level(
+    property: ParagraphProperty<'_>,
+    level: Option<isize>,
+) -> ParagraphProperty<'_> {
+    ParagraphProperty {
+        outline_lvl: level.map(|value| OutlineLvl { value }),
+        ..property
+    }
+}
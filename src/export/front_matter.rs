@@ -0,0 +1,97 @@
+use crate::config::config::FrontMatterConfig;
+use anyhow::{Context, Result};
+use chrono::Local;
+use docx_rust::document::{BreakType, Paragraph, Run};
+use docx_rust::formatting::{CharacterProperty, JustificationVal, ParagraphProperty};
+use docx_rust::Docx;
+use std::path::Path;
+
+/// Adds the cover, disclaimer and preface pages to a new document of `category`.
+pub fn push_front_matter(
+    docx: &mut Docx<'_>,
+    config: &FrontMatterConfig,
+    category: &str,
+) -> Result<()> {
+    let date = Local::now().format("%d/%m/%Y").to_string();
+    let render = |template: &str| {
+        template
+            .replace("{category}", category)
+            .replace("{date}", &date)
+    };
+
+    if config.cover {
+        docx.document.push(centered(
+            Run::default()
+                .push_break(BreakType::TextWrapping)
+                .push_break(BreakType::TextWrapping)
+                .push_break(BreakType::TextWrapping)
+                .push_text(render(&config.title))
+                .property(CharacterProperty::default().bold(true).size(48u8)),
+        ));
+        docx.document.push(centered(
+            Run::default()
+                .push_text(category.to_owned())
+                .property(CharacterProperty::default().size(36u8)),
+        ));
+        docx.document
+            .push(centered(Run::default().push_text(date.clone())));
+        docx.document.push(page_break());
+    }
+
+    if let Some(ref path) = config.disclaimer {
+        for paragraph in paragraphs(&render(&read_template(path)?)) {
+            docx.document.push(
+                Paragraph::default().push(
+                    Run::default()
+                        .push_text(paragraph)
+                        .property(CharacterProperty::default().italics(true).size(18u8)),
+                ),
+            );
+        }
+        docx.document.push(page_break());
+    }
+
+    if let Some(ref path) = config.preface {
+        docx.document.push(centered(
+            Run::default()
+                .push_text("Préface")
+                .property(CharacterProperty::default().bold(true).size(32u8)),
+        ));
+        for paragraph in paragraphs(&render(&read_template(path)?)) {
+            docx.document
+                .push(Paragraph::default().push(Run::default().push_text(paragraph)));
+        }
+        docx.document.push(page_break());
+    }
+
+    Ok(())
+}
+
+fn read_template(path: &Path) -> Result<String> {
+    std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read front matter template {}", path.display()))
+}
+
+/// Splits a template on blank lines, joining the lines of each paragraph.
+fn paragraphs(text: &str) -> Vec<String> {
+    text.split("\n\n")
+        .map(|paragraph| {
+            paragraph
+                .lines()
+                .map(str::trim)
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .filter(|paragraph| !paragraph.is_empty())
+        .collect()
+}
+
+fn centered(run: Run<'static>) -> Paragraph<'static> {
+    Paragraph::default()
+        .push(run)
+        .property(ParagraphProperty::default().justification(JustificationVal::Center))
+}
+
+fn page_break() -> Paragraph<'static> {
+    Paragraph::default().push(Run::default().push_break(BreakType::Page))
+}
@@ -0,0 +1,87 @@
+use crate::config::config::{GlossaryConfig, GlossaryMode};
+use crate::export::docx::push_footnote;
+use anyhow::Result;
+use docx_rust::document::{Run, RunContent, TextSpace};
+use docx_rust::Docx;
+use regex::Regex;
+use std::collections::HashSet;
+
+/// Expands abbreviations on their first occurrence in a topic, one instance per topic.
+#[derive(Debug, Clone)]
+pub struct Glossary {
+    mode: GlossaryMode,
+    entries: Vec<(Regex, String, String)>,
+    seen: HashSet<String>,
+}
+
+impl Glossary {
+    pub fn from_config(config: &GlossaryConfig) -> Result<Self> {
+        let mut entries = Vec::new();
+        for (abbreviation, expansion) in &config.entries {
+            let regex = Regex::new(&format!(r"\b{}\b", regex::escape(abbreviation)))?;
+            entries.push((regex, abbreviation.to_owned(), expansion.to_owned()));
+        }
+
+        Ok(Self {
+            mode: config.mode,
+            entries,
+            seen: HashSet::new(),
+        })
+    }
+
+    pub fn annotate<'a>(&mut self, runs: Vec<Run<'a>>, docx: &mut Docx<'_>) -> Vec<Run<'a>> {
+        let mut annotated = Vec::with_capacity(runs.len());
+
+        for run in runs {
+            // Only plain text runs are split, breaks and references are kept as is
+            let mut text = match run.content.as_slice() {
+                [RunContent::Text(text)] => text.text.to_string(),
+                _ => {
+                    annotated.push(run);
+                    continue;
+                }
+            };
+
+            while let Some((end, abbreviation, expansion)) = self.first_unseen(&text) {
+                self.seen.insert(abbreviation);
+
+                match self.mode {
+                    GlossaryMode::Brackets => {
+                        text.insert_str(end, &format!(" ({})", expansion));
+                    }
+                    GlossaryMode::Footnote => {
+                        let rest = text.split_off(end);
+                        annotated.push(with_text(&run, text));
+                        annotated.push(push_footnote(docx, expansion));
+                        text = rest;
+                    }
+                }
+            }
+
+            annotated.push(with_text(&run, text));
+        }
+
+        annotated
+    }
+
+    /// End of the first occurrence of an abbreviation not expanded yet in the topic.
+    fn first_unseen(&self, text: &str) -> Option<(usize, String, String)> {
+        self.entries
+            .iter()
+            .filter(|(_, abbreviation, _)| !self.seen.contains(abbreviation))
+            .filter_map(|(regex, abbreviation, expansion)| {
+                regex
+                    .find(text)
+                    .map(|m| (m.end(), abbreviation.to_owned(), expansion.to_owned()))
+            })
+            .min_by_key(|(end, _, _)| *end)
+    }
+}
+
+fn with_text<'a>(run: &Run<'a>, text: String) -> Run<'a> {
+    Run {
+        content: Vec::new(),
+        ..run.clone()
+    }
+    .push_text((text, TextSpace::Preserve))
+}
@@ -0,0 +1,106 @@
+use crate::export::manifest::sha256_hex;
+use crate::post::post::Post;
+use crate::utils::functions::parse_post_date;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Writes one all-day event per answered topic, on the day of its first answer, keeping the
+/// events written by previous runs.
+pub fn write_calendar<'a, I, P>(posts: I, path: P) -> Result<()>
+where
+    I: IntoIterator<Item = &'a Post>,
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let mut events = match std::fs::read_to_string(path) {
+        Ok(calendar) => read_events(&calendar),
+        Err(_) => BTreeMap::new(),
+    };
+
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+    for post in posts {
+        let Some(answered_on) = post
+            .messages
+            .iter()
+            .flatten()
+            .find(|message| message.is_answer())
+            .and_then(|message| parse_post_date(&message.date))
+        else {
+            continue;
+        };
+
+        let uid = format!("{}@ribav-scraper", &sha256_hex(post.url.as_bytes())[..16]);
+        let event = [
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:{}", uid),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", answered_on.format("%Y%m%d")),
+            format!("SUMMARY:{}", escape(&post.title)),
+            format!(
+                "DESCRIPTION:{}",
+                escape(&format!("{} — {}", post.category, post.url))
+            ),
+            format!("URL:{}", post.url),
+            "END:VEVENT".to_owned(),
+        ]
+        .iter()
+        .map(|line| fold(line))
+        .collect::<Vec<_>>()
+        .join("\r\n");
+        events.insert(uid, event);
+    }
+
+    let mut calendar = String::from(
+        "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nPRODID:-//ribav-scraper//FR\r\nCALSCALE:GREGORIAN\r\n",
+    );
+    for event in events.values() {
+        calendar.push_str(event);
+        calendar.push_str("\r\n");
+    }
+    calendar.push_str("END:VCALENDAR\r\n");
+
+    std::fs::write(path, calendar)
+        .with_context(|| format!("Failed to write calendar {}", path.display()))
+}
+
+/// Events of a calendar previously written by [`write_calendar`], by UID.
+fn read_events(calendar: &str) -> BTreeMap<String, String> {
+    calendar
+        .split("BEGIN:VEVENT")
+        .skip(1)
+        .filter_map(|block| {
+            let block = block.split("END:VEVENT").next()?;
+            let uid = block
+                .lines()
+                .find_map(|line| line.strip_prefix("UID:"))?
+                .trim()
+                .to_owned();
+            Some((uid, format!("BEGIN:VEVENT{}END:VEVENT", block)))
+        })
+        .collect()
+}
+
+/// Escapes a TEXT value (RFC 5545 §3.3.11).
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds content lines longer than 75 octets, without splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut len = 0;
+    for c in line.chars() {
+        if len + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            len = 1;
+        }
+        folded.push(c);
+        len += c.len_utf8();
+    }
+    folded
+}
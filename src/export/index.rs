@@ -0,0 +1,163 @@
+use crate::export::docx::docx_error;
+use crate::post::post::category_output_path;
+use crate::state::state::State;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate};
+use docx_rust::document::{
+    Paragraph, Run, Table, TableCell, TableCellContent, TableGrid, TableRow,
+};
+use docx_rust::formatting::{CharacterProperty, JustificationVal, ParagraphProperty};
+use docx_rust::Docx;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Width of the columns of the DOCX index, in twips: document, topics, period, last update
+const COLUMN_WIDTHS: [isize; 4] = [3400, 1100, 2900, 2238];
+const HEADERS: [&str; 4] = ["Document", "Sujets", "Période", "Mis à jour"];
+
+/// A category document, as listed in the index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub category: String,
+    pub path: PathBuf,
+    pub topics: usize,
+    /// Days of the first and last message of its topics
+    pub first_message: Option<NaiveDate>,
+    pub last_message: Option<NaiveDate>,
+    /// Last modification of the document
+    pub updated: Option<DateTime<Local>>,
+}
+
+impl IndexEntry {
+    fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    fn period(&self) -> String {
+        match (self.first_message, self.last_message) {
+            (Some(first), Some(last)) if first == last => first.format("%d/%m/%Y").to_string(),
+            (Some(first), Some(last)) => {
+                format!("{} – {}", first.format("%d/%m/%Y"), last.format("%d/%m/%Y"))
+            }
+            _ => "—".to_owned(),
+        }
+    }
+
+    fn updated(&self) -> String {
+        self.updated
+            .map(|updated| updated.format("%d/%m/%Y %H:%M").to_string())
+            .unwrap_or_else(|| "—".to_owned())
+    }
+
+    fn cells(&self) -> [String; 4] {
+        [
+            self.file_name(),
+            self.topics.to_string(),
+            self.period(),
+            self.updated(),
+        ]
+    }
+}
+
+/// The documents of `output_dir` with the `extension` of the export format, one per category of
+/// the topics exported so far, in category order.
+pub fn index_entries(state: &State, output_dir: &Path, extension: &str) -> Vec<IndexEntry> {
+    let mut entries: BTreeMap<&str, IndexEntry> = BTreeMap::new();
+    for topic in state.topics.values() {
+        let entry = entries
+            .entry(topic.category.as_str())
+            .or_insert_with(|| IndexEntry {
+                category: topic.category.clone(),
+                path: category_output_path(output_dir, &topic.category, extension),
+                topics: 0,
+                first_message: None,
+                last_message: None,
+                updated: None,
+            });
+        entry.topics += 1;
+        if let Some(first) = topic.first_message.map(|date| date.date()) {
+            entry.first_message = Some(entry.first_message.map_or(first, |day| day.min(first)));
+        }
+        if let Some(last) = topic.last_message.map(|date| date.date()) {
+            entry.last_message = Some(entry.last_message.map_or(last, |day| day.max(last)));
+        }
+    }
+
+    entries
+        .into_values()
+        // Removed by hand, or never written (a protected document)
+        .filter(|entry| entry.path.exists())
+        .map(|entry| IndexEntry {
+            updated: std::fs::metadata(&entry.path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .map(DateTime::<Local>::from),
+            ..entry
+        })
+        .collect()
+}
+
+/// Writes `entries` as a Markdown table linking to each document.
+pub fn write_markdown(entries: &[IndexEntry], path: &Path) -> Result<()> {
+    let mut markdown = format!(
+        "# Index\n\n| {} |\n|---|--:|---|---|\n",
+        HEADERS.join(" | ")
+    );
+    for entry in entries {
+        let [name, topics, period, updated] = entry.cells();
+        // Angle brackets for the names with spaces
+        markdown.push_str(&format!(
+            "| [{}](<{}>) | {} | {} | {} |\n",
+            entry.category, name, topics, period, updated
+        ));
+    }
+
+    std::fs::write(path, markdown).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+/// Writes `entries` as a table in a Word document.
+pub fn write_docx(entries: &[IndexEntry], path: &Path) -> Result<()> {
+    let mut docx = Docx::default();
+    docx.document.push(
+        Paragraph::default()
+            .push(
+                Run::default()
+                    .push_text("Index des documents")
+                    .property(CharacterProperty::default().bold(true).size(32u8)),
+            )
+            .property(ParagraphProperty::default().justification(JustificationVal::Center)),
+    );
+
+    let grid = COLUMN_WIDTHS
+        .iter()
+        .fold(TableGrid::default(), |grid, width| grid.push_column(*width));
+    let row = |cells: [String; 4], bold: bool| {
+        cells.into_iter().fold(TableRow::default(), |row, text| {
+            let mut run = Run::default().push_text(text);
+            if bold {
+                run = run.property(CharacterProperty::default().bold(true));
+            }
+            row.push_cell(TableCell {
+                content: vec![TableCellContent::Paragraph(Paragraph::default().push(run))],
+                ..Default::default()
+            })
+        })
+    };
+
+    let mut table = Table {
+        grids: grid,
+        ..Default::default()
+    }
+    .push_row(row(HEADERS.map(str::to_owned), true));
+    for entry in entries {
+        table = table.push_row(row(entry.cells(), false));
+    }
+    docx.document.push(table);
+
+    docx.write_file(path)
+        .map_err(|e| docx_error(e, "write", path))?;
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const JOURNAL_EXTENSION: &str = "journal";
+const BACKUP_EXTENSION: &str = "orig";
+
+/// Written next to an output before appending to it, removed once the append is complete.
+#[derive(Debug, Serialize, Deserialize)]
+struct JournalEntry {
+    topic_url: String,
+    /// Whether the output existed before the append, and was backed up
+    backed_up: bool,
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = OsString::from(path.as_os_str());
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Runs `append` on the output at `path` behind a write-ahead journal: the output is backed up
+/// first, so that a run interrupted in the middle of the append leaves enough behind for
+/// [`recover`] to put the output back as it was.
+pub fn journaled<T>(path: &Path, topic_url: &str, append: impl FnOnce() -> Result<T>) -> Result<T> {
+    let journal = with_suffix(path, JOURNAL_EXTENSION);
+    let backup = with_suffix(path, BACKUP_EXTENSION);
+
+    let backed_up = path.exists();
+    if backed_up {
+        std::fs::copy(path, &backup)
+            .with_context(|| format!("Failed to back up {}", path.display()))?;
+    }
+
+    let entry = JournalEntry {
+        topic_url: topic_url.to_owned(),
+        backed_up,
+    };
+    let mut file = File::create(&journal)
+        .with_context(|| format!("Failed to create journal {}", journal.display()))?;
+    file.write_all(serde_json::to_string(&entry)?.as_bytes())?;
+    // The journal must be on disk before the output is touched
+    file.sync_all()
+        .with_context(|| format!("Failed to write journal {}", journal.display()))?;
+
+    let result = append();
+    if result.is_err() {
+        // A failed append is rolled back right away, the journal only covers crashes
+        restore(path, &backup, backed_up)?;
+    }
+
+    std::fs::remove_file(&journal)
+        .with_context(|| format!("Failed to remove journal {}", journal.display()))?;
+    if backed_up && backup.exists() {
+        std::fs::remove_file(&backup)
+            .with_context(|| format!("Failed to remove backup {}", backup.display()))?;
+    }
+
+    result
+}
+
+fn restore(path: &Path, backup: &Path, backed_up: bool) -> Result<()> {
+    if backed_up {
+        std::fs::rename(backup, path)
+            .with_context(|| format!("Failed to restore {}", path.display()))
+    } else if path.exists() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove partial {}", path.display()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Puts back the outputs of `output_dir` whose append was interrupted, returning the urls of
+/// the topics to export again.
+pub fn recover(output_dir: &Path) -> Result<Vec<String>> {
+    let mut interrupted = Vec::new();
+    if !output_dir.exists() {
+        return Ok(interrupted);
+    }
+
+    let entries = std::fs::read_dir(output_dir)
+        .with_context(|| format!("Failed to list {}", output_dir.display()))?;
+    for entry in entries {
+        let file = entry?.path();
+        // Already handled along with its journal
+        if !file.exists() {
+            continue;
+        }
+        let extension = file.extension().and_then(|ext| ext.to_str());
+        if extension == Some(BACKUP_EXTENSION) && !file.with_extension(JOURNAL_EXTENSION).exists() {
+            // Interrupted before the journal was written, or after the append completed
+            std::fs::remove_file(&file)
+                .with_context(|| format!("Failed to remove backup {}", file.display()))?;
+            continue;
+        }
+        if extension != Some(JOURNAL_EXTENSION) {
+            continue;
+        }
+        let journal = file;
+
+        let path = journal.with_extension("");
+        let backup = with_suffix(&path, BACKUP_EXTENSION);
+        // Interrupted while the journal itself was written: the output was not touched yet
+        let entry = std::fs::read_to_string(&journal)
+            .ok()
+            .and_then(|json| serde_json::from_str::<JournalEntry>(&json).ok());
+        match entry {
+            Some(entry) => {
+                warn!(
+                    "Append of {} to {} was interrupted, restoring the previous version",
+                    entry.topic_url,
+                    path.display()
+                );
+                restore(&path, &backup, entry.backed_up)?;
+                interrupted.push(entry.topic_url);
+            }
+            None => {
+                if backup.exists() {
+                    std::fs::remove_file(&backup)?;
+                }
+            }
+        }
+        std::fs::remove_file(&journal)
+            .with_context(|| format!("Failed to remove journal {}", journal.display()))?;
+    }
+
+    Ok(interrupted)
+}
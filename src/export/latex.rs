@@ -0,0 +1,165 @@
+use crate::config::config::ExportConfig;
+use crate::export::titles::heading_title;
+use crate::post::post::{Post, SERIES_MERGED_LINE};
+use crate::utils::functions::is_citation;
+use anyhow::{Context, Result};
+use docx_rust::document::{Run, RunContent};
+use std::path::Path;
+
+const END: &str = "\\end{document}\n";
+
+/// Appends the topic as a section of the `book` document of its category, creating it with a
+/// French babel preamble the first time.
+pub fn append_topic(post: &Post, path: &Path, options: &ExportConfig) -> Result<()> {
+    let mut tex = match std::fs::read_to_string(path) {
+        Ok(tex) => tex.trim_end_matches(END).to_owned(),
+        Err(_) => preamble(&post.category.to_string()),
+    };
+
+    // A topic merged into the section of the one it continues has no heading of its own
+    if post.is_merged() {
+        tex.push_str(&format!(
+            "\n{{\\small\\textit{{{}}}}}\\par\n",
+            SERIES_MERGED_LINE
+        ));
+    } else {
+        tex.push_str(&format!(
+            "\n\\section{{{}}}\n",
+            escape(&heading_title(&post.title, &options.titles))
+        ));
+    }
+    tex.push_str(&format!("\\url{{{}}}\n", escape_url(&post.url)));
+    let activity = post
+        .activity()
+        .filter(|_| options.activity && !post.is_merged());
+    for line in activity
+        .into_iter()
+        .chain(post.series_line(&options.titles))
+    {
+        tex.push_str(&format!(
+            "\n{{\\small\\textit{{{}}}}}\\par\n",
+            escape(&line)
+        ));
+    }
+
+    let mut previous = None;
+    for message in post.messages.iter().flatten() {
+        if let Some(heading) = message.heading(previous) {
+            tex.push_str(&format!("\n\\subsection*{{{}}}\n", escape(&heading)));
+        }
+        previous = Some(message);
+
+        tex.push_str(&format!(
+            "\\textit{{{}}}\\par\n\n",
+            escape(&options.dates.heading(&message.date, message.is_answer()))
+        ));
+        tex.push_str(&runs_to_latex(&message.runs()));
+        tex.push_str("\n\\par\n");
+    }
+    if options.source_footer {
+        tex.push_str(&format!(
+            "\n{{\\small\\textit{{Source: \\url{{{}}} (consulté le {})}}}}\\par\n",
+            escape_url(&post.url),
+            chrono::Local::now().format("%d/%m/%Y")
+        ));
+    }
+    tex.push('\n');
+    tex.push_str(END);
+
+    std::fs::write(path, tex).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn preamble(category: &str) -> String {
+    format!(
+        "\\documentclass[12pt]{{book}}\n\
+         \\usepackage[utf8]{{inputenc}}\n\
+         \\usepackage[T1]{{fontenc}}\n\
+         \\usepackage[french]{{babel}}\n\
+         \\usepackage[hidelinks]{{hyperref}}\n\
+         \n\
+         \\title{{{}}}\n\
+         \\author{{Rav Binyamin Wattenberg}}\n\
+         \n\
+         \\begin{{document}}\n\
+         \\maketitle\n\
+         \\tableofcontents\n",
+        escape(category)
+    )
+}
+
+/// Body of a message, citations going into `quote` environments.
+pub fn runs_to_latex(runs: &[Run<'_>]) -> String {
+    let mut tex = String::new();
+    let mut in_quote = false;
+    for run in runs {
+        let citation = is_citation(run);
+        if citation != in_quote {
+            tex.push_str(if citation {
+                "\n\\begin{quote}\n"
+            } else {
+                "\n\\end{quote}\n"
+            });
+            in_quote = citation;
+        }
+        tex.push_str(&run_to_latex(run));
+    }
+    if in_quote {
+        tex.push_str("\n\\end{quote}\n");
+    }
+
+    tex
+}
+
+fn run_to_latex(run: &Run<'_>) -> String {
+    let property = run.property.as_ref();
+    let bold = property.is_some_and(|p| p.bold.as_ref().is_some_and(|b| b.value != Some(false)));
+    let italics =
+        property.is_some_and(|p| p.italics.as_ref().is_some_and(|i| i.value != Some(false)));
+    let underline = property.is_some_and(|p| p.underline.is_some());
+
+    let mut tex = String::new();
+    for content in &run.content {
+        match content {
+            RunContent::Text(text) if !text.text.is_empty() => {
+                let mut text = escape(&text.text);
+                if underline {
+                    text = format!("\\underline{{{}}}", text);
+                }
+                if italics {
+                    text = format!("\\emph{{{}}}", text);
+                }
+                if bold {
+                    text = format!("\\textbf{{{}}}", text);
+                }
+                tex.push_str(&text);
+            }
+            // A \\ at the start of a paragraph is an error, \par is always safe
+            RunContent::Break(_) => tex.push_str("\\par\n"),
+            _ => {}
+        }
+    }
+
+    tex
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '\\' => escaped.push_str("\\textbackslash{}"),
+            '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            '~' => escaped.push_str("\\textasciitilde{}"),
+            '^' => escaped.push_str("\\textasciicircum{}"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// `\url` takes its argument verbatim but for `%` and `#`.
+fn escape_url(url: &str) -> String {
+    url.replace('%', "\\%").replace('#', "\\#")
+}
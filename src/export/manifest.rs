@@ -0,0 +1,83 @@
+use crate::post::post::Post;
+use anyhow::{Context, Result};
+use chrono::Utc;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize)]
+pub struct Manifest {
+    pub generated_at: String,
+    pub files: Vec<ManifestEntry>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ManifestEntry {
+    pub path: String,
+    pub sha256: String,
+    /// Topics in the whole document after every run, `None` for the files that are not category
+    /// documents, or whose topics the state does not track such as the delta documents
+    pub topic_count: Option<usize>,
+    /// Topics written into the file by this run, an incremental run leaves out the ones exported
+    /// by the previous runs
+    pub run_topic_count: usize,
+    /// Urls of the topics written by this run
+    pub source_urls: Vec<String>,
+    /// Replies and views of its topics, as listed by the search
+    pub replies: u64,
+    pub views: u64,
+}
+
+impl Manifest {
+    /// Builds the manifest from the generated files and the topic urls written into each of them,
+    /// found in `posts`, with the total of topics of the category documents in `document_topics`.
+    pub fn from_outputs(
+        outputs: &BTreeMap<PathBuf, Vec<String>>,
+        document_topics: &BTreeMap<PathBuf, usize>,
+        posts: &BTreeMap<String, Post>,
+    ) -> Result<Self> {
+        let mut files = Vec::new();
+
+        for (path, urls) in outputs {
+            let bytes = std::fs::read(path)
+                .with_context(|| format!("Failed to read generated file {}", path.display()))?;
+
+            let total = |count: fn(&Post) -> Option<u32>| {
+                urls.iter()
+                    .filter_map(|url| posts.get(url).and_then(count))
+                    .map(u64::from)
+                    .sum()
+            };
+            files.push(ManifestEntry {
+                path: path.display().to_string(),
+                sha256: sha256_hex(&bytes),
+                topic_count: document_topics.get(path).copied(),
+                run_topic_count: urls.len(),
+                source_urls: urls.clone(),
+                replies: total(|post| post.replies),
+                views: total(|post| post.views),
+            });
+        }
+
+        Ok(Self {
+            generated_at: Utc::now().to_rfc3339(),
+            files,
+        })
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize manifest")?;
+        std::fs::write(path.as_ref(), json)
+            .with_context(|| format!("Failed to write manifest {}", path.as_ref().display()))?;
+
+        Ok(())
+    }
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
@@ -0,0 +1,25 @@
+pub mod anki;
+#[cfg(feature = "annotations")]
+pub mod annotations;
+pub mod core_properties;
+pub mod cross_refs;
+pub mod csv;
+pub mod custom_properties;
+pub mod docx;
+pub mod feed;
+pub mod formatting;
+pub mod front_matter;
+pub mod glossary;
+pub mod ical;
+pub mod index;
+pub mod journal;
+pub mod latex;
+pub mod manifest;
+pub mod notice;
+pub mod odt;
+pub mod package;
+pub mod plan;
+pub mod quarantine;
+pub mod summary;
+pub mod titles;
+pub mod typography;
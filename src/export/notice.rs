@@ -0,0 +1,28 @@
+use crate::config::config::NoticeConfig;
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::path::Path;
+
+/// Used when no template is configured; `{attribution}`, `{source}` and `{date}` are replaced.
+const DEFAULT_TEMPLATE: &str = "\
+{attribution}
+
+Ces documents reproduisent des échanges publiés sur {source}, recueillis le {date}.
+Les noms des participants ont été anonymisés. Toute diffusion doit mentionner la source et
+respecter les conditions fixées par les auteurs et l'administration du forum.
+";
+
+/// Writes the attribution and permission notice shipped alongside the generated documents.
+pub fn write_notice(config: &NoticeConfig, source: &str, path: &Path) -> Result<()> {
+    let template = match config.template {
+        Some(ref template) => std::fs::read_to_string(template)
+            .with_context(|| format!("Failed to read notice template {}", template.display()))?,
+        None => DEFAULT_TEMPLATE.to_owned(),
+    };
+
+    let notice = template
+        .replace("{attribution}", &config.attribution)
+        .replace("{source}", source)
+        .replace("{date}", &Local::now().format("%d/%m/%Y").to_string());
+    std::fs::write(path, notice).with_context(|| format!("Failed to write {}", path.display()))
+}
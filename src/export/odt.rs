@@ -0,0 +1,293 @@
+use crate::config::config::ExportConfig;
+use crate::export::formatting::Formatting;
+use crate::export::titles::heading_title;
+use crate::post::post::{Post, SERIES_MERGED_LINE};
+use crate::utils::functions::{is_citation, xml_escape};
+use anyhow::{Context, Result};
+use docx_rust::document::{Run, RunContent};
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+const MIMETYPE: &str = "application/vnd.oasis.opendocument.text";
+const BODY_END: &str = "</office:text>";
+
+const MANIFEST: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<manifest:manifest xmlns:manifest="urn:oasis:names:tc:opendocument:xmlns:manifest:1.0" manifest:version="1.2">
+ <manifest:file-entry manifest:full-path="/" manifest:media-type="application/vnd.oasis.opendocument.text"/>
+ <manifest:file-entry manifest:full-path="content.xml" manifest:media-type="text/xml"/>
+ <manifest:file-entry manifest:full-path="meta.xml" manifest:media-type="text/xml"/>
+</manifest:manifest>
+"#;
+
+const CONTENT_BODY: &str = " <office:body>\n  <office:text>\n";
+const CONTENT_END: &str = "  </office:text>\n </office:body>\n</office:document-content>\n";
+
+/// Appends the topic to the OpenDocument text of its category.
+pub fn append_topic(post: &Post, path: &Path, options: &ExportConfig) -> Result<()> {
+    // Only used for a new document, an existing one keeps its styles
+    let formatting = Formatting::from_config(options);
+    let mut content = if path.exists() {
+        read_content(path)?
+    } else {
+        format!("{}{}", content_start(&formatting), CONTENT_END)
+    };
+
+    let mut topic = String::new();
+    if post.is_merged() {
+        topic.push_str(&paragraph("PSource", SERIES_MERGED_LINE));
+    } else {
+        let title = heading_title(&post.title, &options.titles);
+        topic.push_str(&outline_paragraph(
+            "PTitle",
+            formatting.title_level,
+            &xml_escape(&title),
+        ));
+        if let Some(activity) = post.activity().filter(|_| options.activity) {
+            topic.push_str(&paragraph("PSource", &xml_escape(&activity)));
+        }
+    }
+    if let Some(series) = post.series_line(&options.titles) {
+        topic.push_str(&paragraph("PSource", &xml_escape(&series)));
+    }
+
+    let mut previous = None;
+    for message in post.messages.iter().flatten() {
+        if let Some(heading) = message.heading(previous) {
+            topic.push_str(&outline_paragraph(
+                "PHeading",
+                formatting.heading_level,
+                &xml_escape(&heading),
+            ));
+        }
+        previous = Some(message);
+
+        topic.push_str(&paragraph(
+            "PDate",
+            &xml_escape(&options.dates.heading(&message.date, message.is_answer())),
+        ));
+
+        let runs = message.runs();
+        for (citation, runs) in group_citations(&runs) {
+            let text = runs.iter().map(|run| span(run)).collect::<String>();
+            topic.push_str(&if citation {
+                paragraph("PQuote", &text)
+            } else {
+                paragraph("PBody", &text)
+            });
+        }
+    }
+    if options.source_footer {
+        topic.push_str(&paragraph("PSource", &xml_escape(&post.source_footer())));
+    }
+    topic.push_str("   <text:p text:style-name=\"PBreak\"/>\n");
+
+    let end = content
+        .rfind(BODY_END)
+        .ok_or_else(|| anyhow::anyhow!("Malformed content.xml in {}", path.display()))?;
+    content.insert_str(end, &topic);
+
+    let attribution = options
+        .notice
+        .as_ref()
+        .map(|notice| notice.attribution.as_str());
+    write_package(path, &content, &meta(attribution))
+}
+
+/// Same look as the DOCX: centered bold titles, underlined headings, indented citations, sized
+/// and spaced as `formatting` says.
+fn content_start(formatting: &Formatting) -> String {
+    // Half-points and twentieths of a point
+    let pt = |half_points: u8| format!("{}pt", half_points as f32 / 2.0);
+    let body_size = formatting
+        .body_size
+        .map(|size| format!(" fo:font-size=\"{}\"", pt(size)))
+        .unwrap_or_default();
+    let mut body_layout = format!(
+        " fo:margin-top=\"{}pt\" fo:margin-bottom=\"{}pt\"",
+        formatting.spacing_before as f32 / 20.0,
+        formatting.spacing_after as f32 / 20.0
+    );
+    if let Some(line_spacing) = formatting.line_spacing {
+        // 240ths of a line
+        body_layout.push_str(&format!(
+            " fo:line-height=\"{}%\"",
+            (line_spacing as f32 / 2.4).round()
+        ));
+    }
+    if let Some(widow_control) = formatting.widow_control {
+        let lines = if widow_control { 2 } else { 0 };
+        body_layout.push_str(&format!(
+            " fo:widows=\"{}\" fo:orphans=\"{}\"",
+            lines, lines
+        ));
+    }
+    let quote_style = if formatting.citation_italics {
+        " fo:font-style=\"italic\""
+    } else {
+        ""
+    };
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-content xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:style="urn:oasis:names:tc:opendocument:xmlns:style:1.0" xmlns:text="urn:oasis:names:tc:opendocument:xmlns:text:1.0" xmlns:fo="urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0" office:version="1.2">
+ <office:automatic-styles>
+  <style:style style:name="PTitle" style:family="paragraph"><style:paragraph-properties fo:text-align="center" fo:margin-top="0.8cm"/><style:text-properties fo:font-weight="bold" fo:font-size="{title}"/></style:style>
+  <style:style style:name="PHeading" style:family="paragraph"><style:paragraph-properties fo:margin-top="0.4cm"/><style:text-properties fo:font-weight="bold" fo:font-size="{heading}" style:text-underline-style="solid" style:text-underline-width="auto" style:text-underline-color="font-color"/></style:style>
+  <style:style style:name="PDate" style:family="paragraph"><style:text-properties fo:font-weight="bold" style:text-underline-style="solid" style:text-underline-width="auto" style:text-underline-color="font-color"/></style:style>
+  <style:style style:name="PBody" style:family="paragraph"><style:paragraph-properties{body_layout}/><style:text-properties{body_size}/></style:style>
+  <style:style style:name="PQuote" style:family="paragraph"><style:paragraph-properties fo:margin-left="{indent:.2}cm"{body_layout}/><style:text-properties{body_size}{quote_style}/></style:style>
+  <style:style style:name="PSource" style:family="paragraph"><style:text-properties fo:font-style="italic" fo:font-size="{footer}"/></style:style>
+  <style:style style:name="PBreak" style:family="paragraph"><style:paragraph-properties fo:break-after="page"/></style:style>
+  <style:style style:name="Tb" style:family="text"><style:text-properties fo:font-weight="bold"/></style:style>
+  <style:style style:name="Ti" style:family="text"><style:text-properties fo:font-style="italic"/></style:style>
+  <style:style style:name="Tu" style:family="text"><style:text-properties style:text-underline-style="solid" style:text-underline-width="auto" style:text-underline-color="font-color"/></style:style>
+ </office:automatic-styles>
+{CONTENT_BODY}"#,
+        title = pt(formatting.title_size),
+        heading = pt(formatting.heading_size),
+        footer = pt(formatting.footer_size),
+        // 567 twentieths of a point per cm
+        indent = formatting.citation_indent as f32 / 567.0,
+    )
+}
+
+fn meta(attribution: Option<&str>) -> String {
+    let user_defined = attribution
+        .map(|attribution| {
+            format!(
+                "<meta:user-defined meta:name=\"Attribution\">{}</meta:user-defined>",
+                xml_escape(attribution)
+            )
+        })
+        .unwrap_or_default();
+
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<office:document-meta xmlns:office="urn:oasis:names:tc:opendocument:xmlns:office:1.0" xmlns:meta="urn:oasis:names:tc:opendocument:xmlns:meta:1.0" office:version="1.2"><office:meta><meta:generator>ribav-scraper</meta:generator>{}</office:meta></office:document-meta>
+"#,
+        user_defined
+    )
+}
+
+fn read_content(path: &Path) -> Result<String> {
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut archive = ZipArchive::new(file)
+        .with_context(|| format!("{} is not an OpenDocument file", path.display()))?;
+    let mut content = String::new();
+    archive
+        .by_name("content.xml")
+        .with_context(|| format!("No content.xml in {}", path.display()))?
+        .read_to_string(&mut content)?;
+
+    Ok(content)
+}
+
+fn write_package(path: &Path, content: &str, meta: &str) -> Result<()> {
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut zip = ZipWriter::new(file);
+
+    // The mimetype has to be the first entry, uncompressed
+    zip.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    zip.write_all(MIMETYPE.as_bytes())?;
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("META-INF/manifest.xml", options)?;
+    zip.write_all(MANIFEST.as_bytes())?;
+    zip.start_file("content.xml", options)?;
+    zip.write_all(content.as_bytes())?;
+    zip.start_file("meta.xml", options)?;
+    zip.write_all(meta.as_bytes())?;
+
+    zip.finish()
+        .with_context(|| format!("Failed to finalize {}", path.display()))?;
+
+    Ok(())
+}
+
+/// Consecutive runs split into citation and regular paragraphs.
+fn group_citations<'r, 'a>(runs: &'r [Run<'a>]) -> Vec<(bool, Vec<&'r Run<'a>>)> {
+    let mut groups: Vec<(bool, Vec<&Run>)> = Vec::new();
+    for run in runs {
+        let citation = is_citation(run);
+        match groups.last_mut() {
+            Some((last, group)) if *last == citation => group.push(run),
+            _ => groups.push((citation, vec![run])),
+        }
+    }
+    groups
+}
+
+fn paragraph(style: &str, text: &str) -> String {
+    format!(
+        "   <text:p text:style-name=\"{}\">{}</text:p>\n",
+        style, text
+    )
+}
+
+/// Paragraph listed in the outline at `level`, a plain one when `None`.
+fn outline_paragraph(style: &str, level: Option<isize>, text: &str) -> String {
+    match level {
+        // ODF outline levels start at 1
+        Some(level) => format!(
+            "   <text:h text:style-name=\"{}\" text:outline-level=\"{}\">{}</text:h>\n",
+            style,
+            level + 1,
+            text
+        ),
+        None => paragraph(style, text),
+    }
+}
+
+fn span(run: &Run<'_>) -> String {
+    let property = run.property.as_ref();
+    let mut styles = Vec::new();
+    if property.is_some_and(|p| p.bold.as_ref().is_some_and(|b| b.value != Some(false))) {
+        styles.push("Tb");
+    }
+    if property.is_some_and(|p| p.italics.as_ref().is_some_and(|i| i.value != Some(false))) {
+        styles.push("Ti");
+    }
+    if property.is_some_and(|p| p.underline.is_some()) {
+        styles.push("Tu");
+    }
+
+    let mut text = String::new();
+    for content in &run.content {
+        match content {
+            RunContent::Text(t) => text.push_str(&odf_text(&t.text)),
+            RunContent::Break(_) => text.push_str("<text:line-break/>"),
+            _ => {}
+        }
+    }
+
+    // Nested spans combine their styles
+    for style in styles.iter().rev() {
+        text = format!(
+            "<text:span text:style-name=\"{}\">{}</text:span>",
+            style, text
+        );
+    }
+    text
+}
+
+/// Escaped text, ODF collapsing whitespace unless spelled out with `<text:s/>`.
+fn odf_text(text: &str) -> String {
+    let mut odf = String::new();
+    let mut previous_space = true;
+    for c in text.chars() {
+        if c == ' ' && previous_space {
+            odf.push_str("<text:s/>");
+        } else {
+            odf.push_str(&xml_escape(&c.to_string()));
+        }
+        previous_space = c == ' ';
+    }
+    odf
+}
@@ -0,0 +1,36 @@
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::fs::File;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Bundles `files` into a timestamped zip archive created in `dest_dir`, returning its path.
+pub fn package_zip(files: &[PathBuf], dest_dir: &Path) -> Result<PathBuf> {
+    let archive_path = dest_dir.join(format!(
+        "ribav-{}.zip",
+        Local::now().format("%Y%m%d-%H%M%S")
+    ));
+
+    let archive = File::create(&archive_path)
+        .with_context(|| format!("Failed to create archive {}", archive_path.display()))?;
+    let mut zip = ZipWriter::new(archive);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for file in files {
+        let name = file
+            .file_name()
+            .ok_or_else(|| anyhow::anyhow!("Invalid file to package: {}", file.display()))?
+            .to_string_lossy();
+        let bytes = std::fs::read(file)
+            .with_context(|| format!("Failed to read {} for packaging", file.display()))?;
+
+        zip.start_file(name, options)?;
+        zip.write_all(&bytes)?;
+    }
+
+    zip.finish().context("Failed to finalize zip archive")?;
+
+    Ok(archive_path)
+}
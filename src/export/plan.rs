@@ -0,0 +1,79 @@
+use crate::export::csv::push_record;
+use crate::report::report::TopicStats;
+use anyhow::{Context, Result};
+use std::path::Path;
+
+/// A topic placed in a printed volume.
+#[derive(Debug, Clone)]
+pub struct PlannedTopic<'a> {
+    pub volume: usize,
+    /// Page of the volume the topic starts on
+    pub first_page: usize,
+    pub pages: usize,
+    pub topic: &'a TopicStats,
+}
+
+/// Fills the volumes one after the other, keeping topics of a category together and never
+/// splitting a topic across two volumes.
+pub fn plan_volumes(
+    topics: &[TopicStats],
+    pages_per_volume: usize,
+    words_per_page: usize,
+) -> Vec<PlannedTopic<'_>> {
+    let mut topics = topics.iter().collect::<Vec<_>>();
+    topics.sort_by(|a, b| (&a.category, &a.title).cmp(&(&b.category, &b.title)));
+
+    let mut plan = Vec::with_capacity(topics.len());
+    let mut volume = 1;
+    let mut used = 0;
+    for topic in topics {
+        let pages = topic.words.div_ceil(words_per_page).max(1);
+        if used > 0 && used + pages > pages_per_volume {
+            volume += 1;
+            used = 0;
+        }
+
+        plan.push(PlannedTopic {
+            volume,
+            first_page: used + 1,
+            pages,
+            topic,
+        });
+        used += pages;
+    }
+
+    plan
+}
+
+pub fn write_plan_csv<P: AsRef<Path>>(plan: &[PlannedTopic], path: P) -> Result<()> {
+    let mut csv = String::new();
+    push_record(
+        &mut csv,
+        &[
+            "volume",
+            "first_page",
+            "pages",
+            "words",
+            "category",
+            "title",
+            "url",
+        ],
+    );
+    for planned in plan {
+        push_record(
+            &mut csv,
+            &[
+                planned.volume.to_string(),
+                planned.first_page.to_string(),
+                planned.pages.to_string(),
+                planned.topic.words.to_string(),
+                planned.topic.category.clone(),
+                planned.topic.title.clone(),
+                planned.topic.url.clone(),
+            ],
+        );
+    }
+
+    std::fs::write(path.as_ref(), csv)
+        .with_context(|| format!("Failed to write volume plan {}", path.as_ref().display()))
+}
@@ -0,0 +1,73 @@
+use crate::export::custom_properties::{protection_reason, read_custom_properties};
+use crate::export::docx::docx_error;
+use crate::utils::constants::INDEX_DOCX_FILE;
+use anyhow::{Context, Result};
+use docx_rust::{DocxError, DocxFile};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Where the corrupted documents of the output directory are moved.
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+/// Moves the category documents of `output_dir` that docx-rust cannot read back, as the next
+/// append would have to, to its quarantine directory, returning the paths they were at. The
+/// index, the delta documents and the protected documents are left alone, the scraper does not
+/// append to them.
+pub fn quarantine_corrupted(output_dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut corrupted = Vec::new();
+    if !output_dir.exists() {
+        return Ok(corrupted);
+    }
+
+    let entries = std::fs::read_dir(output_dir)
+        .with_context(|| format!("Failed to list {}", output_dir.display()))?;
+    let mut documents = entries
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<std::io::Result<Vec<_>>>()?;
+    documents.retain(|path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        name.ends_with(".docx") && !name.ends_with(".delta.docx") && name != INDEX_DOCX_FILE
+    });
+    documents.sort();
+
+    for path in documents {
+        let error = match DocxFile::from_file(&path) {
+            Ok(file) => file.parse().err(),
+            Err(e) => Some(e),
+        };
+        let e = match error {
+            None => continue,
+            // Open in Word, or unreadable: not corrupted, the append reports it
+            Some(DocxError::IO(e)) => {
+                warn!("Failed to check {}: {}", path.display(), e);
+                continue;
+            }
+            Some(e) => docx_error(e, "parse", &path),
+        };
+        // Edited by hand in a way docx-rust does not follow. A document without readable
+        // properties is no zip archive at all, truncated rather than edited.
+        if let Some(reason) = read_custom_properties(&path)
+            .ok()
+            .and_then(|properties| protection_reason(&properties))
+        {
+            warn!("Failed to parse {}, left as is: {}", path.display(), reason);
+            continue;
+        }
+        let quarantine_dir = output_dir.join(QUARANTINE_DIR);
+        std::fs::create_dir_all(&quarantine_dir)
+            .with_context(|| format!("Failed to create {}", quarantine_dir.display()))?;
+        // Timestamped, a document corrupted again does not replace the first one
+        let name = format!(
+            "{}.{}.docx",
+            path.file_stem().unwrap_or_default().to_string_lossy(),
+            chrono::Utc::now().format("%Y%m%dT%H%M%S")
+        );
+        let target = quarantine_dir.join(name);
+        warn!("{:#}, moving it to {}", e, target.display());
+        std::fs::rename(&path, &target)
+            .with_context(|| format!("Failed to quarantine {}", path.display()))?;
+        corrupted.push(path);
+    }
+
+    Ok(corrupted)
+}
@@ -0,0 +1,65 @@
+use crate::config::config::QuestionSummary;
+use crate::export::docx::plain_text;
+use docx_rust::document::Run;
+
+const MAX_SUMMARY_CHARS: usize = 200;
+
+/// One-line summary of a question, introducing its answer in the condensed edition.
+///
+/// A `generated` summary (see [`crate::llm::summarizer`]) is preferred to the first sentence.
+pub fn summarize_question(
+    runs: &[Run<'_>],
+    generated: Option<&str>,
+    title: &str,
+    mode: QuestionSummary,
+) -> String {
+    let summary = match mode {
+        QuestionSummary::FirstSentence => generated
+            .map(str::to_owned)
+            .or_else(|| first_question(&plain_text(runs)))
+            .filter(|sentence| !sentence.is_empty())
+            .unwrap_or_else(|| title.to_owned()),
+        QuestionSummary::Title => title.to_owned(),
+    };
+
+    if summary.chars().count() > MAX_SUMMARY_CHARS {
+        summary.chars().take(MAX_SUMMARY_CHARS).collect::<String>() + "…"
+    } else {
+        summary
+    }
+}
+
+/// First sentence ending with a question mark, else the first sentence.
+fn first_question(text: &str) -> Option<String> {
+    let sentences = sentences(text);
+    sentences
+        .iter()
+        .find(|sentence| sentence.ends_with('?'))
+        .or(sentences.first())
+        .cloned()
+}
+
+/// Splits on line breaks and on sentence terminators followed by a space.
+fn sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    for line in text.lines() {
+        let mut current = String::new();
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            current.push(c);
+            let ends = matches!(c, '.' | '!' | '?' | '…')
+                && chars.peek().is_none_or(|next| next.is_whitespace());
+            if ends {
+                sentences.push(current.split_whitespace().collect::<Vec<_>>().join(" "));
+                current.clear();
+            }
+        }
+        let rest = current.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !rest.is_empty() {
+            sentences.push(rest);
+        }
+    }
+
+    sentences.retain(|sentence| !sentence.is_empty());
+    sentences
+}
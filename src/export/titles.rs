@@ -0,0 +1,91 @@
+use crate::config::config::TitlesConfig;
+
+/// Title of a topic as shown in the heading of the documents: capitalized, cleaned up as
+/// configured in `[export.titles]`.
+pub fn heading_title(title: &str, config: &TitlesConfig) -> String {
+    let mut title = title.trim().to_owned();
+    if config.sentence_case && is_all_caps(&title) {
+        title = title.to_lowercase();
+    }
+    if config.trim_punctuation {
+        title = trim_punctuation(&title);
+    }
+    if let Some(max_length) = config.max_length {
+        title = shorten(&title, max_length);
+    }
+
+    let mut chars = title.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => title,
+    }
+}
+
+/// Whether `title` is written in capitals only, "POURQUOI ON ALLUME 2 BOUGIES". The titles mixing
+/// cases are left as they are, their capitals being names.
+fn is_all_caps(title: &str) -> bool {
+    let mut letters = title.chars().filter(|c| c.is_alphabetic()).peekable();
+    letters.peek().is_some() && letters.all(|c| !c.is_lowercase())
+}
+
+/// Runs of question and exclamation marks reduced to one of each, "Vraiment ???!!" to
+/// "Vraiment ?!", and of more than three dots to an ellipsis.
+fn trim_punctuation(title: &str) -> String {
+    let mut trimmed = String::with_capacity(title.len());
+    // Dots and marks of the run being read
+    let (mut dots, mut marks) = (0, String::new());
+    let flush = |trimmed: &mut String, dots: &mut usize, marks: &mut String| {
+        trimmed.push_str(&".".repeat((*dots).min(3)));
+        trimmed.push_str(marks);
+        *dots = 0;
+        marks.clear();
+    };
+    for c in title.chars() {
+        match c {
+            '.' if marks.is_empty() => dots += 1,
+            '?' | '!' if dots == 0 => {
+                if !marks.contains(c) {
+                    marks.push(c);
+                }
+            }
+            '.' => {
+                flush(&mut trimmed, &mut dots, &mut marks);
+                dots = 1;
+            }
+            '?' | '!' => {
+                flush(&mut trimmed, &mut dots, &mut marks);
+                marks.push(c);
+            }
+            _ => {
+                flush(&mut trimmed, &mut dots, &mut marks);
+                trimmed.push(c);
+            }
+        }
+    }
+    flush(&mut trimmed, &mut dots, &mut marks);
+    trimmed
+}
+
+/// `title` cut at the last word fitting in `max_length` characters, ellipsis included.
+fn shorten(title: &str, max_length: usize) -> String {
+    if title.chars().count() <= max_length {
+        return title.to_owned();
+    }
+    let mut shortened = String::new();
+    for word in title.split_whitespace() {
+        let length = shortened.chars().count() + usize::from(!shortened.is_empty());
+        if length + word.chars().count() + 1 > max_length {
+            break;
+        }
+        if !shortened.is_empty() {
+            shortened.push(' ');
+        }
+        shortened.push_str(word);
+    }
+    // A first word longer than the limit is cut
+    if shortened.is_empty() {
+        shortened = title.chars().take(max_length.saturating_sub(1)).collect();
+    }
+    let shortened = shortened.trim_end_matches([',', ';', ':', '-', ' ']);
+    format!("{}…", shortened)
+}
@@ -0,0 +1,48 @@
+use docx_rust::document::{Run, RunContent};
+use regex::Regex;
+use std::sync::LazyLock;
+
+const NARROW_NBSP: &str = "\u{202F}";
+const NBSP: &str = "\u{A0}";
+
+static RULES: LazyLock<Vec<(Regex, String)>> = LazyLock::new(|| {
+    vec![
+        // Only punctuation ending a word is concerned, not "?" in urls or ":" in "10:15"
+        (
+            Regex::new(r"([^\s;:!?])[ \u{A0}\u{202F}]*([;!?]+)(\s|$)").unwrap(),
+            format!("${{1}}{NARROW_NBSP}${{2}}${{3}}"),
+        ),
+        (
+            Regex::new(r"([^\s;:!?])[ \u{A0}\u{202F}]*:(\s|$)").unwrap(),
+            format!("${{1}}{NBSP}:${{2}}"),
+        ),
+        (
+            Regex::new(r#""\s*([^"]*?)\s*""#).unwrap(),
+            format!("«{NBSP}${{1}}{NBSP}»"),
+        ),
+        (Regex::new(r"\.\.\.").unwrap(), "…".to_string()),
+    ]
+});
+
+/// French print typography: (narrow) non-breaking spaces before ; : ! ?, « » quotes instead
+/// of straight ones and the ellipsis character.
+pub fn french_typography(text: &str) -> String {
+    RULES
+        .iter()
+        .fold(text.to_owned(), |text, (regex, replacement)| {
+            regex.replace_all(&text, replacement.as_str()).into_owned()
+        })
+}
+
+pub fn apply_to_runs<'a>(runs: Vec<Run<'a>>) -> Vec<Run<'a>> {
+    runs.into_iter()
+        .map(|mut run| {
+            for content in run.content.iter_mut() {
+                if let RunContent::Text(text) = content {
+                    text.text = french_typography(&text.text).into();
+                }
+            }
+            run
+        })
+        .collect()
+}
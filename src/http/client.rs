@@ -1,38 +1,114 @@
-use crate::utils::constants::BASE_URL;
-use crate::{post::post::Post, utils::functions::number_days_since_2020};
+use crate::config::config::SearchConfig;
+use crate::post::category::Category;
+use crate::post::post::{Pinned, Post};
+use crate::store::archive::RawArchive;
+use crate::utils::constants::MAX_PAGE_BYTES;
+use crate::utils::functions::number_days_since_2020;
 use anyhow::Result;
 use encoding_rs::WINDOWS_1252;
-use reqwest::{header, Client};
-use scraper::{selectable::Selectable, Html, Selector};
-use std::collections::HashMap;
+use reqwest::{header, Client, Response, StatusCode};
+use scraper::{selectable::Selectable, ElementRef, Html, Selector};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
 
 use tracing::warn;
 
-pub async fn get_posts_from_current_page(html: &Html) -> Result<HashMap<String, Post>> {
-    let mut posts = HashMap::new();
+// Days the search POST looks back, 0 meaning the whole archive
+static SEARCH_WINDOW_DAYS: AtomicI64 = AtomicI64::new(0);
 
-    let table_rows_selector = Selector::parse("table.forumline tr")
-        .map_err(|e| anyhow::anyhow!("Failed to parse row selector: {}", e))?;
+// Until when every request waits, after the forum answered 429 or 503
+static PAUSED_UNTIL: Mutex<Option<Instant>> = Mutex::new(None);
 
-    let cells_selector = Selector::parse("td")
-        .map_err(|e| anyhow::anyhow!("Failed to parse cell selector: {}", e))?;
+static RESULT_ROWS: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("table.forumline tr").expect("valid selector"));
+static CELLS: LazyLock<Selector> = LazyLock::new(|| Selector::parse("td").expect("valid selector"));
+static LINKS: LazyLock<Selector> = LazyLock::new(|| Selector::parse("a").expect("valid selector"));
+static ICONS: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("img").expect("valid selector"));
+static NEXT_RESULTS_PAGE: LazyLock<Selector> = LazyLock::new(|| {
+    Selector::parse(".nav a[href^=\"search.php?search_id\"]").expect("valid selector")
+});
 
-    let link_selector = Selector::parse("a")
-        .map_err(|e| anyhow::anyhow!("Failed to parse link selector: {}", e))?;
+/// Pause when a 429 or 503 comes without `Retry-After`.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// Longest pause honored, a larger `Retry-After` is capped.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(15 * 60);
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// Shown, lowercased, by the forum in place of the results once a `search_id` has expired.
+const SEARCH_EXPIRED_MESSAGES: [&str; 3] = [
+    "your search session expired",
+    "search session has expired",
+    "session de recherche a expiré",
+];
+
+// Where every fetched page is kept, see `scrape.archive_dir`
+static ARCHIVE: Mutex<Option<RawArchive>> = Mutex::new(None);
+
+// Where the pages are read from instead of the forum, see `set_replay`
+static REPLAY: Mutex<Option<RawArchive>> = Mutex::new(None);
+
+// Largest body read, see `scrape.max_page_bytes`
+static MAX_BODY_BYTES: AtomicU64 = AtomicU64::new(MAX_PAGE_BYTES);
+
+// Size of the bodies of every response received since the start of the process
+static BYTES_DOWNLOADED: AtomicU64 = AtomicU64::new(0);
+
+// Requests sent since the start of the process, retries included
+static REQUESTS_SENT: AtomicU64 = AtomicU64::new(0);
+
+/// Fails the pages larger than `bytes` from now on, before they are read whole.
+pub fn set_max_body_bytes(bytes: u64) {
+    MAX_BODY_BYTES.store(bytes, Ordering::Relaxed);
+}
+
+/// Requests sent since the start of the process.
+pub fn requests_sent() -> u64 {
+    REQUESTS_SENT.load(Ordering::Relaxed)
+}
+
+/// Bytes received since the start of the process.
+pub fn bytes_downloaded() -> u64 {
+    BYTES_DOWNLOADED.load(Ordering::Relaxed)
+}
+
+/// Reads every page from `archive` instead of the forum from now on, until set back to `None`,
+/// to regenerate documents from stored data.
+pub fn set_replay(archive: Option<RawArchive>) {
+    *REPLAY.lock().unwrap() = archive;
+}
+
+/// Keeps every page fetched from now on in `archive`.
+pub fn set_archive(archive: RawArchive) {
+    *ARCHIVE.lock().unwrap() = Some(archive);
+}
+
+/// Restricts the forum search to the last `days` days instead of the whole archive.
+pub fn set_search_window_days(days: i64) {
+    SEARCH_WINDOW_DAYS.store(days, Ordering::Relaxed);
+}
+
+pub async fn get_posts_from_current_page(
+    html: &Html,
+    base_url: &str,
+) -> Result<BTreeMap<String, Post>> {
+    let mut posts = BTreeMap::new();
 
     // Skip the first row if it's a header (adjust as needed)
-    let table_rows = html.select(&table_rows_selector).skip(1);
+    let table_rows = html.select(&RESULT_ROWS).skip(1);
 
     for row in table_rows {
         // Extracting cells
-        let cells: Vec<_> = row.select(&cells_selector).collect();
+        let cells: Vec<_> = row.select(&CELLS).collect();
         if cells.len() < 7 {
             // Not a valid row
             continue;
         }
 
         let title_cell = &cells[2];
-        let title_link = match title_cell.select(&link_selector).next() {
+        let title_link = match title_cell.select(&LINKS).next() {
             Some(link) => link,
             None => {
                 warn!("No title link found in cell");
@@ -52,11 +128,24 @@ pub async fn get_posts_from_current_page(html: &Html) -> Result<HashMap<String,
 
         let category = cells[1].text().collect::<String>();
 
+        let pinned = pinned(&cells[0], title_cell, &title);
+
+        let count = |cell: usize| cells[cell].text().collect::<String>().trim().parse().ok();
+
+        let last_post = cells[6].text().collect::<Vec<_>>().join(" ");
+        let last_post = last_post.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let url = without_highlight(&format!("{}{}", base_url, href));
         posts.insert(
-            format!("{}{}", BASE_URL, href),
+            url.clone(),
             Post {
+                url,
                 title,
-                category,
+                category: Category::parse(&category),
+                last_post: (!last_post.is_empty()).then_some(last_post),
+                replies: count(4),
+                views: count(5),
+                pinned,
                 ..Default::default()
             },
         );
@@ -65,10 +154,68 @@ pub async fn get_posts_from_current_page(html: &Html) -> Result<HashMap<String,
     Ok(posts)
 }
 
+/// Whether the row is an announcement or a sticky topic, from its folder icon or the "Annonce:" or
+/// "Post-it:" before the title.
+fn pinned(icon_cell: &ElementRef, title_cell: &ElementRef, title: &str) -> Pinned {
+    let icon = icon_cell
+        .select(&ICONS)
+        .next()
+        .and_then(|img| img.value().attr("src"))
+        .unwrap_or_default();
+    let text = title_cell.text().collect::<String>();
+    let prefix = text
+        .split(title)
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+    if icon.contains("announce") || prefix.starts_with("annonce") {
+        Pinned::Announcement
+    } else if icon.contains("sticky") || prefix.starts_with("post-it") {
+        Pinned::Sticky
+    } else {
+        Pinned::No
+    }
+}
+
+/// `url` without the `highlight` parameter the search adds to the topic links: the topic has a
+/// single url across searches, and its pages come without the search terms wrapped in spans.
+fn without_highlight(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_owned();
+    };
+    if !parsed.query_pairs().any(|(key, _)| key == "highlight") {
+        return url.to_owned();
+    }
+
+    let pairs = parsed
+        .query_pairs()
+        .filter(|(key, _)| key != "highlight")
+        .map(|(key, value)| (key.into_owned(), value.into_owned()))
+        .collect::<Vec<_>>();
+    if pairs.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    }
+    parsed.to_string()
+}
+
+/// Whether the forum answered with its expired search session page instead of the results.
+pub fn search_expired(html: &Html) -> bool {
+    let text = html
+        .root_element()
+        .text()
+        .collect::<String>()
+        .to_lowercase();
+    SEARCH_EXPIRED_MESSAGES
+        .iter()
+        .any(|message| text.contains(message))
+}
+
 pub fn find_next_page(html: &Html) -> Option<&str> {
     // Find the next page link
-    let next_page_selector = Selector::parse(".nav a[href^=\"search.php?search_id\"]").ok()?;
-    let next_page_link = html.select(&next_page_selector).next()?;
+    let next_page_link = html.select(&NEXT_RESULTS_PAGE).next()?;
 
     let href = next_page_link.value().attr("href")?;
     let base_link = href.split('&').next()?;
@@ -76,29 +223,143 @@ pub fn find_next_page(html: &Html) -> Option<&str> {
     Some(base_link)
 }
 
+/// Waits for the pause asked by the forum, if any, to be over.
+async fn wait_for_pause() {
+    let until = *PAUSED_UNTIL.lock().unwrap();
+    if let Some(until) = until {
+        tokio::time::sleep_until(until.into()).await;
+    }
+}
+
+/// Pauses every request until `delay` from now.
+fn pause_for(delay: Duration) {
+    let until = Instant::now() + delay;
+    let mut paused_until = PAUSED_UNTIL.lock().unwrap();
+    if paused_until.is_none_or(|current| current < until) {
+        *paused_until = Some(until);
+    }
+}
+
+/// Delay asked by a `Retry-After` header, in seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response
+        .headers()
+        .get(header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(
+        (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+            .to_std()
+            .unwrap_or_default(),
+    )
+}
+
 pub async fn get_html<S>(client: &Client, url: S) -> Result<(Html, S)>
 where
     S: reqwest::IntoUrl + Clone,
 {
-    let url_cloned = url.clone();
+    let (page, url) = get_page(client, url).await?;
+    Ok((Html::parse_document(&page), url))
+}
 
-    let response = if url.as_str().contains("search.php?search_id") {
-        client.get(url).send().await?
-    } else {
-        let mut headers = header::HeaderMap::new();
-        headers.insert(
-            header::CONTENT_TYPE,
-            header::HeaderValue::from_static("application/x-www-form-urlencoded"),
-        );
+/// Source of the page at `url`, decoded but not parsed, e.g. to be sent to another thread.
+pub async fn get_page<S>(client: &Client, url: S) -> Result<(String, S)>
+where
+    S: reqwest::IntoUrl + Clone,
+{
+    fetch(client, url, &SearchConfig::default()).await
+}
+
+/// Posts `search` to the search form at `url`.
+pub async fn search_html<S>(client: &Client, url: S, search: &SearchConfig) -> Result<(Html, S)>
+where
+    S: reqwest::IntoUrl + Clone,
+{
+    let (page, url) = fetch(client, url, search).await?;
+    Ok((Html::parse_document(&page), url))
+}
 
-        let days_since_2020 = number_days_since_2020();
-        let body = format!("search_keywords=&search_terms=any&search_author=Rav+Binyamin+Wattenberg&search_forum=-1&search_time={days}&search_fields=all&search_cat=-1&sort_by=0&sort_dir=DESC&show_results=topics&return_chars=200",
-            days = days_since_2020
+async fn fetch<S>(client: &Client, url: S, search: &SearchConfig) -> Result<(String, S)>
+where
+    S: reqwest::IntoUrl + Clone,
+{
+    if let Some(ref archive) = *REPLAY.lock().unwrap() {
+        let raw = archive
+            .latest(url.as_str())?
+            .ok_or_else(|| anyhow::anyhow!("{} is not in the archive", url.as_str()))?;
+        return Ok((decode_page(raw), url));
+    }
+
+    let mut attempts = 0;
+    loop {
+        wait_for_pause().await;
+
+        let response = send(client, url.clone(), search).await?;
+        let status = response.status();
+        if status != StatusCode::TOO_MANY_REQUESTS && status != StatusCode::SERVICE_UNAVAILABLE {
+            return read_page(response, url).await;
+        }
+
+        attempts += 1;
+        if attempts > MAX_RATE_LIMIT_RETRIES {
+            return Err(anyhow::anyhow!(
+                "Still {} after {} retries",
+                status,
+                MAX_RATE_LIMIT_RETRIES
+            ));
+        }
+        let delay = retry_after(&response)
+            .unwrap_or(DEFAULT_RETRY_AFTER)
+            .min(MAX_RETRY_AFTER);
+        warn!(
+            "{} from {}, pausing every request for {}s",
+            status,
+            url.as_str(),
+            delay.as_secs()
         );
+        pause_for(delay);
+    }
+}
 
-        client.post(url).headers(headers).body(body).send().await?
-    };
+async fn send<S: reqwest::IntoUrl>(
+    client: &Client,
+    url: S,
+    search: &SearchConfig,
+) -> Result<Response> {
+    REQUESTS_SENT.fetch_add(1, Ordering::Relaxed);
+    Ok(if url.as_str().contains("search.php?search_id") {
+        client.get(url).send().await?
+    } else {
+        let days = match SEARCH_WINDOW_DAYS.load(Ordering::Relaxed) {
+            0 => number_days_since_2020(),
+            days => days,
+        }
+        .to_string();
+        let form = [
+            ("search_keywords", search.keywords.as_str()),
+            ("search_terms", "any"),
+            ("search_author", search.author.as_str()),
+            ("search_forum", "-1"),
+            ("search_time", days.as_str()),
+            ("search_fields", "all"),
+            ("search_cat", "-1"),
+            ("sort_by", "0"),
+            ("sort_dir", "DESC"),
+            ("show_results", "topics"),
+            ("return_chars", "200"),
+        ];
 
+        client.post(url).form(&form).send().await?
+    })
+}
+
+async fn read_page<S: reqwest::IntoUrl>(response: Response, url: S) -> Result<(String, S)> {
     if !response.status().is_success() {
         return Err(anyhow::anyhow!(
             "Non-success HTTP status: {}",
@@ -106,15 +367,70 @@ where
         ));
     }
 
-    let res_bytes = response.bytes().await?;
-    let response_text = match String::from_utf8(res_bytes.to_vec()) {
-        Ok(text) => text,
-        Err(_) => {
-            // Attempt fallback encoding
-            let (decoded_text, _, _) = WINDOWS_1252.decode(&res_bytes);
-            decoded_text.to_string()
+    let page_url = response.url().to_string();
+    let max_bytes = MAX_BODY_BYTES.load(Ordering::Relaxed);
+    let too_large = || anyhow::anyhow!("Page larger than {} bytes", max_bytes);
+    if response
+        .content_length()
+        .is_some_and(|length| length > max_bytes)
+    {
+        return Err(too_large());
+    }
+    // Read chunk by chunk, the length announced by the server is not always there nor right
+    let mut response = response;
+    let mut res_bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await? {
+        BYTES_DOWNLOADED.fetch_add(chunk.len() as u64, Ordering::Relaxed);
+        if (res_bytes.len() + chunk.len()) as u64 > max_bytes {
+            return Err(too_large());
         }
+        res_bytes.extend_from_slice(&chunk);
+    }
+    let res_bytes = if ARCHIVE.lock().unwrap().is_some() {
+        archive_page(url.as_str().to_owned(), page_url, res_bytes).await
+    } else {
+        res_bytes
     };
+    Ok((decode_page(res_bytes), url))
+}
+
+/// Stores `raw` in the archive set by `set_archive`, giving it back once done.
+///
+/// Compressed and written on the blocking pool, the lock held there: the stores of the pages
+/// fetched at once append to the same index one after the other.
+async fn archive_page(url: String, page_url: String, raw: Vec<u8>) -> Vec<u8> {
+    let stored = tokio::task::spawn_blocking(move || {
+        if let Some(ref mut archive) = *ARCHIVE.lock().unwrap() {
+            // Kept under the url requested, the one a replay asks for, and the one redirected to
+            let mut urls = vec![url.as_str()];
+            if page_url != url {
+                urls.push(&page_url);
+            }
+            for archived_url in urls {
+                // Losing a page of the archive is no reason to fail the scrape
+                if let Err(e) = archive.store(archived_url, &raw) {
+                    warn!("Failed to archive {}: {:#}", archived_url, e);
+                }
+            }
+        }
+        raw
+    })
+    .await;
+
+    match stored {
+        Ok(raw) => raw,
+        Err(e) => std::panic::resume_unwind(e.into_panic()),
+    }
+}
 
-    Ok((Html::parse_document(&response_text), url_cloned))
+/// Text of a whole page, UTF-8 when it all is, windows-1252 otherwise.
+///
+/// Not decoded as the chunks arrive: the encoding is only known once every byte is checked, and
+/// the archive keeps the raw page anyway. `scrape.max_page_bytes` bounds the buffer instead.
+fn decode_page(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes).unwrap_or_else(|e| {
+        // Attempt fallback encoding
+        let (decoded_text, _, _) = WINDOWS_1252.decode(e.as_bytes());
+        decoded_text.into_owned()
+    })
 }
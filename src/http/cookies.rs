@@ -0,0 +1,32 @@
+use anyhow::{Context, Result};
+use reqwest_cookie_store::{CookieStore, CookieStoreMutex};
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+/// Cookies saved by the previous run at `path`, or an empty store on the first one.
+///
+/// The expired cookies are dropped, the forum then hands out a new session as usual.
+pub fn load_cookies(path: &Path) -> Result<Arc<CookieStoreMutex>> {
+    let store = if path.exists() {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        cookie_store::serde::json::load(BufReader::new(file))
+            .map_err(|e| anyhow::anyhow!("Failed to parse cookies {}: {}", path.display(), e))?
+    } else {
+        CookieStore::default()
+    };
+
+    Ok(Arc::new(CookieStoreMutex::new(store)))
+}
+
+/// Saves the cookies for the next run, session cookies included: the forum session id has no
+/// expiry date but stays valid on the server for a while.
+pub fn save_cookies(store: &CookieStoreMutex, path: &Path) -> Result<()> {
+    let mut file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let store = store.lock().unwrap();
+    cookie_store::serde::json::save_incl_expired_and_nonpersistent(&store, &mut file)
+        .map_err(|e| anyhow::anyhow!("Failed to write cookies {}: {}", path.display(), e))
+}
@@ -0,0 +1,73 @@
+use anyhow::Result;
+use regex::Regex;
+use scraper::{Html, Selector};
+use serde::Serialize;
+use std::sync::LazyLock;
+
+static ROWS: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("table.forumline tr").expect("valid selector"));
+static SECTIONS: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("a.cattitle, span.cattitle").expect("valid selector"));
+static FORUM_LINKS: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("a.forumlink").expect("valid selector"));
+static COUNTS: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("td.row2").expect("valid selector"));
+
+/// A forum of the index, where the topics of one category are posted.
+#[derive(Debug, Clone, Serialize)]
+pub struct Forum {
+    pub id: u32,
+    /// Section of the index the forum is listed under
+    pub section: String,
+    pub name: String,
+    pub url: String,
+    pub topics: u64,
+    pub posts: u64,
+}
+
+/// Forums listed on the index page of the forum, in the order of the page.
+pub fn get_forums_from_index(html: &Html, base_url: &str) -> Result<Vec<Forum>> {
+    // viewforum.php?f=3, or forum-f3.html once rewritten
+    let id = Regex::new(r"(?:[?&]f=|-f)(\d+)")?;
+
+    let mut forums = Vec::new();
+    let mut section = String::new();
+    for row in html.select(&ROWS) {
+        if let Some(title) = row.select(&SECTIONS).next() {
+            section = title.text().collect::<String>().trim().to_string();
+            continue;
+        }
+
+        let Some(link) = row.select(&FORUM_LINKS).next() else {
+            continue;
+        };
+        let Some(href) = link.value().attr("href") else {
+            continue;
+        };
+        let Some(captures) = id.captures(href) else {
+            continue;
+        };
+
+        // Topics then posts, "1 204" written with a (non-breaking) space every thousand
+        let mut counts = row.select(&COUNTS).map(|cell| {
+            cell.text()
+                .collect::<String>()
+                .chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<String>()
+                .parse::<u64>()
+                .unwrap_or(0)
+        });
+
+        forums.push(Forum {
+            id: captures[1].parse()?,
+            section: section.clone(),
+            name: link.text().collect::<String>().trim().to_string(),
+            url: format!("{}{}", base_url, href),
+            topics: counts.next().unwrap_or(0),
+            posts: counts.next().unwrap_or(0),
+        });
+    }
+
+    Ok(forums)
+}
@@ -1 +1,4 @@
 pub mod client;
+pub mod cookies;
+pub mod forums;
+pub mod profiles;
@@ -0,0 +1,60 @@
+use crate::http::client::get_html;
+use anyhow::Result;
+use reqwest::Client;
+use scraper::{ElementRef, Html, Selector};
+use std::sync::LazyLock;
+
+static ROWS: LazyLock<Selector> = LazyLock::new(|| Selector::parse("tr").expect("valid selector"));
+
+/// Public profile of a participant, used to spot the rare posters who may need anonymizing.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Profile {
+    pub url: String,
+    pub author: String,
+    /// As displayed by the forum, e.g. "14 Fév 2021"
+    pub registered: Option<String>,
+    pub posts: Option<u64>,
+}
+
+/// Reads the registration date and post count off a profile page.
+pub fn parse_profile(html: &Html, url: &str, author: &str) -> Result<Profile> {
+    let mut profile = Profile {
+        url: url.to_owned(),
+        author: author.to_owned(),
+        registered: None,
+        posts: None,
+    };
+    for row in html.select(&ROWS) {
+        // Only the cells of this row, the layout nests tables
+        let cells: Vec<String> = row
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|cell| cell.value().name() == "td")
+            .map(|cell| cell.text().collect::<String>())
+            .collect();
+        let [label, value, ..] = cells.as_slice() else {
+            continue;
+        };
+
+        let label = label.trim().trim_end_matches(':').trim();
+        let value = value.trim();
+        match label {
+            "Inscrit le" | "Joined" => profile.registered = Some(value.to_owned()),
+            "Messages" | "Total posts" => {
+                profile.posts = value
+                    .split(|c: char| !c.is_ascii_digit())
+                    .next()
+                    .and_then(|count| count.parse().ok())
+            }
+            _ => {}
+        }
+    }
+
+    Ok(profile)
+}
+
+/// Fetches the profile of `author` at `url`.
+pub async fn get_profile(client: &Client, url: &str, author: &str) -> Result<Profile> {
+    let (html, _) = get_html(client, url).await?;
+    parse_profile(&html, url, author)
+}
@@ -0,0 +1,41 @@
+//! Scraper of the answers of Rav Binyamin Wattenberg on the forum techouvot.com, exported to
+//! DOCX, ODT, LaTeX, CSV or Anki documents.
+//!
+//! The binary runs the whole scrape. The library lets integrators reuse its parts, e.g. read the
+//! messages of a topic page:
+//!
+//! ```
+//! use scraper::Html;
+//! use scrapper::post::post::parse_messages;
+//!
+//! let page = Html::parse_document(
+//!     r#"<div class="container"><div class="overflow-hidden border-blue-500"><div>
+//!       <div class="flex">
+//!         <div><strong class="block mb-2">Rav Binyamin Wattenberg</strong></div>
+//!         <div>
+//!           <a class="text-blue-link" href="viewtopic.php?p=5002#5002">Posté le: 13/03/2023 08:30</a>
+//!           <div class="py-4 postrow-message">Il faut allumer <span style="font-weight:bold">juste avant</span>.</div>
+//!         </div>
+//!       </div>
+//!     </div></div></div>"#,
+//! );
+//! let messages = parse_messages(&page).unwrap();
+//! assert_eq!(messages[0].id.as_deref(), Some("5002"));
+//! assert!(messages[0].is_answer());
+//! assert_eq!(messages[0].text(), "Il faut allumer juste avant.");
+//! ```
+//!
+//! Complete programs are in `examples/`: a topic to Markdown, documents rebuilt from the archive
+//! of the pages, statistics of a run.
+pub mod cli;
+pub mod config;
+pub mod delivery;
+pub mod export;
+pub mod http;
+pub mod llm;
+pub mod parser;
+pub mod post;
+pub mod report;
+pub mod state;
+pub mod store;
+pub mod utils;
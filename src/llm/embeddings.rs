@@ -0,0 +1,19 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+
+/// Turns a text into a vector, close to the vectors of texts with a similar meaning.
+pub trait Embedder: Send + Sync {
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>>>;
+}
+
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot = a.iter().zip(b).map(|(x, y)| x * y).sum::<f32>();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norms = norm(a) * norm(b);
+
+    if norms == 0.0 {
+        0.0
+    } else {
+        dot / norms
+    }
+}
@@ -0,0 +1,9 @@
+pub mod embeddings;
+#[cfg(feature = "llm")]
+pub mod openai;
+#[cfg(feature = "embeddings")]
+pub mod semantic;
+pub mod summarizer;
+#[cfg(feature = "translate")]
+pub mod translation;
+pub mod translator;
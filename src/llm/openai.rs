@@ -0,0 +1,163 @@
+use crate::config::config::{EmbeddingsConfig, SummarizerConfig};
+use crate::llm::embeddings::Embedder;
+use crate::llm::summarizer::Summarizer;
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PROMPT: &str = "Résume la question suivante en une seule phrase, en français, \
+sans formule de politesse. Réponds uniquement par cette phrase.";
+
+#[derive(Debug, Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+    temperature: f32,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    choices: Vec<Choice>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Choice {
+    message: ChoiceMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChoiceMessage {
+    content: String,
+}
+
+/// Chat completions client, for OpenAI or any server exposing the same API (Ollama, llama.cpp...).
+pub struct OpenAiSummarizer {
+    client: Client,
+    config: SummarizerConfig,
+}
+
+impl OpenAiSummarizer {
+    pub fn new(client: Client, config: SummarizerConfig) -> Self {
+        Self { client, config }
+    }
+
+    async fn complete(&self, question: &str) -> Result<String> {
+        let url = format!(
+            "{}/chat/completions",
+            self.config.endpoint.trim_end_matches('/')
+        );
+        let request = ChatRequest {
+            model: &self.config.model,
+            messages: vec![
+                ChatMessage {
+                    role: "system",
+                    content: self.config.prompt.as_deref().unwrap_or(DEFAULT_PROMPT),
+                },
+                ChatMessage {
+                    role: "user",
+                    content: question,
+                },
+            ],
+            temperature: 0.0,
+        };
+
+        let mut builder = self.client.post(&url).json(&request);
+        if let Some(ref api_key) = self.config.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+
+        let response: ChatResponse = builder
+            .send()
+            .await
+            .with_context(|| format!("Failed to call {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Summarization rejected by {}", url))?
+            .json()
+            .await
+            .context("Malformed chat completion response")?;
+
+        response
+            .choices
+            .into_iter()
+            .next()
+            .map(|choice| choice.message.content.trim().to_owned())
+            .ok_or_else(|| anyhow::anyhow!("Empty chat completion response from {}", url))
+    }
+}
+
+impl Summarizer for OpenAiSummarizer {
+    fn summarize<'a>(&'a self, question: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(self.complete(question))
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct EmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingResponse {
+    data: Vec<EmbeddingData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EmbeddingData {
+    embedding: Vec<f32>,
+}
+
+/// Embeddings client for OpenAI-compatible servers.
+pub struct OpenAiEmbedder {
+    client: Client,
+    config: EmbeddingsConfig,
+}
+
+impl OpenAiEmbedder {
+    pub fn new(client: Client, config: EmbeddingsConfig) -> Self {
+        Self { client, config }
+    }
+
+    async fn request(&self, text: &str) -> Result<Vec<f32>> {
+        let url = format!("{}/embeddings", self.config.endpoint.trim_end_matches('/'));
+        let request = EmbeddingRequest {
+            model: &self.config.model,
+            input: text,
+        };
+
+        let mut builder = self.client.post(&url).json(&request);
+        if let Some(ref api_key) = self.config.api_key {
+            builder = builder.bearer_auth(api_key);
+        }
+
+        let response: EmbeddingResponse = builder
+            .send()
+            .await
+            .with_context(|| format!("Failed to call {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Embedding rejected by {}", url))?
+            .json()
+            .await
+            .context("Malformed embeddings response")?;
+
+        response
+            .data
+            .into_iter()
+            .next()
+            .map(|data| data.embedding)
+            .ok_or_else(|| anyhow::anyhow!("Empty embeddings response from {}", url))
+    }
+}
+
+impl Embedder for OpenAiEmbedder {
+    fn embed<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<Vec<f32>>> {
+        Box::pin(self.request(text))
+    }
+}
@@ -0,0 +1,69 @@
+use crate::llm::embeddings::{cosine_similarity, Embedder};
+use crate::post::post::Post;
+use crate::store::sqlite::{Database, StoredEmbedding};
+use anyhow::Result;
+use std::collections::BTreeMap;
+
+/// A topic matching a semantic search, scored by its closest exchange.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub topic_url: String,
+    pub title: String,
+    pub category: String,
+    pub score: f32,
+}
+
+/// Embeds each question/answers exchange of the topic into the database.
+pub async fn index_post(
+    post: &Post,
+    embedder: &dyn Embedder,
+    db: &Database,
+    model: &str,
+) -> Result<()> {
+    for (exchange, text) in post.exchanges().iter().enumerate() {
+        let vector = embedder.embed(text).await?;
+        db.upsert_embedding(
+            &StoredEmbedding {
+                topic_url: post.url.clone(),
+                title: post.title.clone(),
+                category: post.category.to_string(),
+                exchange,
+                vector,
+            },
+            model,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Topics whose exchanges are the closest to `query`, best first.
+pub async fn search(
+    query: &str,
+    embedder: &dyn Embedder,
+    db: &Database,
+    model: &str,
+    limit: usize,
+) -> Result<Vec<SearchHit>> {
+    let query = embedder.embed(query).await?;
+
+    let mut best: BTreeMap<String, SearchHit> = BTreeMap::new();
+    for stored in db.embeddings(model)? {
+        let score = cosine_similarity(&query, &stored.vector);
+        let hit = best
+            .entry(stored.topic_url.clone())
+            .or_insert_with(|| SearchHit {
+                topic_url: stored.topic_url,
+                title: stored.title,
+                category: stored.category,
+                score,
+            });
+        hit.score = hit.score.max(score);
+    }
+
+    let mut hits = best.into_values().collect::<Vec<_>>();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits.truncate(limit);
+
+    Ok(hits)
+}
@@ -0,0 +1,7 @@
+use anyhow::Result;
+use futures::future::BoxFuture;
+
+/// Produces a one-sentence summary of a question, stored on the message for the exporters.
+pub trait Summarizer: Send + Sync {
+    fn summarize<'a>(&'a self, question: &'a str) -> BoxFuture<'a, Result<String>>;
+}
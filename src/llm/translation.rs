@@ -0,0 +1,149 @@
+use crate::config::config::{TranslationService, TranslatorConfig};
+use crate::llm::translator::Translator;
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+const DEEPL_ENDPOINT: &str = "https://api-free.deepl.com";
+const GOOGLE_ENDPOINT: &str = "https://translation.googleapis.com";
+
+#[derive(Debug, Serialize)]
+struct DeepLRequest<'a> {
+    text: [&'a str; 1],
+    source_lang: &'a str,
+    target_lang: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepLResponse {
+    translations: Vec<DeepLTranslation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeepLTranslation {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct GoogleRequest<'a> {
+    q: &'a str,
+    source: &'a str,
+    target: &'a str,
+    format: &'a str,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleResponse {
+    data: GoogleData,
+}
+
+#[derive(Debug, Deserialize)]
+struct GoogleData {
+    translations: Vec<GoogleTranslation>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleTranslation {
+    translated_text: String,
+}
+
+/// Client for the DeepL or Google Cloud Translation v2 API, translating from French.
+pub struct ApiTranslator {
+    client: Client,
+    config: TranslatorConfig,
+}
+
+impl ApiTranslator {
+    pub fn new(client: Client, config: TranslatorConfig) -> Self {
+        Self { client, config }
+    }
+
+    async fn request(&self, text: &str) -> Result<String> {
+        match self.config.service {
+            TranslationService::DeepL => self.deepl(text).await,
+            TranslationService::Google => self.google(text).await,
+        }
+    }
+
+    fn endpoint(&self, default: &str) -> String {
+        self.config
+            .endpoint
+            .as_deref()
+            .unwrap_or(default)
+            .trim_end_matches('/')
+            .to_owned()
+    }
+
+    async fn deepl(&self, text: &str) -> Result<String> {
+        let url = format!("{}/v2/translate", self.endpoint(DEEPL_ENDPOINT));
+        let request = DeepLRequest {
+            text: [text],
+            source_lang: "FR",
+            target_lang: &self.config.target_lang,
+        };
+
+        let response: DeepLResponse = self
+            .client
+            .post(&url)
+            .header(
+                "Authorization",
+                format!("DeepL-Auth-Key {}", self.config.api_key),
+            )
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to call {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Translation rejected by {}", url))?
+            .json()
+            .await
+            .context("Malformed DeepL response")?;
+
+        response
+            .translations
+            .into_iter()
+            .next()
+            .map(|translation| translation.text)
+            .ok_or_else(|| anyhow::anyhow!("Empty translation response from {}", url))
+    }
+
+    async fn google(&self, text: &str) -> Result<String> {
+        let url = format!("{}/language/translate/v2", self.endpoint(GOOGLE_ENDPOINT));
+        let request = GoogleRequest {
+            q: text,
+            source: "fr",
+            target: &self.config.target_lang,
+            format: "text",
+        };
+
+        let response: GoogleResponse = self
+            .client
+            .post(&url)
+            .query(&[("key", &self.config.api_key)])
+            .json(&request)
+            .send()
+            .await
+            .with_context(|| format!("Failed to call {}", url))?
+            .error_for_status()
+            .with_context(|| format!("Translation rejected by {}", url))?
+            .json()
+            .await
+            .context("Malformed Google Translate response")?;
+
+        response
+            .data
+            .translations
+            .into_iter()
+            .next()
+            .map(|translation| translation.translated_text)
+            .ok_or_else(|| anyhow::anyhow!("Empty translation response from {}", url))
+    }
+}
+
+impl Translator for ApiTranslator {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(self.request(text))
+    }
+}
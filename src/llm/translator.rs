@@ -0,0 +1,70 @@
+use crate::export::manifest::sha256_hex;
+use anyhow::{Context, Result};
+use futures::future::BoxFuture;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// Translates the text of a message into the configured target language.
+pub trait Translator: Send + Sync {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>>;
+}
+
+/// Keeps the translations already paid for, keyed by the hash of the target language and text,
+/// so that a message is only sent to the translation service once.
+pub struct CachedTranslator {
+    inner: Box<dyn Translator>,
+    target_lang: String,
+    path: PathBuf,
+    cache: Mutex<HashMap<String, String>>,
+}
+
+impl CachedTranslator {
+    pub fn new(inner: Box<dyn Translator>, target_lang: &str, path: &Path) -> Result<Self> {
+        let cache = if path.exists() {
+            let content = std::fs::read_to_string(path)
+                .with_context(|| format!("Failed to read translation cache {}", path.display()))?;
+            serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse translation cache {}", path.display()))?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(Self {
+            inner,
+            target_lang: target_lang.to_owned(),
+            path: path.to_path_buf(),
+            cache: Mutex::new(cache),
+        })
+    }
+
+    fn key(&self, text: &str) -> String {
+        sha256_hex(format!("{}\n{}", self.target_lang, text).as_bytes())
+    }
+
+    async fn cached(&self, text: &str) -> Result<String> {
+        let key = self.key(text);
+        if let Some(translation) = self.cache.lock().unwrap().get(&key) {
+            return Ok(translation.clone());
+        }
+
+        let translation = self.inner.translate(text).await?;
+
+        // Saved on every new translation, an interrupted run keeps what it paid for
+        let mut cache = self.cache.lock().unwrap();
+        cache.insert(key, translation.clone());
+        let json = serde_json::to_string_pretty(&*cache)
+            .context("Failed to serialize translation cache")?;
+        std::fs::write(&self.path, json).with_context(|| {
+            format!("Failed to write translation cache {}", self.path.display())
+        })?;
+
+        Ok(translation)
+    }
+}
+
+impl Translator for CachedTranslator {
+    fn translate<'a>(&'a self, text: &'a str) -> BoxFuture<'a, Result<String>> {
+        Box::pin(self.cached(text))
+    }
+}
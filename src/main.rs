@@ -1,85 +1,1250 @@
 use anyhow::{Context, Result};
-use futures::future::join_all;
+use clap::Parser;
+use futures::channel::mpsc;
+use futures::{stream, FutureExt, SinkExt, StreamExt};
 use reqwest::Client;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::process::ExitCode;
+use std::sync::Arc;
 use tokio::{self};
 
 use tracing::{info, warn, Level};
 
-mod http;
-mod parser;
-mod post;
-mod utils;
+use scrapper::cli::args::{
+    Args, Command, ExportArgs, ImportArgs, PackageFormat, PlanArgs, SemanticSearchArgs, Strategy,
+};
+use scrapper::cli::exit::Exit;
+use scrapper::config::config::{
+    Config, ExportFormat, PinnedTopics, ScrapeConfig, SearchConfig, SeriesMode, TopicBreak,
+};
+use scrapper::export::cross_refs::set_known_topics;
+use scrapper::export::custom_properties::{protection_reason, read_custom_properties};
+use scrapper::export::docx::is_in_use_error;
+use scrapper::export::feed::{entries_from_post, merge_entries, write_atom};
+use scrapper::export::ical::write_calendar;
+use scrapper::export::index::{index_entries, write_docx, write_markdown};
+use scrapper::export::journal::recover;
+use scrapper::export::manifest::Manifest;
+use scrapper::export::notice::write_notice;
+use scrapper::export::package::package_zip;
+use scrapper::export::plan::{plan_volumes, write_plan_csv};
+use scrapper::export::quarantine::quarantine_corrupted;
+use scrapper::http::client::{
+    bytes_downloaded, find_next_page, get_html, get_page, get_posts_from_current_page,
+    requests_sent, search_expired, search_html, set_archive, set_max_body_bytes, set_replay,
+    set_search_window_days,
+};
+use scrapper::http::cookies::{load_cookies, save_cookies};
+use scrapper::http::forums::get_forums_from_index;
+use scrapper::llm::summarizer::Summarizer;
+use scrapper::llm::translator::{CachedTranslator, Translator};
+use scrapper::parser::characters::{take_unmapped, CharacterMap};
+use scrapper::parser::coverage::Coverage;
+use scrapper::parser::hooks::register_hook;
+use scrapper::parser::parser::set_font_size;
+use scrapper::parser::transforms::TextTransforms;
+use scrapper::post::category::Category;
+use scrapper::post::context::{ScrapeContext, Stage};
+use scrapper::post::post::{category_output_path, set_topic_limits, Post, PostMessage};
+use scrapper::post::series::{link_series, merge_series};
+use scrapper::report::crash::{install_panic_hook, set_current_topic, take_crash};
+use scrapper::report::fidelity::lost_words;
+use scrapper::report::report::{FidelityLoss, Report};
+use scrapper::report::summary::RunSummary;
+use scrapper::state::import::{import_topics, read_document};
+use scrapper::state::pauses::pause_until;
+use scrapper::state::schedule::schedule_topics;
+use scrapper::state::state::{State, StoredTopic};
+use scrapper::store::archive::RawArchive;
+use scrapper::store::{open_store, Store};
+use scrapper::utils::constants::{
+    CALENDAR_FILE, COOKIES_FILE, CRASH_FILE, EXPORT_STAGING_DIR, FEED_FILE, INDEX_DOCX_FILE,
+    INDEX_MD_FILE, MANIFEST_FILE, NOTICE_FILE, REPORT_FILE, VOLUMES_FILE,
+};
 
-use http::client::{find_next_page, get_html, get_posts_from_current_page};
-use utils::constants::{BASE_URL, MAX_PAGES, PAGE_SIZE};
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args = Args::parse();
 
-#[tokio::main(flavor = "current_thread")] // Use current_thread runtime for blocking operations
-async fn main() -> Result<()> {
     // Initialize logging
     tracing_subscriber::FmtSubscriber::builder()
         .with_max_level(Level::INFO)
         .init();
+    install_panic_hook();
+
+    match run(args).await {
+        Ok(exit) => exit.into(),
+        Err(e) => {
+            eprintln!("Error: {:?}", e);
+            Exit::of(&e).into()
+        }
+    }
+}
+
+async fn run(args: Args) -> Result<Exit> {
+    let mut config = Config::load(&args.config)?;
+    config.export.answers_only |= args.answers_only;
+    config.export.delta |= args.delta;
+    config.export.drop.extend(&args.drop);
+    if let Some(format) = args.format {
+        config.export.format = format;
+    }
+    if args.page_break_between_topics && config.export.topic_break == TopicBreak::Blank {
+        config.export.topic_break = TopicBreak::Page;
+    }
+    if let Some(formatting) = args.formatting {
+        config.export.formatting = formatting;
+    }
+
+    match args.command {
+        Some(Command::Plan(ref plan)) => return plan_command(&config, plan).map(|_| Exit::Success),
+        Some(Command::SemanticSearch(ref search)) => {
+            return semantic_search_command(&config, search)
+                .await
+                .map(|_| Exit::Success)
+        }
+        Some(Command::Categories) => {
+            return categories_command(&config).await.map(|_| Exit::Success)
+        }
+        Some(Command::Export(_)) | Some(Command::Import(_)) | None => {}
+    }
+
+    set_font_size(config.export.font_size);
+    // Always registered, to warn about the characters left unmapped
+    register_hook(CharacterMap::from_config(&config.characters)?);
+    let transforms = TextTransforms::from_config(&config.transforms)?;
+    if !transforms.is_empty() {
+        register_hook(transforms);
+    }
+
+    let output_dir = config.scrape.output_dir.as_path();
+    std::fs::create_dir_all(output_dir)
+        .with_context(|| format!("Failed to create output directory {}", output_dir.display()))?;
 
     // Build a reqwest client with a timeout to be more production-ready
+    // The session of the previous run is reused rather than opening a new one every run
+    let cookies_path = output_dir.join(COOKIES_FILE);
+    let cookies = load_cookies(&cookies_path)?;
     let client = Client::builder()
-        .cookie_store(true)
+        .cookie_provider(cookies.clone())
         .timeout(std::time::Duration::from_secs(30))
         .build()
         .context("Failed to build HTTP client")?;
 
-    let url = "https://www.techouvot.com/search.php?mode=results";
-    let mut posts = HashMap::new();
+    if let Some(ref archive_dir) = config.scrape.archive_dir {
+        set_archive(RawArchive::new(archive_dir));
+    }
+    set_max_body_bytes(config.scrape.max_page_bytes);
+    set_topic_limits(
+        config.scrape.max_topic_messages,
+        config.scrape.max_topic_pages,
+    );
+
+    let summarizer: Option<Box<dyn Summarizer>> = match config.summarizer {
+        #[cfg(feature = "llm")]
+        Some(ref summarizer) => Some(Box::new(scrapper::llm::openai::OpenAiSummarizer::new(
+            client.clone(),
+            summarizer.clone(),
+        ))),
+        #[cfg(not(feature = "llm"))]
+        Some(_) => {
+            warn_feature_off("summarizer", "llm");
+            None
+        }
+        None => None,
+    };
+
+    #[cfg(feature = "embeddings")]
+    let index = match config.embeddings {
+        Some(ref embeddings) => Some((
+            scrapper::llm::openai::OpenAiEmbedder::new(client.clone(), embeddings.clone()),
+            scrapper::store::sqlite::Database::open(embeddings.database_path(output_dir))?,
+            embeddings.model.as_str(),
+        )),
+        None => None,
+    };
+    #[cfg(not(feature = "embeddings"))]
+    if config.embeddings.is_some() {
+        warn_feature_off("embeddings", "embeddings");
+    }
+
+    let translator: Option<CachedTranslator> = match config.translator {
+        #[cfg(feature = "translate")]
+        Some(ref translator) => Some(CachedTranslator::new(
+            Box::new(scrapper::llm::translation::ApiTranslator::new(
+                client.clone(),
+                translator.clone(),
+            )),
+            &translator.target_lang,
+            &output_dir.join(scrapper::utils::constants::TRANSLATION_CACHE_FILE),
+        )?),
+        #[cfg(not(feature = "translate"))]
+        Some(_) => {
+            warn_feature_off("translator", "translate");
+            None
+        }
+        None => None,
+    };
+
+    #[cfg(not(feature = "annotations"))]
+    if let Some(ref annotations) = config.export.annotations {
+        warn!(
+            "Annotations configured ({:?}) but the scraper was built without the `annotations` feature",
+            annotations
+        );
+    }
+
+    let store = open_store(&config.store, output_dir)?;
+    let mut state = store.load()?;
+
+    if let Some(Command::Import(ref import)) = args.command {
+        return import_command(&config, import, &client, store.as_ref(), &mut state)
+            .await
+            .map(|_| Exit::Success);
+    }
+
+    if let Some(Command::Export(ref export)) = args.command {
+        return export_command(
+            &config,
+            export,
+            &client,
+            summarizer.as_deref(),
+            translator
+                .as_ref()
+                .map(|translator| translator as &dyn Translator),
+            &state,
+        )
+        .await
+        .map(|_| Exit::Success);
+    }
+
+    let mut full = args.full;
+    loop {
+        if args.watch {
+            if let Some(until) = pause_until(&config.watch.pauses, chrono::Utc::now()) {
+                info!("Paused until {}", until.to_rfc3339());
+                let pause = (until - chrono::Utc::now()).to_std().unwrap_or_default();
+                tokio::time::sleep(pause).await;
+                continue;
+            }
+        }
+
+        // A panic ends the run, the state keeping the topics exported before it
+        let scraped = AssertUnwindSafe(scrape(
+            &args,
+            &config,
+            &client,
+            summarizer.as_deref(),
+            translator
+                .as_ref()
+                .map(|translator| translator as &dyn Translator),
+            #[cfg(feature = "embeddings")]
+            index.as_ref(),
+            store.as_ref(),
+            &mut state,
+            full,
+        ))
+        .catch_unwind()
+        .await;
+        save_cookies(&cookies, &cookies_path)?;
+        let Ok(scraped) = scraped else {
+            store.save(&state)?;
+            let crash_path = output_dir.join(CRASH_FILE);
+            if let Some(crash) = take_crash() {
+                crash.write(&crash_path)?;
+            }
+            anyhow::bail!("The scraper panicked, see {}", crash_path.display());
+        };
+        if !args.watch {
+            let (_, summary) = scraped?;
+            return Ok(if summary.topics_failed > 0 {
+                Exit::PartialFailure
+            } else {
+                Exit::Success
+            });
+        }
+
+        match scraped {
+            Ok((posts, _)) => {
+                let entries = posts
+                    .iter()
+                    .flat_map(|post| entries_from_post(post, &config.export.dates))
+                    .collect();
+                let added = merge_entries(&mut state.feed, entries, config.watch.feed_entries);
+                store.save(&state)?;
+
+                let feed_path = output_dir.join(FEED_FILE);
+                write_atom(&state.feed, &config.watch.feed_title, &feed_path)?;
+                info!("{} new answers published in {}", added, feed_path.display());
+            }
+            Err(e) => warn!("Scrape failed, retrying at the next interval: {:#}", e),
+        }
+
+        // Only the first pass of a watch can be a full one
+        full = false;
+        info!("Next scrape in {} minutes", config.watch.interval_minutes);
+        tokio::time::sleep(std::time::Duration::from_secs(
+            config.watch.interval_minutes * 60,
+        ))
+        .await;
+    }
+}
+
+/// Reads the topics of the documents back into the state, their urls being found by searching
+/// the whole archive without fetching any topic.
+async fn import_command(
+    config: &Config,
+    import: &ImportArgs,
+    client: &Client,
+    store: &dyn Store,
+    state: &mut State,
+) -> Result<()> {
+    let output_dir = &config.scrape.output_dir;
+    let paths = if import.paths.is_empty() {
+        let mut paths = std::fs::read_dir(output_dir)
+            .with_context(|| format!("Failed to list {}", output_dir.display()))?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        paths.retain(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.ends_with(".docx") && !name.ends_with(".delta.docx") && name != INDEX_DOCX_FILE
+        });
+        paths.sort();
+        paths
+    } else {
+        import.paths.clone()
+    };
+
+    let mut topics = Vec::new();
+    for path in &paths {
+        topics.extend(read_document(path, &config.export.dates)?);
+    }
+    info!(
+        "{} topics read from {} documents",
+        topics.len(),
+        paths.len()
+    );
+
+    set_search_window_days(0);
+    let mut summary = RunSummary::default();
+    let mut listed = BTreeMap::new();
+    for search_config in &config.scrape.searches {
+        listed.extend(discover(client, &config.scrape, search_config, &mut summary).await?);
+    }
+    for post in listed.values_mut() {
+        post.category = std::mem::take(&mut post.category).grouped(config.export.group_by);
+    }
+
+    let read = topics.len();
+    let last_message = topics.iter().flat_map(|topic| &topic.dates).max().copied();
+    let unmatched = import_topics(state, topics, &listed);
+    for topic in &unmatched {
+        warn!(
+            "{:?} of {} not found by the searches, not imported",
+            topic.title, topic.category
+        );
+    }
+    // The next run searches what was posted since the documents
+    if state.last_successful_run.is_none() {
+        state.last_successful_run =
+            last_message.map(|date| config.export.dates.forum_timezone.to_utc(date));
+    }
+    store.save(state)?;
+    info!("{} topics imported", read - unmatched.len());
+
+    Ok(())
+}
+
+#[cfg(feature = "embeddings")]
+type Index<'a> = (
+    scrapper::llm::openai::OpenAiEmbedder,
+    scrapper::store::sqlite::Database,
+    &'a str,
+);
+
+/// Scrapes the topics posted since the last successful run into the documents and returns them,
+/// with the summary of the run.
+#[allow(clippy::too_many_arguments)]
+async fn scrape(
+    args: &Args,
+    config: &Config,
+    client: &Client,
+    summarizer: Option<&dyn Summarizer>,
+    translator: Option<&dyn Translator>,
+    #[cfg(feature = "embeddings")] index: Option<&Index<'_>>,
+    store: &dyn Store,
+    state: &mut State,
+    full: bool,
+) -> Result<(Vec<Post>, RunSummary)> {
+    let run_started_at = chrono::Utc::now();
+    let mut summary = RunSummary::default();
+    let bytes_at_start = bytes_downloaded();
+    let coverage_at_start = Coverage::current();
+    let requests_at_start = requests_sent();
+
+    let scrape = &config.scrape;
+    let output_dir = scrape.output_dir.as_path();
+
+    let interrupted = recover(output_dir)?;
+    if !interrupted.is_empty() {
+        warn!(
+            "{} interrupted appends rolled back, their topics are exported again",
+            interrupted.len()
+        );
+    }
+
+    if config.export.format == ExportFormat::Docx {
+        for path in quarantine_corrupted(output_dir)? {
+            let category = state
+                .topics
+                .values()
+                .map(|topic| topic.category.clone())
+                .find(|category| category_output_path(output_dir, category, "docx") == path);
+            let Some(category) = category else {
+                continue;
+            };
+            if scrape.archive_dir.is_some() {
+                match regenerate_category(config, client, summarizer, translator, state, &category)
+                    .await
+                {
+                    Ok(()) => continue,
+                    Err(e) => {
+                        warn!("Failed to regenerate {}: {:#}", path.display(), e);
+                        summary.errors += 1;
+                    }
+                }
+            } else {
+                warn!(
+                    "{} cannot be regenerated without scrape.archive_dir",
+                    path.display()
+                );
+            }
+            // Exported again as new topics when they are found by a search
+            for (url, topic) in &state.topics {
+                if topic.category == category {
+                    state.exported.remove(url);
+                }
+            }
+        }
+    }
+
+    // A backfill looks for the old topics, beyond the period since the last run
+    if !full && args.strategy != Strategy::Backfill {
+        let days = state.search_window_days();
+        info!("Searching the last {} days", days);
+        set_search_window_days(days);
+    } else {
+        set_search_window_days(0);
+    }
+
+    // A topic found by several searches is only fetched once
+    let mut posts = BTreeMap::new();
+    for search_config in &scrape.searches {
+        posts.extend(discover(client, scrape, search_config, &mut summary).await?);
+    }
+    if !args.category.is_empty() {
+        posts.retain(|_, post| {
+            args.category
+                .iter()
+                .any(|category| post.category.matches(category))
+        });
+        info!(
+            "{} topics in the categories {}",
+            posts.len(),
+            args.category.join(", ")
+        );
+    }
+
+    // The documents are made for the categories at the configured level
+    for post in posts.values_mut() {
+        post.category = std::mem::take(&mut post.category).grouped(config.export.group_by);
+    }
+
+    if scrape.pinned != PinnedTopics::Include {
+        posts.retain(|_, post| scrape.pinned.keeps(post.pinned));
+        info!("{} topics once filtered by scrape.pinned", posts.len());
+    }
+
+    // Before the unchanged topics are left out, the first topic of a series being one of them
+    if config.export.series != SeriesMode::Off {
+        let linked = link_series(&mut posts);
+        if linked > 0 {
+            info!("{} topics continue an earlier one", linked);
+        }
+    }
+    // The topics mentioned by title are looked for in the archive and among the ones found
+    if config.export.cross_references {
+        set_known_topics(
+            state
+                .topics
+                .iter()
+                .map(|(url, topic)| (topic.title.as_str(), url.as_str()))
+                .chain(
+                    posts
+                        .values()
+                        .map(|post| (post.title.as_str(), post.url.as_str())),
+                ),
+        );
+    }
+
+    summary.topics_discovered = posts.len();
+    // Nothing to fetch in the topics without a new message since they were exported, unless
+    // their document is gone or in another format
+    if !full {
+        posts.retain(|_, post| {
+            !state.is_unchanged(post) || !post.document_path(output_dir, &config.export).exists()
+        });
+        summary.topics_unchanged = summary.topics_discovered - posts.len();
+        if summary.topics_unchanged > 0 {
+            info!(
+                "{} topics without new messages skipped",
+                summary.topics_unchanged
+            );
+        }
+    }
+    summary.end_stage("search");
+
+    // Now let's fetch the HTML for each post and store it in the Post struct
+    // Exported in the order the topics were posted, so that regenerated documents diff cleanly
+    let mut post_urls = posts.keys().cloned().collect::<Vec<_>>();
+    post_urls.sort_by_key(|url| (posts[url].topic_id(), url.clone()));
+    if let Some(max_requests) = args.max_requests {
+        let budget = max_requests.saturating_sub((requests_sent() - requests_at_start) as usize);
+        post_urls = schedule_topics(&posts, args.strategy, &state.exported, budget);
+        summary.topics_deferred = posts.len() - post_urls.len();
+        if summary.topics_deferred > 0 {
+            info!(
+                "{} topics fetched within {} requests, {} left to the next runs",
+                post_urls.len(),
+                max_requests,
+                summary.topics_deferred
+            );
+        }
+    }
+
+    if config.export.series == SeriesMode::Merge {
+        merge_series(&mut post_urls, &mut posts);
+    }
+
+    // The next topics are fetched while one is exported, at most `scrape.fetch_ahead` of them
+    // waiting in memory for their export
+    let fetch_ahead = scrape.fetch_ahead.max(1);
+    let (mut fetched_tx, mut fetched) = mpsc::channel(fetch_ahead);
+    let fetcher = async {
+        let mut fetches = stream::iter(&post_urls)
+            .map(|url| async move {
+                let fetch = get_page(client, url)
+                    .await
+                    .with_context(|| ScrapeContext::topic(Stage::Fetch, url).page(1));
+                (url.clone(), fetch)
+            })
+            .buffered(fetch_ahead);
+        while let Some(fetch) = fetches.next().await {
+            if fetched_tx.send(fetch).await.is_err() {
+                break;
+            }
+        }
+        // Closes the channel, the exporter then moves to the topics appended again
+        drop(fetched_tx);
+    };
+
+    // Generated file -> topic urls written into it, used to build the manifest
+    let mut outputs: BTreeMap<_, Vec<String>> = BTreeMap::new();
+    let mut report = Report::default();
+
+    // Topic url -> times its document was found open in another program (Word on Windows). The
+    // topic is appended again at the end of the run, then to the delta document of its category.
+    let mut in_use: BTreeMap<String, usize> = BTreeMap::new();
+    let mut sidecar_config = config.clone();
+    sidecar_config.export.delta = true;
+    let sidecar_config = Arc::new(sidecar_config);
+    // Shared with the export of each topic, which runs on the blocking pool
+    let export_config = Arc::new(config.clone());
+
+    let exporter = async {
+        // Topics already fetched, appended again once the others are exported
+        let mut queue = VecDeque::new();
+
+        // A failing topic is skipped, the run then ends with Exit::PartialFailure
+        loop {
+            // None for a topic already fetched
+            let (url, post_doc) = match fetched.next().await {
+                Some((url, post_doc)) => (url, Some(post_doc)),
+                None => match queue.pop_front() {
+                    Some(url) => (url, None),
+                    None => break,
+                },
+            };
+            set_current_topic(Some(&url));
+            let post = posts.get_mut(&url).unwrap();
+            if let Some(post_doc) = post_doc {
+                let doc = match post_doc {
+                    Ok((doc, _)) => doc,
+                    Err(e) => {
+                        warn!("{:#}", e);
+                        summary.topics_failed += 1;
+                        summary.errors += 1;
+                        continue;
+                    }
+                };
+                summary.topics_fetched += 1;
+                info!("Fetched HTML for post: {}", url);
+                post.html = Some(doc);
+            } else {
+                // The messages are parsed again
+                post.messages = None;
+            }
+            // In a chapter of its own when the topic it continues was not exported before it
+            if let Some(series) = post.series.as_mut().filter(|series| series.merged) {
+                series.merged = outputs
+                    .values()
+                    .flatten()
+                    .any(|url| *url == series.first_url);
+            }
+            let attempts = in_use.get(&url).copied().unwrap_or(0);
+            match post
+                .save(
+                    client,
+                    if attempts < 2 {
+                        &export_config
+                    } else {
+                        &sidecar_config
+                    },
+                    &mut report,
+                    summarizer,
+                    translator,
+                    Some(state),
+                )
+                .await
+            {
+                Ok(Some(path)) => {
+                    outputs.entry(path).or_default().push(post.url.clone());
+                    summary.topics_exported += 1;
+                }
+                Ok(None) => {}
+                Err(e) if attempts < 2 && is_in_use_error(&e) => {
+                    warn!(
+                        "{:#}, the topic is appended {}",
+                        e,
+                        if attempts == 0 {
+                            "again at the end of the run"
+                        } else {
+                            "to the delta document of its category instead"
+                        }
+                    );
+                    in_use.insert(url.clone(), attempts + 1);
+                    queue.push_back(url);
+                    continue;
+                }
+                Err(e) => {
+                    warn!("{:#}", e);
+                    summary.topics_failed += 1;
+                    summary.errors += 1;
+                    continue;
+                }
+            }
+            if args.verify {
+                for message in post.messages.iter().flatten() {
+                    let lost = lost_words(message);
+                    if !lost.is_empty() {
+                        warn!(
+                            "{} words of the message of {} in {} were lost: {}",
+                            lost.len(),
+                            message.author,
+                            post.url,
+                            lost.join(" ")
+                        );
+                        report.fidelity_losses.push(FidelityLoss {
+                            topic_url: post.url.clone(),
+                            author: message.author.clone(),
+                            date: message.date.clone(),
+                            lost_words: lost,
+                        });
+                    }
+                }
+            }
+            // The messages left out by delta mode are kept, the edited ones updated and the deleted
+            // ones marked
+            let mut topic = StoredTopic::from_post(post);
+            if let Some(previous) = state.topics.remove(&post.url) {
+                for (key, message) in previous.messages {
+                    topic.messages.entry(key).or_insert(message);
+                }
+            }
+            for change in report
+                .changed_messages
+                .iter()
+                .filter(|change| change.topic_url == post.url)
+            {
+                if let Some(message) = topic.messages.get_mut(&change.key) {
+                    message.text = change.text.clone();
+                }
+            }
+            for deleted in report
+                .deleted_messages
+                .iter()
+                .filter(|deleted| deleted.topic_url == post.url)
+            {
+                if let Some(message) = topic.messages.get_mut(&deleted.key) {
+                    message.deleted = true;
+                }
+            }
+            state.topics.insert(post.url.clone(), topic);
+            state
+                .exported
+                .entry(post.url.clone())
+                .or_default()
+                .extend(post.messages.iter().flatten().map(PostMessage::key));
+
+            #[cfg(feature = "embeddings")]
+            if let Some((embedder, db, model)) = index {
+                if let Err(e) = scrapper::llm::semantic::index_post(post, embedder, db, model)
+                    .await
+                    .with_context(|| ScrapeContext::topic(Stage::Index, &post.url))
+                {
+                    warn!("{:#}", e);
+                    summary.errors += 1;
+                }
+            }
+        }
+    };
+    futures::join!(fetcher, exporter);
+    set_current_topic(None);
+
+    if let Some(ref profiles) = config.profiles {
+        #[cfg(feature = "sqlite")]
+        {
+            let db = scrapper::store::sqlite::Database::open(profiles.database_path(output_dir))?;
+            summary.errors += record_profiles(client, posts.values(), &db).await?;
+        }
+        #[cfg(not(feature = "sqlite"))]
+        warn!(
+            "Profiles configured ({:?}) but the scraper was built without the `sqlite` feature",
+            profiles
+        );
+    }
+
+    let unmapped = take_unmapped();
+    if !unmapped.is_empty() {
+        warn!(
+            "Characters Word may show as boxes, to map in [characters]: {}",
+            unmapped
+                .iter()
+                .map(|(c, count)| format!("U+{:04X} ({}x)", *c as u32, count))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    info!("Total posts found: {}", posts.len());
+    summary.end_stage("export");
+
+    let report_path = output_dir.join(REPORT_FILE);
+    report.write(&report_path)?;
+    if !report.long_messages.is_empty() {
+        warn!(
+            "{} unusually long messages, see {}",
+            report.long_messages.len(),
+            report_path.display()
+        );
+    }
+    if !report.changed_messages.is_empty() {
+        warn!(
+            "{} messages edited since they were exported, see {}",
+            report.changed_messages.len(),
+            report_path.display()
+        );
+    }
+    if !report.deleted_messages.is_empty() {
+        warn!(
+            "{} messages deleted from the forum since they were exported, see {}",
+            report.deleted_messages.len(),
+            report_path.display()
+        );
+    }
+    if !report.protected_documents.is_empty() {
+        warn!(
+            "{} topics not added to protected documents, see {}",
+            report.protected_documents.len(),
+            report_path.display()
+        );
+    }
+
+    let mut files = outputs.keys().cloned().collect::<Vec<_>>();
 
-    let page = 0;
-    let (doc, _) = get_html(&client, url)
+    // Category document -> its topics after every run, for the manifest
+    let mut document_topics = BTreeMap::new();
+    if let Some(extension) = config.export.format.category_extension() {
+        let entries = index_entries(state, output_dir, extension);
+        document_topics.extend(
+            entries
+                .iter()
+                .map(|entry| (entry.path.clone(), entry.topics)),
+        );
+        let index_path = output_dir.join(INDEX_MD_FILE);
+        write_markdown(&entries, &index_path)?;
+        files.push(index_path);
+        if config.export.format == ExportFormat::Docx {
+            let index_path = output_dir.join(INDEX_DOCX_FILE);
+            write_docx(&entries, &index_path)?;
+            files.push(index_path);
+        }
+    }
+
+    if let Some(ref notice) = config.export.notice {
+        let notice_path = output_dir.join(NOTICE_FILE);
+        write_notice(notice, &scrape.base_url, &notice_path)?;
+        files.push(notice_path);
+    }
+
+    if config.export.ical {
+        let calendar_path = output_dir.join(CALENDAR_FILE);
+        write_calendar(posts.values(), &calendar_path)?;
+        files.push(calendar_path);
+    }
+
+    // After every other output, for all of them to be listed, the ones without topics included
+    let mut manifested = outputs.clone();
+    for path in &files {
+        manifested.entry(path.clone()).or_default();
+    }
+    let manifest_path = output_dir.join(MANIFEST_FILE);
+    Manifest::from_outputs(&manifested, &document_topics, &posts)?.write(&manifest_path)?;
+    info!("Manifest written to {}", manifest_path.display());
+    files.push(manifest_path);
+
+    if let Some(PackageFormat::Zip) = args.package {
+        let archive = package_zip(&files, output_dir)?;
+        info!("Outputs packaged into {}", archive.display());
+        files.push(archive);
+    }
+
+    summary.end_stage("outputs");
+
+    #[cfg(feature = "upload")]
+    if let Some(ref upload) = config.upload {
+        scrapper::delivery::upload::upload_files(client, upload, &files)
+            .await
+            .context(Exit::DeliveryFailed)?;
+    }
+    #[cfg(not(feature = "upload"))]
+    if config.upload.is_some() {
+        warn_feature_off("upload", "upload");
+    }
+
+    #[cfg(feature = "drive")]
+    if let Some(ref drive) = config.drive {
+        scrapper::delivery::drive::upload_to_drive(client, drive, &files)
+            .await
+            .context(Exit::DeliveryFailed)?;
+    }
+    #[cfg(not(feature = "drive"))]
+    if config.drive.is_some() {
+        warn_feature_off("drive", "drive");
+    }
+
+    #[cfg(feature = "email")]
+    if let Some(ref email) = config.email {
+        let documents = outputs.keys().cloned().collect::<Vec<_>>();
+        if documents.is_empty() {
+            info!("No document generated, skipping email");
+        } else {
+            scrapper::delivery::email::send_documents(email, &documents)
+                .await
+                .context(Exit::DeliveryFailed)?;
+        }
+    }
+    #[cfg(not(feature = "email"))]
+    if config.email.is_some() {
+        warn_feature_off("email", "email");
+    }
+
+    summary.end_stage("delivery");
+
+    summary.bytes_downloaded = bytes_downloaded() - bytes_at_start;
+    summary.parser_coverage = Coverage::current().since(&coverage_at_start);
+    summary.log();
+    if let Some(ref path) = args.summary {
+        summary.write(path)?;
+    }
+
+    // The failed, deferred and filtered out topics are searched again by the next run
+    if summary.topics_failed == 0 && summary.topics_deferred == 0 && args.category.is_empty() {
+        state.last_successful_run = Some(run_started_at);
+    } else if summary.topics_failed > 0 {
+        warn!(
+            "{} topics failed, the next run searches the same period again",
+            summary.topics_failed
+        );
+    }
+    store.save(state)?;
+
+    Ok((posts.into_values().collect(), summary))
+}
+
+/// Stores the profile of every participant not known yet, returning the number of profiles that
+/// could not be fetched.
+#[cfg(feature = "sqlite")]
+async fn record_profiles<'a>(
+    client: &Client,
+    posts: impl Iterator<Item = &'a Post>,
+    db: &scrapper::store::sqlite::Database,
+) -> Result<usize> {
+    use scrapper::http::profiles::get_profile;
+
+    let mut profiles = BTreeMap::new();
+    for post in posts {
+        let topic_url = reqwest::Url::parse(&post.url)?;
+        for message in post.messages.iter().flatten() {
+            if let Some(ref href) = message.author_profile {
+                profiles.insert(topic_url.join(href)?.to_string(), message.author.clone());
+            }
+        }
+    }
+
+    let mut failed = 0;
+    for (url, author) in profiles {
+        if db.has_profile(&url)? {
+            continue;
+        }
+        match get_profile(client, &url, &author).await {
+            Ok(profile) => db.upsert_profile(&profile)?,
+            Err(e) => {
+                warn!("Failed to fetch the profile of {}: {:#}", author, e);
+                failed += 1;
+            }
+        }
+    }
+
+    Ok(failed)
+}
+
+/// Topics found by one search of the forum, page after page.
+async fn discover(
+    client: &Client,
+    scrape: &ScrapeConfig,
+    search_config: &SearchConfig,
+    summary: &mut RunSummary,
+) -> Result<BTreeMap<String, Post>> {
+    let url = format!("{}search.php?mode=results", scrape.base_url);
+    let mut posts = BTreeMap::new();
+
+    info!("Searching {:?}", search_config);
+    let mut next_page_url = search(client, &url, search_config).await?;
+    let page_url = |next_page_url: &str, page: u32| {
+        format!(
+            "{}{}&start={}",
+            scrape.base_url,
+            next_page_url,
+            page * scrape.page_size
+        )
+    };
+
+    // The pages are fetched one after the other, the first empty one ends the results and no
+    // page is requested past it
+    let mut searched_again = false;
+    for page in 0..scrape.max_pages {
+        let next_url = page_url(&next_page_url, page);
+        info!("Next URL: {}", next_url);
+        let (mut doc, _) = get_html(client, next_url)
+            .await
+            .with_context(|| ScrapeContext::search(page as usize + 1))
+            .context(Exit::DiscoveryFailed)?;
+        summary.search_pages += 1;
+        // The search is posted again at most once, every page after it uses the new search_id
+        if search_expired(&doc) {
+            if !searched_again {
+                warn!("Search session expired at page {}, searching again", page);
+                next_page_url = search(client, &url, search_config).await?;
+                searched_again = true;
+            }
+            (doc, _) = get_html(client, page_url(&next_page_url, page))
+                .await
+                .with_context(|| ScrapeContext::search(page as usize + 1))
+                .context(Exit::DiscoveryFailed)?;
+            if search_expired(&doc) {
+                return Err(anyhow::anyhow!(
+                    "Search session expired again at page {}",
+                    page
+                ))
+                .context(Exit::DiscoveryFailed);
+            }
+        }
+        let page_posts = get_posts_from_current_page(&doc, &scrape.base_url)
+            .await
+            .with_context(|| ScrapeContext::search(page as usize + 1))
+            .context(Exit::DiscoveryFailed)?;
+        if page_posts.is_empty() {
+            info!(
+                "Page {} of the search is empty, no more pages requested",
+                page
+            );
+            break;
+        }
+        posts.extend(page_posts);
+    }
+
+    Ok(posts)
+}
+
+/// Posts the search, returning the url of its results without the offset.
+async fn search(client: &Client, url: &str, search_config: &SearchConfig) -> Result<String> {
+    let (doc, _) = search_html(client, url, search_config)
         .await
-        .context("Failed to get initial HTML page")?;
+        .context("Failed to get initial HTML page")
+        .context(Exit::DiscoveryFailed)?;
 
-    let next_page_url = find_next_page(&doc)
+    Ok(find_next_page(&doc)
         .ok_or_else(|| {
             warn!("No next page found");
         })
-        .unwrap_or(&"");
+        .unwrap_or("")
+        .to_owned())
+}
 
-    let urls = (0..MAX_PAGES)
-        .map(|page| {
-            let next_url = format!("{}{}&start={}", BASE_URL, next_page_url, page * PAGE_SIZE);
-            info!("Next URL: {}", next_url);
-            next_url
-        })
-        .collect::<Vec<_>>();
+async fn categories_command(config: &Config) -> Result<()> {
+    let base_url = &config.scrape.base_url;
+    let client = Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .build()
+        .context("Failed to build HTTP client")?;
 
-    let docs = join_all(
-        urls.iter()
-            .map(|url| get_html(&client, url))
-            .collect::<Vec<_>>(),
-    )
-    .await;
+    let (index, _) = get_html(&client, format!("{}index.php", base_url))
+        .await
+        .context("Failed to get the forum index")?;
+    let forums = get_forums_from_index(&index, base_url)?;
 
-    for doc in docs {
-        posts.extend(
-            get_posts_from_current_page(&(doc?).0)
-                .await
-                .with_context(|| format!("Failed to extract posts from page {}", page))?,
+    println!("id\tsection\tname\ttopics\tposts\turl");
+    for forum in forums {
+        println!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            forum.id, forum.section, forum.name, forum.topics, forum.posts, forum.url
         );
     }
 
-    // Now let's fetch the HTML for each post and store it in the Post struct
-    let post_urls = posts.keys().cloned().collect::<Vec<_>>();
-    let post_fetches = post_urls
+    Ok(())
+}
+
+/// Rebuilds the documents of the selected categories from the archived pages of their topics,
+/// leaving the other documents untouched.
+async fn export_command(
+    config: &Config,
+    args: &ExportArgs,
+    client: &Client,
+    summarizer: Option<&dyn Summarizer>,
+    translator: Option<&dyn Translator>,
+    state: &State,
+) -> Result<()> {
+    if args.category.is_empty() && args.topic_url.is_empty() {
+        anyhow::bail!("Nothing to export, pass --category or --topic-url");
+    }
+
+    let mut categories = BTreeSet::new();
+    for category in &args.category {
+        let category = state
+            .topics
+            .values()
+            .map(|topic| &topic.category)
+            .find(|stored| stored.trim().eq_ignore_ascii_case(category.trim()))
+            .ok_or_else(|| anyhow::anyhow!("No topic exported in the category {}", category))?;
+        categories.insert(category.clone());
+    }
+    for url in &args.topic_url {
+        let topic = state
+            .topics
+            .get(url)
+            .ok_or_else(|| anyhow::anyhow!("{} was never exported", url))?;
+        categories.insert(topic.category.clone());
+    }
+
+    if config.export.cross_references {
+        set_known_topics(
+            state
+                .topics
+                .iter()
+                .map(|(url, topic)| (topic.title.as_str(), url.as_str())),
+        );
+    }
+    for category in categories {
+        regenerate_category(config, client, summarizer, translator, state, &category).await?;
+    }
+
+    Ok(())
+}
+
+/// Rebuilds the document of `category` from the archived pages of its stored topics, in the
+/// order they were posted.
+async fn regenerate_category(
+    config: &Config,
+    client: &Client,
+    summarizer: Option<&dyn Summarizer>,
+    translator: Option<&dyn Translator>,
+    state: &State,
+    category: &str,
+) -> Result<()> {
+    let Some(ref archive_dir) = config.scrape.archive_dir else {
+        anyhow::bail!("Exporting from stored data needs scrape.archive_dir");
+    };
+    // One file for every category, only a full run can rebuild it
+    let Some(extension) = config.export.format.category_extension() else {
+        anyhow::bail!(
+            "The {:?} export holds every category, it cannot be regenerated by category",
+            config.export.format
+        );
+    };
+
+    let mut posts = state
+        .topics
         .iter()
-        .map(|url| get_html(&client, url))
+        .filter(|(_, topic)| topic.category == category)
+        .map(|(url, topic)| Post {
+            url: url.clone(),
+            title: topic.title.clone(),
+            html: None,
+            messages: None,
+            last_author: None,
+            category: Category::parse(&topic.category),
+            last_post: topic.last_post.clone(),
+            replies: topic.replies,
+            views: topic.views,
+            pinned: Default::default(),
+            series: None,
+        })
         .collect::<Vec<_>>();
+    posts.sort_by_key(|post| (post.topic_id(), post.url.clone()));
+    let Some(first) = posts.first() else {
+        anyhow::bail!("No topic exported in the category {}", category);
+    };
 
-    for post_doc in join_all(post_fetches).await {
-        let (doc, url) = post_doc?;
-        info!("Fetched HTML for post: {}", url);
-        let post = posts.get_mut(url).unwrap();
-        post.html = Some(doc);
-        post.save(&client).await?;
+    let output_dir = &config.scrape.output_dir;
+    let path = first.output_path_with_extension(output_dir, extension);
+    if config.export.format == ExportFormat::Docx && path.exists() {
+        if let Some(reason) = protection_reason(&read_custom_properties(&path)?) {
+            warn!("Not regenerating {}: {}", path.display(), reason);
+            return Ok(());
+        }
     }
 
-    info!("Total posts found: {}", posts.len());
+    // Built aside and swapped in once complete, a failure leaves the previous version. The
+    // messages of the previous runs are exported again along with the others.
+    let mut rebuilt_config = config.clone();
+    rebuilt_config.export.delta = false;
+    rebuilt_config.scrape.output_dir = output_dir.join(EXPORT_STAGING_DIR);
+    let rebuilt_config = Arc::new(rebuilt_config);
+    let staging_dir = &rebuilt_config.scrape.output_dir;
+    if staging_dir.exists() {
+        std::fs::remove_dir_all(staging_dir)?;
+    }
+    std::fs::create_dir_all(staging_dir)?;
+    let staged = first.output_path_with_extension(staging_dir, extension);
+
+    set_replay(Some(RawArchive::open(archive_dir)?));
+    let mut report = Report::default();
+    let rebuilt = async {
+        for post in &mut posts {
+            let (html, _) = get_page(client, &post.url)
+                .await
+                .with_context(|| ScrapeContext::topic(Stage::Fetch, &post.url).page(1))?;
+            post.html = Some(html);
+            post.save(
+                client,
+                &rebuilt_config,
+                &mut report,
+                summarizer,
+                translator,
+                Some(state),
+            )
+            .await?;
+        }
+        Ok::<_, anyhow::Error>(())
+    }
+    .await;
+    // The rest of the run reads the forum again
+    set_replay(None);
+    rebuilt?;
+
+    std::fs::rename(&staged, &path)
+        .with_context(|| format!("Failed to replace {}", path.display()))?;
+    std::fs::remove_dir_all(staging_dir)?;
+    info!("Regenerated {} from {} topics", path.display(), posts.len());
+
     Ok(())
 }
+
+fn plan_command(config: &Config, args: &PlanArgs) -> Result<()> {
+    let output_dir = &config.scrape.output_dir;
+    let report_path = args
+        .report
+        .clone()
+        .unwrap_or_else(|| output_dir.join(REPORT_FILE));
+    let report = Report::load(&report_path)?;
+
+    let plan = plan_volumes(
+        &report.topics,
+        args.pages_per_volume.get(),
+        args.words_per_page.get(),
+    );
+    let volumes = plan.last().map_or(0, |planned| planned.volume);
+
+    let output = args
+        .output
+        .clone()
+        .unwrap_or_else(|| output_dir.join(VOLUMES_FILE));
+    write_plan_csv(&plan, &output)?;
+    info!(
+        "{} topics planned into {} volumes, written to {}",
+        plan.len(),
+        volumes,
+        output.display()
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "embeddings")]
+async fn semantic_search_command(config: &Config, args: &SemanticSearchArgs) -> Result<()> {
+    use scrapper::llm::openai::OpenAiEmbedder;
+    use scrapper::llm::semantic::search;
+    use scrapper::store::sqlite::Database;
+
+    let embeddings = config
+        .embeddings
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("No [embeddings] section in the config"))?;
+    let db = Database::open(embeddings.database_path(&config.scrape.output_dir))?;
+    let embedder = OpenAiEmbedder::new(Client::new(), embeddings.clone());
+
+    let hits = search(
+        &args.query,
+        &embedder,
+        &db,
+        &embeddings.model,
+        args.limit.get(),
+    )
+    .await?;
+    for hit in hits {
+        println!(
+            "{:.3}\t{}\t{}\t{}",
+            hit.score, hit.category, hit.title, hit.topic_url
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(not(feature = "embeddings"))]
+async fn semantic_search_command(_config: &Config, _args: &SemanticSearchArgs) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "semantic-search needs the scraper to be built with the `embeddings` feature"
+    ))
+}
+
+/// Only the section is named: the configs ignored here hold passwords and API keys
+#[allow(dead_code)] // Unused when every optional feature is enabled
+fn warn_feature_off(section: &str, feature: &str) {
+    warn!(
+        "[{}] configured but the scraper was built without the `{}` feature",
+        section, feature
+    );
+}
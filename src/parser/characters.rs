@@ -0,0 +1,80 @@
+use crate::config::config::CharactersConfig;
+use crate::parser::hooks::ParserHook;
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+// Characters left as they are that Word may not render, with their number of occurrences
+static UNMAPPED: Mutex<BTreeMap<char, usize>> = Mutex::new(BTreeMap::new());
+
+/// The `[characters]` map of the config file, run as a parser hook. The characters it does not
+/// map and that Word shows as boxes are counted, see [`take_unmapped`].
+#[derive(Debug, Clone, Default)]
+pub struct CharacterMap {
+    map: BTreeMap<char, String>,
+}
+
+impl CharacterMap {
+    pub fn from_config(config: &CharactersConfig) -> Result<Self> {
+        let mut map = BTreeMap::new();
+        for (from, to) in &config.map {
+            let mut chars = from.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => map.insert(c, to.clone()),
+                _ => anyhow::bail!(
+                    "Invalid characters map key {:?}, not a single character",
+                    from
+                ),
+            };
+        }
+
+        Ok(Self { map })
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut mapped = String::with_capacity(text.len());
+        let mut previous = None;
+        for c in text.chars() {
+            match self.map.get(&c) {
+                Some(replacement) => mapped.push_str(replacement),
+                None => {
+                    if renders_as_box(c, previous) {
+                        *UNMAPPED.lock().unwrap().entry(c).or_default() += 1;
+                    }
+                    mapped.push(c);
+                }
+            }
+            previous = Some(c);
+        }
+        mapped
+    }
+}
+
+impl ParserHook for CharacterMap {
+    fn transform_text(&self, text: String) -> String {
+        self.apply(&text)
+    }
+}
+
+/// Characters seen since the previous call that Word may show as boxes, with their number of
+/// occurrences.
+pub fn take_unmapped() -> BTreeMap<char, usize> {
+    std::mem::take(&mut *UNMAPPED.lock().unwrap())
+}
+
+/// Private use glyphs of the forum emoticons, control characters and combining marks with no
+/// letter to combine with.
+fn renders_as_box(c: char, previous: Option<char>) -> bool {
+    let private_use = matches!(c, '\u{E000}'..='\u{F8FF}' | '\u{F0000}'..='\u{10FFFF}');
+    let control = c.is_control() && !matches!(c, '\t' | '\n' | '\r');
+    let combining = matches!(
+        c,
+        '\u{0300}'..='\u{036F}'
+            | '\u{1AB0}'..='\u{1AFF}'
+            | '\u{1DC0}'..='\u{1DFF}'
+            | '\u{20D0}'..='\u{20FF}'
+            | '\u{FE20}'..='\u{FE2F}'
+    );
+    let stray = previous.is_none_or(|previous| !previous.is_alphanumeric());
+    private_use || control || combining && stray
+}
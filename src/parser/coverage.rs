@@ -0,0 +1,69 @@
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static HANDLED: AtomicU64 = AtomicU64::new(0);
+static DEGRADED: AtomicU64 = AtomicU64::new(0);
+static DROPPED: AtomicU64 = AtomicU64::new(0);
+
+/// How the elements and attributes of a message made it into the documents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Converted to the matching formatting
+    Handled,
+    /// Content kept without its formatting, or flagged for review
+    Degraded,
+    /// Left out of the documents
+    Dropped,
+}
+
+pub fn record(outcome: Outcome) {
+    let counter = match outcome {
+        Outcome::Handled => &HANDLED,
+        Outcome::Degraded => &DEGRADED,
+        Outcome::Dropped => &DROPPED,
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Elements and attributes seen by the sanitizer and the parser, per outcome.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize)]
+pub struct Coverage {
+    pub handled: u64,
+    pub degraded: u64,
+    pub dropped: u64,
+    pub handled_percent: f64,
+    pub degraded_percent: f64,
+    pub dropped_percent: f64,
+}
+
+impl Coverage {
+    /// Counts since the start of the process.
+    pub fn current() -> Self {
+        Self::from_counts(
+            HANDLED.load(Ordering::Relaxed),
+            DEGRADED.load(Ordering::Relaxed),
+            DROPPED.load(Ordering::Relaxed),
+        )
+    }
+
+    /// Counts since `start`, e.g. over a run.
+    pub fn since(&self, start: &Coverage) -> Self {
+        Self::from_counts(
+            self.handled - start.handled,
+            self.degraded - start.degraded,
+            self.dropped - start.dropped,
+        )
+    }
+
+    fn from_counts(handled: u64, degraded: u64, dropped: u64) -> Self {
+        let total = (handled + degraded + dropped).max(1) as f64;
+        Self {
+            handled,
+            degraded,
+            dropped,
+            handled_percent: 100.0 * handled as f64 / total,
+            degraded_percent: 100.0 * degraded as f64 / total,
+            dropped_percent: 100.0 * dropped as f64 / total,
+        }
+    }
+}
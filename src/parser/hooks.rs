@@ -0,0 +1,52 @@
+use docx_rust::document::Run;
+use scraper::ElementRef;
+use std::borrow::Cow;
+use std::sync::{Arc, RwLock};
+
+/// Extension point letting users customize the HTML to docx conversion without forking,
+/// e.g. to render gematria notations or BB tags specific to the forum.
+pub trait ParserHook: Send + Sync {
+    /// Called for every element before the built-in handling. Returning `Some` replaces the
+    /// built-in conversion of the element (and of its children) with the returned runs.
+    fn handle_element(&self, _el: ElementRef, _in_citation: bool) -> Option<Vec<Run<'static>>> {
+        None
+    }
+
+    /// Called on every text node, after its whitespace has been collapsed to single spaces.
+    fn transform_text(&self, text: String) -> String {
+        text
+    }
+}
+
+static HOOKS: RwLock<Vec<Arc<dyn ParserHook>>> = RwLock::new(Vec::new());
+
+/// Registers a hook, called after the ones already registered.
+pub fn register_hook<H: ParserHook + 'static>(hook: H) {
+    HOOKS.write().unwrap().push(Arc::new(hook));
+}
+
+pub fn clear_hooks() {
+    HOOKS.write().unwrap().clear();
+}
+
+/// First hook handling `el`, if any.
+pub fn handle_element(el: ElementRef, in_citation: bool) -> Option<Vec<Run<'static>>> {
+    HOOKS
+        .read()
+        .unwrap()
+        .iter()
+        .find_map(|hook| hook.handle_element(el, in_citation))
+}
+
+/// `text` passed through every hook, in registration order, still borrowed when there is none.
+pub fn transform_text(text: Cow<'_, str>) -> Cow<'_, str> {
+    let hooks = HOOKS.read().unwrap();
+    if hooks.is_empty() {
+        return text;
+    }
+    Cow::Owned(
+        hooks
+            .iter()
+            .fold(text.into_owned(), |text, hook| hook.transform_text(text)),
+    )
+}
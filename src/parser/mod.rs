@@ -1 +1,7 @@
+pub mod characters;
+pub mod coverage;
+pub mod hooks;
+#[allow(clippy::module_inception)]
 pub mod parser;
+pub mod sanitizer;
+pub mod transforms;
@@ -1,11 +1,52 @@
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
 use tracing::{info, warn};
 
-use docx_rust::document::{BreakType, Run, TextSpace};
-use docx_rust::formatting::{CharacterProperty, CharacterStyleId, Color, Size, UnderlineStyle};
+use docx_rust::document::{BreakType, Run, RunContent, TextSpace};
+use docx_rust::formatting::{
+    CharacterProperty, CharacterStyleId, Color, JustificationVal, Size, UnderlineStyle, VertAlign,
+    VertAlignType,
+};
 use scraper::Node;
 use scraper::{CaseSensitivity, ElementRef};
 
+use crate::config::config::FontSizeConfig;
+use crate::parser::coverage::{record, Outcome};
+use crate::parser::hooks;
+use crate::utils::functions::looks_mis_encoded;
+
+/// Properties of the `style` of a span converted to formatting, the others are ignored
+const KNOWN_STYLE_PROPERTIES: [&str; 5] = [
+    "font-weight",
+    "font-style",
+    "text-decoration",
+    "font-size",
+    "color",
+];
+
+// Formatting of the fixed elements, built once and cloned for each of their runs
+static LINK: LazyLock<CharacterProperty<'static>> =
+    LazyLock::new(|| CharacterProperty::default().underline(UnderlineStyle::Single));
+static CITATION: LazyLock<CharacterProperty<'static>> =
+    LazyLock::new(|| CharacterProperty::default().style_id(CharacterStyleId::from("citation")));
+static CITATION_LABEL: LazyLock<CharacterProperty<'static>> =
+    LazyLock::new(|| CITATION.clone().bold(true));
+static STRIKE: LazyLock<CharacterProperty<'static>> =
+    LazyLock::new(|| CharacterProperty::default().strike(true));
+static SUPERSCRIPT: LazyLock<CharacterProperty<'static>> =
+    LazyLock::new(|| vertical_align(VertAlignType::Superscript));
+static SUBSCRIPT: LazyLock<CharacterProperty<'static>> =
+    LazyLock::new(|| vertical_align(VertAlignType::Subscript));
+
+/// `None` until configured, for the default clamp
+static FONT_SIZE: RwLock<Option<FontSizeConfig>> = RwLock::new(None);
+
+/// Handles the small font sizes of the messages parsed from now on as `config` says.
+pub fn set_font_size(config: FontSizeConfig) {
+    *FONT_SIZE.write().unwrap() = Some(config);
+}
+
 pub trait CharacterPropertyExt {
     fn merge(&self, other: &Self) -> Self;
 }
@@ -19,20 +60,296 @@ impl CharacterPropertyExt for CharacterProperty<'_> {
             size: self.size.to_owned().or(other.size.to_owned()),
             color: self.color.to_owned().or(other.color.to_owned()),
             style_id: self.style_id.to_owned().or(other.style_id.to_owned()),
+            strike: self.strike.to_owned().or(other.strike.to_owned()),
+            vertical_align: self
+                .vertical_align
+                .to_owned()
+                .or(other.vertical_align.to_owned()),
             ..Default::default()
         }
     }
 }
 
-pub fn parse_recursive<'a>(container: ElementRef, last_element_is_citation: bool) -> Vec<Run<'a>> {
+/// Piece of a parsed message: a run of its text, or a note for the DOCX export that the other
+/// exports never see.
+// Runs are nearly every item, boxing them would cost an allocation each
+#[allow(clippy::large_enum_variant)]
+#[derive(Debug, Clone)]
+pub enum MessageItem<'a> {
+    Run(Run<'a>),
+    /// Spot the parser could not fully understand, turned into a Word comment holding the reason
+    /// by the DOCX export
+    ReviewNote(String),
+    /// Aligns the paragraphs that follow, `None` going back to the default alignment. Starts a new
+    /// paragraph in the DOCX export.
+    Align(Option<JustificationVal>),
+}
+
+impl<'a> MessageItem<'a> {
+    pub fn as_run(&self) -> Option<&Run<'a>> {
+        match self {
+            MessageItem::Run(run) => Some(run),
+            _ => None,
+        }
+    }
+}
+
+/// Runs of `items`, without the notes.
+pub fn item_runs(items: Vec<MessageItem<'_>>) -> Vec<Run<'_>> {
+    items
+        .into_iter()
+        .filter_map(|item| match item {
+            MessageItem::Run(run) => Some(run),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Applies `f` to each sequence of runs of `items` between the other items.
+pub fn map_runs<'a>(
+    items: Vec<MessageItem<'a>>,
+    mut f: impl FnMut(Vec<Run<'a>>) -> Vec<Run<'a>>,
+) -> Vec<MessageItem<'a>> {
+    let mut mapped = Vec::with_capacity(items.len());
+    let mut runs = Vec::new();
+    for item in items {
+        match item {
+            MessageItem::Run(run) => runs.push(run),
+            other => {
+                mapped.extend(
+                    f(std::mem::take(&mut runs))
+                        .into_iter()
+                        .map(MessageItem::Run),
+                );
+                mapped.push(other);
+            }
+        }
+    }
+    mapped.extend(f(runs).into_iter().map(MessageItem::Run));
+    mapped
+}
+
+/// `text-align` of a block, or center for `<center>`.
+fn block_alignment(el: ElementRef) -> Option<JustificationVal> {
+    match el.value().name() {
+        "center" => Some(JustificationVal::Center),
+        "div" | "p" => {
+            let style = el.attr("style")?;
+            let (_, value) = style
+                .split(';')
+                .filter_map(|declaration| declaration.split_once(':'))
+                .find(|(key, _)| key.trim() == "text-align")?;
+            match value.trim() {
+                "left" | "start" => Some(JustificationVal::Left),
+                "center" => Some(JustificationVal::Center),
+                "right" | "end" => Some(JustificationVal::Right),
+                "justify" => Some(JustificationVal::Both),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Merges the adjacent runs with the same formatting, the parser emitting one per text node, and
+/// collapses the spaces where they meet as a browser would: one space between two words however
+/// many text nodes end or start with one, none at the start or end of a line.
+///
+/// Runs holding anything but text and breaks (footnote references), and the notes, are kept apart.
+pub fn merge_runs(items: Vec<MessageItem<'_>>) -> Vec<MessageItem<'_>> {
+    let mergeable = |run: &Run| {
+        run.content
+            .iter()
+            .all(|content| matches!(content, RunContent::Text(_) | RunContent::Break(_)))
+    };
+    // CharacterProperty has no PartialEq, the formats are compared through their Debug output,
+    // formatted once per run
+    let format_of = |run: &Run| format!("{:?}", run.property);
+    let mut last_format = String::new();
+
+    let mut merged: Vec<MessageItem> = Vec::with_capacity(items.len());
+    // Whether the text so far ends with a space, a break starting a new line
+    let mut after_space = true;
+    for item in items {
+        let mut run = match item {
+            MessageItem::Run(run) => run,
+            MessageItem::Align(justification) => {
+                // Starts a new paragraph
+                after_space = true;
+                merged.push(MessageItem::Align(justification));
+                continue;
+            }
+            note => {
+                merged.push(note);
+                continue;
+            }
+        };
+        if mergeable(&run) {
+            let mut content = Vec::with_capacity(run.content.len());
+            for item in run.content.drain(..) {
+                match item {
+                    RunContent::Text(mut text) => {
+                        if after_space {
+                            trim_start_spaces(&mut text.text);
+                        }
+                        if text.text.is_empty() {
+                            continue;
+                        }
+                        after_space = text.text.ends_with(' ');
+                        match content.last_mut() {
+                            Some(RunContent::Text(previous)) => {
+                                previous.text.to_mut().push_str(&text.text);
+                            }
+                            _ => content.push(RunContent::Text(text)),
+                        }
+                    }
+                    other => {
+                        after_space = true;
+                        content.push(other);
+                    }
+                }
+            }
+            run.content = content;
+            if run.content.is_empty() {
+                continue;
+            }
+        }
+
+        let format = if mergeable(&run) {
+            format_of(&run)
+        } else {
+            String::new()
+        };
+        match merged.last_mut() {
+            Some(MessageItem::Run(previous))
+                if mergeable(previous) && mergeable(&run) && format == last_format =>
+            {
+                for item in run.content {
+                    match (previous.content.last_mut(), item) {
+                        (Some(RunContent::Text(last)), RunContent::Text(text)) => {
+                            last.text.to_mut().push_str(&text.text);
+                        }
+                        (_, item) => previous.content.push(item),
+                    }
+                }
+            }
+            _ => {
+                merged.push(MessageItem::Run(run));
+                last_format = format;
+            }
+        }
+    }
+
+    // Nor before a break or the end of the message, going backwards
+    let mut before_break = true;
+    for item in merged.iter_mut().rev() {
+        let run = match item {
+            MessageItem::Run(run) => run,
+            MessageItem::Align(_) => {
+                before_break = true;
+                continue;
+            }
+            MessageItem::ReviewNote(_) => continue,
+        };
+        for content in run.content.iter_mut().rev() {
+            match content {
+                RunContent::Break(_) => before_break = true,
+                RunContent::Text(text) if before_break => {
+                    trim_end_spaces(&mut text.text);
+                    before_break = text.text.is_empty();
+                }
+                _ => {}
+            }
+        }
+    }
+    merged.retain_mut(|item| {
+        let MessageItem::Run(run) = item else {
+            return true;
+        };
+        if !mergeable(run) {
+            return true;
+        }
+        run.content
+            .retain(|content| !matches!(content, RunContent::Text(text) if text.text.is_empty()));
+        !run.content.is_empty()
+    });
+
+    // Word drops the spaces at the edges of a text unless told to keep them
+    for content in merged.iter_mut().flat_map(|item| match item {
+        MessageItem::Run(run) => run.content.iter_mut(),
+        _ => [].iter_mut(),
+    }) {
+        if let RunContent::Text(text) = content {
+            if text.text.starts_with(' ') || text.text.ends_with(' ') {
+                text.space = Some(TextSpace::Preserve);
+            }
+        }
+    }
+
+    merged
+}
+
+/// Removes the spaces starting `text` in place, without copying a borrowed text.
+fn trim_start_spaces(text: &mut Cow<'_, str>) {
+    let spaces = text.len() - text.trim_start_matches(' ').len();
+    match text {
+        Cow::Borrowed(borrowed) => *borrowed = &borrowed[spaces..],
+        Cow::Owned(owned) => {
+            owned.drain(..spaces);
+        }
+    }
+}
+
+fn trim_end_spaces(text: &mut Cow<'_, str>) {
+    let len = text.trim_end_matches(' ').len();
+    match text {
+        Cow::Borrowed(borrowed) => *borrowed = &borrowed[..len],
+        Cow::Owned(owned) => owned.truncate(len),
+    }
+}
+
+/// `text` with every sequence of HTML whitespace (not the non-breaking spaces) as a single space,
+/// borrowed when it has none to collapse, as most text nodes.
+pub fn collapse_whitespace(text: &str) -> Cow<'_, str> {
+    let is_whitespace = |c: char| matches!(c, ' ' | '\t' | '\n' | '\r' | '\x0C');
+    if !text.contains(['\t', '\n', '\r', '\x0C']) && !text.contains("  ") {
+        return Cow::Borrowed(text);
+    }
+
+    let mut collapsed = String::with_capacity(text.len());
+    let mut in_whitespace = false;
+    for c in text.chars() {
+        if is_whitespace(c) {
+            if !in_whitespace {
+                collapsed.push(' ');
+            }
+            in_whitespace = true;
+        } else {
+            collapsed.push(c);
+            in_whitespace = false;
+        }
+    }
+    Cow::Owned(collapsed)
+}
+
+pub fn parse_recursive<'a>(
+    container: ElementRef,
+    last_element_is_citation: bool,
+) -> Vec<MessageItem<'a>> {
     let mut paragraphs = Vec::new();
-    let mut children = container.children();
 
-    while let Some(node) = children.next() {
+    for node in container.children() {
         match node.value() {
             Node::Text(text) => {
-                let text = text.text.trim();
-                paragraphs.push(Run::default().push_text(text.to_owned()));
+                let text = hooks::transform_text(collapse_whitespace(&text.text));
+                if looks_mis_encoded(&text) {
+                    warn!("Suspicious encoding: {}", text);
+                    paragraphs.push(MessageItem::ReviewNote("Encodage suspect".to_owned()));
+                }
+                // Copied once, the runs outlive the parsed HTML
+                paragraphs.push(MessageItem::Run(
+                    Run::default().push_text(text.into_owned()),
+                ));
             }
             Node::Element(ref _elem) => {
                 let el = ElementRef::wrap(node);
@@ -40,6 +357,7 @@ pub fn parse_recursive<'a>(container: ElementRef, last_element_is_citation: bool
             }
             _ => {
                 info!("Unknown node: {:?}", node);
+                record(Outcome::Dropped);
             }
         }
     }
@@ -47,10 +365,37 @@ pub fn parse_recursive<'a>(container: ElementRef, last_element_is_citation: bool
     paragraphs
 }
 
+fn vertical_align(value: VertAlignType) -> CharacterProperty<'static> {
+    CharacterProperty {
+        vertical_align: Some(VertAlign { value: Some(value) }),
+        ..Default::default()
+    }
+}
+
+/// Applies `cp` to the runs of `items`, under the formatting they already have.
+fn with_property<'a>(
+    items: Vec<MessageItem<'a>>,
+    cp: &CharacterProperty<'a>,
+) -> Vec<MessageItem<'a>> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            MessageItem::Run(run) => {
+                let cp = match run.property {
+                    Some(ref run_cp) => cp.merge(run_cp),
+                    None => cp.clone(),
+                };
+                MessageItem::Run(run.property(cp))
+            }
+            note => note,
+        })
+        .collect()
+}
+
 pub fn parse_html_to_docx_format<'a>(
     el: Option<ElementRef>,
     last_element_is_citation: bool,
-) -> Vec<Run<'a>> {
+) -> Vec<MessageItem<'a>> {
     let mut paragraphs = Vec::new();
 
     if el.is_none() {
@@ -59,19 +404,36 @@ pub fn parse_html_to_docx_format<'a>(
 
     let el = el.unwrap();
 
+    if let Some(runs) = hooks::handle_element(el, last_element_is_citation) {
+        record(Outcome::Handled);
+        paragraphs.extend(runs.into_iter().map(MessageItem::Run));
+        return paragraphs;
+    }
+
+    if let Some(justification) = block_alignment(el) {
+        record(Outcome::Handled);
+        paragraphs.push(MessageItem::Align(Some(justification)));
+        paragraphs.extend(parse_recursive(el, last_element_is_citation));
+        paragraphs.push(MessageItem::Align(None));
+        return paragraphs;
+    }
+
     match el.value().name() {
         "a" => {
-            paragraphs.push(
-                Run::default()
-                    .property(CharacterProperty::default().underline(UnderlineStyle::Single))
-                    .push_text(el.text().collect::<String>()),
-            );
+            record(Outcome::Handled);
+            paragraphs.push(MessageItem::Run(
+                Run::default().property(LINK.clone()).push_text(
+                    hooks::transform_text(collapse_whitespace(&el.text().collect::<String>()))
+                        .into_owned(),
+                ),
+            ));
         }
         "div" => {
             if el
                 .value()
                 .has_class("postrow-message", CaseSensitivity::CaseSensitive)
             {
+                record(Outcome::Dropped);
                 return paragraphs;
             }
 
@@ -79,51 +441,67 @@ pub fn parse_html_to_docx_format<'a>(
                 .value()
                 .has_class("border-blue-500", CaseSensitivity::CaseSensitive)
             {
-                paragraphs.push(
+                record(Outcome::Handled);
+                paragraphs.push(MessageItem::Run(
                     Run::default()
-                        .property(
-                            CharacterProperty::default()
-                                .bold(true)
-                                .style_id(CharacterStyleId::from("citation")),
-                        )
+                        .property(CITATION_LABEL.clone())
                         .push_text("Citation: "),
-                );
+                ));
 
-                // last div on the citation block
-                let children = parse_recursive(el.child_elements().last().unwrap(), true);
-                let children = children.into_iter().map(|c| {
-                    c.property(
-                        CharacterProperty::default().style_id(CharacterStyleId::from("citation")),
-                    )
+                // last div on the citation block, the first ones hold the quoted author
+                let children = match el.child_elements().last() {
+                    Some(quote) => parse_recursive(quote, true),
+                    None => parse_recursive(el, true),
+                };
+                let children = children.into_iter().map(|item| match item {
+                    MessageItem::Run(run) => MessageItem::Run(run.property(CITATION.clone())),
+                    note => note,
                 });
                 paragraphs.extend(children);
             } else {
-                unimplemented!(
-                    "Unknown div class: {:?} with text {:?}",
-                    el.value(),
-                    el.text().collect::<String>()
-                );
+                warn!("Unknown div class: {:?}, keeping its content", el.value());
+                record(Outcome::Degraded);
+                paragraphs.push(MessageItem::ReviewNote(format!(
+                    "Bloc non reconnu ({}), contenu conservé",
+                    el.value().attr("class").unwrap_or_default()
+                )));
+                paragraphs.extend(parse_recursive(el, last_element_is_citation));
             }
         }
+        "s" | "strike" | "sup" | "sub" => {
+            record(Outcome::Handled);
+            let cp = match el.value().name() {
+                "sup" => &*SUPERSCRIPT,
+                "sub" => &*SUBSCRIPT,
+                _ => &*STRIKE,
+            };
+            paragraphs.extend(with_property(
+                parse_recursive(el, last_element_is_citation),
+                cp,
+            ));
+        }
         "br" => {
-            paragraphs.push(
+            record(Outcome::Handled);
+            paragraphs.push(MessageItem::Run(
                 Run::default()
                     .push_text("")
                     .push_break(BreakType::TextWrapping),
-            );
+            ));
         }
         "span" => {
             let properties = el
                 .attr("style")
                 .unwrap_or_default()
                 .split(';')
-                .map(|prop| {
-                    let mut split = prop.split(':');
-                    let key = split.next().unwrap();
-                    let value = split.next().unwrap();
-                    (key, value)
-                })
+                .filter_map(|prop| prop.split_once(':'))
+                .map(|(key, value)| (key.trim(), value.trim()))
                 .collect::<HashMap<_, _>>();
+            record(Outcome::Handled);
+            for key in properties.keys() {
+                if !KNOWN_STYLE_PROPERTIES.contains(key) {
+                    record(Outcome::Dropped);
+                }
+            }
 
             let mut cp = CharacterProperty::default();
 
@@ -135,48 +513,60 @@ pub fn parse_html_to_docx_format<'a>(
                 cp = cp.italics(true);
             }
 
-            if *properties.get("text-decoration").unwrap_or(&"") == "underline" {
-                cp = cp.underline(UnderlineStyle::Single);
+            // e.g. "underline line-through"
+            let decorations = properties.get("text-decoration").unwrap_or(&"");
+            for decoration in decorations.split_whitespace() {
+                match decoration {
+                    "underline" => cp = cp.underline(UnderlineStyle::Single),
+                    "line-through" => cp = cp.strike(true),
+                    _ => {}
+                }
             }
 
-            if properties.get("font-size").is_some() {
-                let size = properties
-                    .get("font-size")
-                    .unwrap()
-                    .trim_end_matches("px")
-                    .parse::<u8>()
-                    .unwrap();
-                cp = cp.size(Size::from(size.lt(&15).then_some(16u8).unwrap_or(size)));
+            if let Some(size) = properties.get("font-size") {
+                match size.trim_end_matches("px").trim().parse::<f32>() {
+                    Ok(px) if px.is_finite() && px > 0.0 => {
+                        let config = FONT_SIZE.read().unwrap().unwrap_or_default();
+                        cp = cp.size(Size::from(config.half_points(px)));
+                    }
+                    _ => {
+                        warn!("Unknown font size: {}", size);
+                        record(Outcome::Degraded);
+                        paragraphs.push(MessageItem::ReviewNote(format!(
+                            "Taille inconnue : {}",
+                            size
+                        )));
+                    }
+                }
             }
 
-            if properties.get("color").is_some() {
-                let color = (*properties.get("color").unwrap()).to_string();
-
-                match color.as_ref() {
+            if let Some(color) = properties.get("color") {
+                match *color {
                     "blue" => {
                         cp = cp.color(Color::from((0, 0, 255)));
                     }
                     _ => {
                         warn!("Unknown color: {}", color);
+                        record(Outcome::Degraded);
+                        paragraphs.push(MessageItem::ReviewNote(format!(
+                            "Couleur inconnue : {}",
+                            color
+                        )));
                     }
                 }
             }
 
-            if !last_element_is_citation {
-                paragraphs.push(Run::default().push_text((" ", TextSpace::Preserve)));
-            }
-
-            for child in parse_recursive(el, false) {
-                let mut cp = cp.clone();
-                if let Some(ref child_cp) = child.property {
-                    cp = cp.merge(child_cp);
-                }
-                paragraphs.push(child.property(cp));
-            }
-            paragraphs.push(Run::default().push_text((" ", TextSpace::Preserve)));
+            // The spaces around the span are in the text nodes next to it
+            paragraphs.extend(with_property(parse_recursive(el, false), &cp));
         }
         _ => {
-            panic!("Unknown tag: {}", el.value().name());
+            warn!("Unknown tag: {}, keeping its content", el.value().name());
+            record(Outcome::Degraded);
+            paragraphs.push(MessageItem::ReviewNote(format!(
+                "Balise <{}> non reconnue, contenu conservé",
+                el.value().name()
+            )));
+            paragraphs.extend(parse_recursive(el, last_element_is_citation));
         }
     }
 
@@ -0,0 +1,100 @@
+use ego_tree::NodeRef;
+use scraper::{Html, Node};
+use tracing::warn;
+
+use crate::parser::coverage::{record, Outcome};
+use crate::utils::functions::xml_escape;
+
+/// Removed with their content: never part of the text of a message.
+const DROPPED_TAGS: [&str; 10] = [
+    "script", "style", "noscript", "iframe", "object", "embed", "template", "head", "link", "meta",
+];
+
+/// Tags a message may contain, with the attributes kept on them. Other tags are replaced by
+/// their content.
+const ALLOWED_TAGS: [(&str, &[&str]); 21] = [
+    ("a", &["href"]),
+    ("br", &[]),
+    ("div", &["class", "style"]),
+    ("span", &["style"]),
+    ("b", &[]),
+    ("i", &[]),
+    ("u", &[]),
+    ("strong", &[]),
+    ("em", &[]),
+    ("s", &[]),
+    ("strike", &[]),
+    ("sup", &[]),
+    ("sub", &[]),
+    ("p", &["style"]),
+    ("center", &[]),
+    ("ul", &[]),
+    ("ol", &[]),
+    ("li", &[]),
+    ("blockquote", &[]),
+    ("img", &["src", "alt"]),
+    ("hr", &[]),
+];
+
+/// Wrapped by the forum around the search terms, replaced by their content.
+const HIGHLIGHT_CLASSES: [&str; 2] = ["posthilit", "highlight"];
+
+const VOID_TAGS: [&str; 3] = ["br", "img", "hr"];
+
+/// Rewrites the HTML of a `.postrow-message` with only the allowed tags and attributes, so that
+/// the parser never sees scripts, styles or event handlers.
+pub fn sanitize_message(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut sanitized = String::with_capacity(html.len());
+    for child in fragment.root_element().children() {
+        write_node(child, &mut sanitized);
+    }
+    sanitized
+}
+
+fn write_node(node: NodeRef<Node>, out: &mut String) {
+    match node.value() {
+        Node::Text(text) => out.push_str(&xml_escape(text)),
+        Node::Element(el) => {
+            let name = el.name();
+            if DROPPED_TAGS.contains(&name) {
+                warn!("Removing <{}> from a message", name);
+                record(Outcome::Dropped);
+                return;
+            }
+            // Search terms of a topic reached through a search, e.g. <span class="posthilit">
+            let highlight = el.classes().any(|class| HIGHLIGHT_CLASSES.contains(&class));
+            if highlight {
+                record(Outcome::Handled);
+                node.children().for_each(|child| write_node(child, out));
+                return;
+            }
+            let Some((_, attributes)) = ALLOWED_TAGS.iter().find(|(tag, _)| *tag == name) else {
+                warn!("Unknown tag <{}> in a message, keeping its content", name);
+                record(Outcome::Degraded);
+                node.children().for_each(|child| write_node(child, out));
+                return;
+            };
+
+            out.push('<');
+            out.push_str(name);
+            for (attribute, value) in el.attrs() {
+                let scripted = attribute == "href"
+                    && value.trim_start().to_lowercase().starts_with("javascript:");
+                if attributes.contains(&attribute) && !scripted {
+                    out.push_str(&format!(" {}=\"{}\"", attribute, xml_escape(value)));
+                } else {
+                    record(Outcome::Dropped);
+                }
+            }
+            out.push('>');
+            if VOID_TAGS.contains(&name) {
+                return;
+            }
+            node.children().for_each(|child| write_node(child, out));
+            out.push_str(&format!("</{}>", name));
+        }
+        // Comments, doctypes and processing instructions
+        _ => {}
+    }
+}
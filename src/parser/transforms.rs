@@ -0,0 +1,72 @@
+use crate::config::config::TransformConfig;
+use crate::parser::hooks::ParserHook;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::sync::LazyLock;
+
+static TYPOGRAPHY: LazyLock<Vec<(Regex, &str)>> = LazyLock::new(|| {
+    vec![
+        (Regex::new(r"[ \t]{2,}").unwrap(), " "),
+        (Regex::new(r" +([,.])").unwrap(), "$1"),
+        (Regex::new(r"(\w)'(\w)").unwrap(), "$1’$2"),
+    ]
+});
+
+/// The text transforms of the config file, compiled once and run as a parser hook.
+#[derive(Debug, Clone, Default)]
+pub struct TextTransforms {
+    replacements: Vec<(Regex, String)>,
+}
+
+impl TextTransforms {
+    pub fn from_config(transforms: &[TransformConfig]) -> Result<Self> {
+        let mut replacements = Vec::new();
+
+        for transform in transforms {
+            match transform {
+                TransformConfig::Regex {
+                    pattern,
+                    replacement,
+                } => {
+                    let regex = Regex::new(pattern)
+                        .with_context(|| format!("Invalid transform pattern {:?}", pattern))?;
+                    replacements.push((regex, replacement.to_owned()));
+                }
+                TransformConfig::Abbreviations { entries } => {
+                    for (abbreviation, expansion) in entries {
+                        let regex = Regex::new(&format!(r"\b{}\b", regex::escape(abbreviation)))?;
+                        // `$` would otherwise be read as a capture group reference
+                        replacements.push((regex, expansion.replace('$', "$$")));
+                    }
+                }
+                TransformConfig::Typography => {
+                    replacements.extend(
+                        TYPOGRAPHY
+                            .iter()
+                            .map(|(regex, replacement)| (regex.clone(), replacement.to_string())),
+                    );
+                }
+            }
+        }
+
+        Ok(Self { replacements })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        self.replacements
+            .iter()
+            .fold(text.to_owned(), |text, (regex, replacement)| {
+                regex.replace_all(&text, replacement.as_str()).into_owned()
+            })
+    }
+}
+
+impl ParserHook for TextTransforms {
+    fn transform_text(&self, text: String) -> String {
+        self.apply(&text)
+    }
+}
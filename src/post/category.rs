@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use std::fmt;
+
+/// Separators of the parent forum in a category listed by the search, e.g. "Halakha » Chabbat".
+const SEPARATORS: [char; 2] = ['»', '>'];
+
+/// Forum of a topic, and its subforum when the search lists the category with its parent.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Category {
+    pub forum: String,
+    pub subforum: Option<String>,
+}
+
+/// Level of the categories the documents are made for, `export.group_by`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CategoryLevel {
+    /// One document per subforum, or per forum for the topics outside of subforums
+    #[default]
+    Subforum,
+    /// One document per forum, its subforums included
+    Forum,
+}
+
+impl Category {
+    /// Parses a category as listed by the search, the first part being the forum and the last
+    /// one the subforum.
+    pub fn parse(listed: &str) -> Self {
+        let parts = listed
+            .split(SEPARATORS)
+            .map(|part| part.split_whitespace().collect::<Vec<_>>().join(" "))
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>();
+        match parts.as_slice() {
+            [] => Self::default(),
+            [forum] => Self {
+                forum: forum.clone(),
+                subforum: None,
+            },
+            [forum, .., subforum] => Self {
+                forum: forum.clone(),
+                subforum: Some(subforum.clone()),
+            },
+        }
+    }
+
+    /// The category the documents are made for at `level`.
+    pub fn grouped(self, level: CategoryLevel) -> Self {
+        match level {
+            CategoryLevel::Subforum => self,
+            CategoryLevel::Forum => Self {
+                forum: self.forum,
+                subforum: None,
+            },
+        }
+    }
+
+    /// Whether `name` is this category, its forum or its subforum, ignoring case.
+    pub fn matches(&self, name: &str) -> bool {
+        let name = name.trim();
+        [
+            Some(self.to_string()),
+            Some(self.forum.clone()),
+            self.subforum.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .any(|candidate| candidate.eq_ignore_ascii_case(name))
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.subforum {
+            Some(subforum) => write!(f, "{} » {}", self.forum, subforum),
+            None => write!(f, "{}", self.forum),
+        }
+    }
+}
+
+impl From<&str> for Category {
+    fn from(listed: &str) -> Self {
+        Self::parse(listed)
+    }
+}
+
+impl From<String> for Category {
+    fn from(listed: String) -> Self {
+        Self::parse(&listed)
+    }
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+/// Step of the run a failure happened at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stage {
+    /// Listing the topics with the forum search
+    Search,
+    /// Downloading a page of a topic
+    Fetch,
+    /// Extracting the messages of a page
+    Parse,
+    /// Writing the topic into its documents
+    Export,
+    /// Adding the messages to the semantic index
+    Index,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Stage::Search => "search",
+            Stage::Fetch => "fetch",
+            Stage::Parse => "parse",
+            Stage::Export => "export",
+            Stage::Index => "index",
+        })
+    }
+}
+
+/// Where a run failed, attached as context to the errors
+/// (`.with_context(|| ScrapeContext::topic(Stage::Fetch, url).page(2))`) so that the failure of
+/// one topic in a long run tells which of its pages broke, and at which stage.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScrapeContext {
+    /// `None` for the search
+    pub topic_url: Option<String>,
+    pub stage: Stage,
+    /// From 1, `None` when the failure is not about one page
+    pub page: Option<usize>,
+}
+
+impl ScrapeContext {
+    pub fn topic(stage: Stage, topic_url: impl fmt::Display) -> Self {
+        Self {
+            topic_url: Some(topic_url.to_string()),
+            stage,
+            page: None,
+        }
+    }
+
+    /// Page of the search results.
+    pub fn search(page: usize) -> Self {
+        Self {
+            topic_url: None,
+            stage: Stage::Search,
+            page: Some(page),
+        }
+    }
+
+    pub fn page(self, page: usize) -> Self {
+        Self {
+            page: Some(page),
+            ..self
+        }
+    }
+}
+
+impl fmt::Display for ScrapeContext {
+    /// E.g. "fetch of https://forum/viewtopic.php?t=101, page 2 failed"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.stage)?;
+        if let Some(url) = &self.topic_url {
+            write!(f, " of {}", url)?;
+        }
+        if let Some(page) = self.page {
+            write!(f, ", page {}", page)?;
+        }
+        f.write_str(" failed")
+    }
+}
@@ -1 +1,6 @@
+pub mod category;
+pub mod context;
+#[allow(clippy::module_inception)]
 pub mod post;
+pub mod roles;
+pub mod series;
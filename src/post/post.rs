@@ -1,148 +1,878 @@
+use crate::config::config::{Config, ExportConfig, ExportFormat, TitlesConfig, TopicBreak};
+use crate::export::anki::append_cards;
+#[cfg(feature = "annotations")]
+use crate::export::annotations::Annotations;
+use crate::export::core_properties::{read_created, set_core_properties, CoreProperties};
+use crate::export::cross_refs::CrossReferences;
+use crate::export::csv::append_messages;
+use crate::export::custom_properties::{
+    mark_generated, protection_reason, read_custom_properties, ATTRIBUTION_PROPERTY,
+};
+use crate::export::docx::{
+    bilingual_table, docx_error, heading_numbering, keep_with_next, numbered, plain_text,
+    push_comment, text_len, truncate_runs, word_count,
+};
+use crate::export::formatting::{with_outline_level, Formatting};
+use crate::export::front_matter::push_front_matter;
+use crate::export::glossary::Glossary;
+use crate::export::journal::journaled;
+use crate::export::summary::summarize_question;
+use crate::export::titles::heading_title;
+use crate::export::typography::{self, french_typography};
+use crate::export::{latex, odt};
 use crate::extract;
 use crate::http::client::get_html;
-use crate::parser::parser::parse_recursive;
-use crate::utils::constants::BASE_URL;
-use crate::utils::functions::{anonymize_author, is_citation};
-use anyhow::Result;
+use crate::llm::summarizer::Summarizer;
+use crate::llm::translator::Translator;
+use crate::parser::parser::{item_runs, map_runs, merge_runs, parse_recursive, MessageItem};
+use crate::parser::sanitizer::sanitize_message;
+use crate::post::category::Category;
+use crate::post::context::{ScrapeContext, Stage};
+use crate::post::roles::{classify_roles, Role};
+use crate::post::series::SeriesLink;
+use crate::report::diff::changed_messages;
+use crate::report::report::{DeletedMessage, LongMessage, ProtectedDocument, Report, TopicStats};
+use crate::state::state::State;
+use crate::state::tombstones::{deleted_messages, restore_deleted};
+use crate::utils::constants::{
+    ANKI_FILE, MAX_TOPIC_MESSAGES, MAX_TOPIC_PAGES, MESSAGES_CSV_FILE, READING_WORDS_PER_MINUTE,
+};
+use crate::utils::functions::{anonymize_author, is_citation, safe_file_stem};
+use anyhow::{Context, Result};
 use docx_rust::document::{BreakType, Paragraph, Run};
 use docx_rust::formatting::{
-    CharacterProperty, Indent, JustificationVal, ParagraphProperty, UnderlineStyle,
+    CharacterProperty, JustificationVal, PageBreakBefore, ParagraphProperty, SectionProperty,
+    SectionType, SectionTypeP, UnderlineStyle,
 };
 use docx_rust::{Docx, DocxFile};
-use reqwest::Client;
+use reqwest::{Client, Url};
 use scraper::{Html, Selector};
+use std::panic::AssertUnwindSafe;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, LazyLock};
+use tracing::{info, instrument, warn};
+
+// Parsed once, the topic pages are read by the hundred
+static POSTS: LazyLock<Selector> = LazyLock::new(|| {
+    Selector::parse(".container > .overflow-hidden.border-blue-500 > div > .flex")
+        .expect("valid selector")
+});
+static AUTHOR: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("div strong.block.mb-2").expect("valid selector"));
+static DATE: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("a.text-blue-link").expect("valid selector"));
+static POST_MESSAGE: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(".py-4.postrow-message").expect("valid selector"));
+static PROFILE: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse("a[href*='mode=viewprofile']").expect("valid selector"));
+static NEXT_PAGE: LazyLock<Selector> = LazyLock::new(|| {
+    Selector::parse("nav.pagination > a[href^='suivante']").expect("valid selector")
+});
+/// Container of a message, as kept in `PostMessage::message`
+pub(crate) static MESSAGE: LazyLock<Selector> =
+    LazyLock::new(|| Selector::parse(".postrow-message").expect("valid selector"));
+
+// Sanity limits of a topic, see `scrape.max_topic_messages`
+static MAX_MESSAGES: AtomicUsize = AtomicUsize::new(MAX_TOPIC_MESSAGES);
+static MAX_PAGES: AtomicUsize = AtomicUsize::new(MAX_TOPIC_PAGES);
+
+/// Fails the topics with more than `messages` messages or `pages` pages from now on.
+pub fn set_topic_limits(messages: usize, pages: usize) {
+    MAX_MESSAGES.store(messages, Ordering::Relaxed);
+    MAX_PAGES.store(pages, Ordering::Relaxed);
+}
+
+/// Line starting a topic merged into the chapter of the one it continues, see `export.series`.
+pub const SERIES_MERGED_LINE: &str = "(suite)";
+
+/// Author shown on the comments flagging what the parser could not fully understand.
+const REVIEW_AUTHOR: &str = "Relecture";
 
 #[derive(Debug, Default, Clone)]
 pub struct Post {
+    pub url: String,
     pub title: String,
-    pub html: Option<Html>,
+    /// Source of the first page of the topic, parsed by `get_messages`
+    pub html: Option<String>,
     pub messages: Option<Vec<PostMessage>>,
     pub last_author: Option<String>,
-    pub category: String,
+    pub category: Category,
+    /// Last message of the topic as listed by the search, e.g. "14/03/2023 09:02 Rav Binyamin
+    /// Wattenberg"
+    pub last_post: Option<String>,
+    /// Replies and views of the topic as listed by the search
+    pub replies: Option<u32>,
+    pub views: Option<u32>,
+    pub pinned: Pinned,
+    /// When the topic continues an earlier one, "… (suite)"
+    pub series: Option<SeriesLink>,
+}
+
+/// Whether a topic is kept at the top of its forum, usually for administrative messages rather
+/// than questions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Pinned {
+    #[default]
+    No,
+    /// "Post-it"
+    Sticky,
+    /// "Annonce"
+    Announcement,
 }
 
 #[derive(Debug, Clone)]
 pub struct PostMessage {
+    /// Forum post id, taken from the permalink of the date
+    pub id: Option<String>,
     pub author: String,
+    /// Link to the profile of the author, relative to the topic page
+    pub author_profile: Option<String>,
     pub date: String,
     pub message: String,
+    /// One-sentence summary of a question, when a summarizer is configured
+    pub summary: Option<String>,
+    /// Machine translation of the message, when a translator is configured
+    pub translation: Option<String>,
+    /// Tombstone of a message deleted from the forum, rebuilt from the state
+    pub deleted: bool,
+    /// Set once the messages of the topic are all fetched, see `classify_roles`
+    pub role: Option<Role>,
 }
 
-impl Into<Vec<Run<'_>>> for PostMessage {
-    fn into(self) -> Vec<Run<'static>> {
+impl PostMessage {
+    /// Identifies the message across runs: its forum id, or its author and date when unknown.
+    pub fn key(&self) -> String {
+        self.id
+            .clone()
+            .unwrap_or_else(|| format!("{} {}", self.author, self.date))
+    }
+
+    /// Text of the message, with line breaks.
+    pub fn text(&self) -> String {
+        plain_text(&self.runs())
+    }
+
+    /// Heading introducing the message, none for the follow-ups of the Rav to his own answer.
+    pub fn heading(&self, previous: Option<&PostMessage>) -> Option<String> {
+        let is_rav = |message: &PostMessage| message.author.contains("Binyamin Wattenberg");
+        if is_rav(self) {
+            return (!previous.is_some_and(is_rav)).then(|| "Réponse:".to_owned());
+        }
+
+        let author = anonymize_author(&self.author);
+        Some(if self.is_answer() {
+            format!("Réponse par {}", author)
+        } else {
+            format!("Question par {}", author)
+        })
+    }
+
+    /// The message converted to runs, without copying it first.
+    pub fn runs<'a>(&self) -> Vec<Run<'a>> {
+        item_runs(self.items())
+    }
+
+    /// The message converted to runs, along with the notes for the DOCX export.
+    pub fn items<'a>(&self) -> Vec<MessageItem<'a>> {
         let html = Html::parse_fragment(&self.message);
-        let container = html
-            .select(&Selector::parse(".postrow-message").unwrap())
-            .next()
-            .unwrap();
+        // Restored messages are read back from files, they may hold anything
+        let Some(container) = html.select(&MESSAGE).next() else {
+            warn!("Message by {} without its content block", self.author);
+            return vec![MessageItem::ReviewNote(
+                "Message sans bloc de contenu, non converti".to_owned(),
+            )];
+        };
 
-        parse_recursive(container, false)
+        merge_runs(parse_recursive(container, false))
+    }
+
+    /// Whether the message was posted by a Rav rather than by the person asking.
+    pub fn is_answer(&self) -> bool {
+        self.author.contains("Binyamin Wattenberg")
+            || anonymize_author(&self.author)
+                .to_lowercase()
+                .starts_with("rav ")
+    }
+}
+
+impl From<PostMessage> for Vec<Run<'_>> {
+    fn from(message: PostMessage) -> Self {
+        message.runs()
     }
 }
 
 impl Post {
-    pub async fn save(&mut self, client: &Client) -> Result<()> {
-        self._get_messages(&client).await?;
-        self._messages_to_word()?;
+    /// Fetches the messages and appends them to the output of the configured format, returning
+    /// its path, or `None` when the format leaves the topic out or, in delta mode, when it has no
+    /// new message. The messages edited since they were exported, after the `state` of the
+    /// previous runs, are added to `report`.
+    ///
+    /// The warnings logged meanwhile, by the parser among others, are prefixed with the topic url.
+    #[instrument(name = "topic", skip_all, fields(url = %self.url))]
+    pub async fn save(
+        &mut self,
+        client: &Client,
+        config: &Arc<Config>,
+        report: &mut Report,
+        summarizer: Option<&dyn Summarizer>,
+        translator: Option<&dyn Translator>,
+        state: Option<&State>,
+    ) -> Result<Option<PathBuf>> {
+        self.get_messages(client).await?;
+        if let Some(topic) = state.and_then(|state| state.topics.get(&self.url)) {
+            // Not twice for a topic appended again, its document being in use
+            let reported = report
+                .changed_messages
+                .iter()
+                .any(|change| change.topic_url == self.url)
+                || report
+                    .deleted_messages
+                    .iter()
+                    .any(|deleted| deleted.topic_url == self.url);
+            if !reported {
+                report
+                    .changed_messages
+                    .extend(changed_messages(self, topic));
+                report.deleted_messages.extend(
+                    deleted_messages(self, topic)
+                        .into_iter()
+                        .filter(|(_, message)| !message.deleted)
+                        .map(|(key, message)| DeletedMessage {
+                            topic_url: self.url.clone(),
+                            author: message.author.clone(),
+                            date: message.date.clone(),
+                            key: key.clone(),
+                        }),
+                );
+            }
+            restore_deleted(self, topic, config.export.deleted_note);
+        }
+        if config.export.delta {
+            let exported = state.and_then(|state| state.exported.get(&self.url));
+            if let (Some(exported), Some(messages)) = (exported, self.messages.as_mut()) {
+                messages.retain(|message| !exported.contains(&message.key()));
+                if messages.is_empty() {
+                    info!("No new message in {}", self.url);
+                    return Ok(None);
+                }
+            }
+        }
+        // Left out of the documents only, the state keeps them
+        let dropped = self.drop_messages(&config.export.drop);
+        if self.messages.as_ref().is_some_and(Vec::is_empty) {
+            // E.g. a thanks, the only new message in delta mode
+            info!("Only messages left out in {}", self.url);
+            self.restore_dropped(dropped);
+            return Ok(None);
+        }
+        if let Some(summarizer) = summarizer {
+            self.summarize_questions(summarizer).await;
+        }
+        if let Some(translator) = translator {
+            self.translate_messages(translator).await;
+        }
 
-        Ok(())
+        // Reading and writing the documents is synchronous: they run on the blocking pool, the
+        // connections fetching the next topics keeping the workers meanwhile. The topic comes
+        // back even when the writer panics, the panic being raised again once it is restored.
+        let url = self.url.clone();
+        let mut post = std::mem::take(self);
+        let config = Arc::clone(config);
+        let (post, topic_report, written) = tokio::task::spawn_blocking(move || {
+            let mut topic_report = Report::default();
+            let written = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                post.write_outputs(&config, &mut topic_report)
+            }));
+            (post, topic_report, written)
+        })
+        .await
+        .with_context(|| ScrapeContext::topic(Stage::Export, &url))?;
+        *self = post;
+        report.append(topic_report);
+        self.restore_dropped(dropped);
+        match written {
+            Ok(written) => written.with_context(|| ScrapeContext::topic(Stage::Export, &self.url)),
+            Err(panic) => std::panic::resume_unwind(panic),
+        }
+    }
+
+    /// Takes the messages of the `roles` out of `messages`, returned with their index.
+    fn drop_messages(&mut self, roles: &[Role]) -> Vec<(usize, PostMessage)> {
+        let Some(messages) = self.messages.as_mut().filter(|_| !roles.is_empty()) else {
+            return Vec::new();
+        };
+        let mut dropped = Vec::new();
+        let mut kept = Vec::with_capacity(messages.len());
+        for (index, message) in std::mem::take(messages).into_iter().enumerate() {
+            if message.role.is_some_and(|role| roles.contains(&role)) {
+                dropped.push((index, message));
+            } else {
+                kept.push(message);
+            }
+        }
+        *messages = kept;
+        dropped
+    }
+
+    /// Puts the messages taken out by `drop_messages` back at their index.
+    fn restore_dropped(&mut self, dropped: Vec<(usize, PostMessage)>) {
+        if let Some(messages) = self.messages.as_mut() {
+            for (index, message) in dropped {
+                messages.insert(index, message);
+            }
+        }
+    }
+
+    /// Appends the topic to the output of the configured format, see `save`.
+    fn write_outputs(&mut self, config: &Config, report: &mut Report) -> Result<Option<PathBuf>> {
+        let output_dir = &config.scrape.output_dir;
+        let url = self.url.clone();
+        let path = match config.export.format {
+            ExportFormat::Docx => {
+                let path = self.document_path(output_dir, &config.export);
+                let mut properties = if path.exists() {
+                    let properties = read_custom_properties(&path)?;
+                    if let Some(reason) = protection_reason(&properties) {
+                        warn!("Not adding {} to {}: {}", self.url, path.display(), reason);
+                        report.protected_documents.push(ProtectedDocument {
+                            path: path.display().to_string(),
+                            topic_url: self.url.clone(),
+                            reason,
+                        });
+                        return Ok(None);
+                    }
+                    properties
+                } else {
+                    Default::default()
+                };
+                let created = if path.exists() {
+                    read_created(&path)?
+                } else {
+                    None
+                };
+                if let Some(ref notice) = config.export.notice {
+                    properties.insert(ATTRIBUTION_PROPERTY.to_owned(), notice.attribution.clone());
+                }
+                journaled(&path, &url, || {
+                    self.messages_to_word(&path, &config.export, report)?;
+                    set_core_properties(
+                        &path,
+                        &CoreProperties {
+                            title: self.category.to_string(),
+                            creator: "Rav Binyamin Wattenberg".to_owned(),
+                            // The category, as in the tags of the Anki cards
+                            keywords: vec![self.category.to_string()],
+                            created,
+                        },
+                    )?;
+                    mark_generated(&path, properties)
+                })?;
+                path
+            }
+            ExportFormat::Csv => {
+                let path = self.document_path(output_dir, &config.export);
+                journaled(&path, &url, || append_messages(self, &path, &config.export))?;
+                self.report_stats(report);
+                path
+            }
+            ExportFormat::Latex => {
+                let path = self.document_path(output_dir, &config.export);
+                journaled(&path, &url, || {
+                    latex::append_topic(self, &path, &config.export)
+                })?;
+                self.report_stats(report);
+                path
+            }
+            ExportFormat::Odt => {
+                let path = self.document_path(output_dir, &config.export);
+                journaled(&path, &url, || {
+                    odt::append_topic(self, &path, &config.export)
+                })?;
+                self.report_stats(report);
+                path
+            }
+            ExportFormat::Anki => {
+                let anki = &config.export.anki;
+                if !anki.categories.is_empty()
+                    && !anki
+                        .categories
+                        .iter()
+                        .any(|category| self.category.matches(category))
+                {
+                    return Ok(None);
+                }
+                let path = self.document_path(output_dir, &config.export);
+                journaled(&path, &url, || append_cards(self, &path, &config.export))?;
+                self.report_stats(report);
+                path
+            }
+        };
+
+        Ok(Some(path))
+    }
+
+    /// File the topic is appended to in the configured format.
+    pub fn document_path(&self, output_dir: &Path, options: &ExportConfig) -> PathBuf {
+        match options.format {
+            ExportFormat::Docx if options.delta => {
+                self.output_path_with_extension(output_dir, "delta.docx")
+            }
+            ExportFormat::Csv => output_dir.join(MESSAGES_CSV_FILE),
+            ExportFormat::Anki => output_dir.join(ANKI_FILE),
+            format => self.output_path_with_extension(
+                output_dir,
+                format.category_extension().expect("per category format"),
+            ),
+        }
+    }
+
+    fn report_stats(&self, report: &mut Report) {
+        let messages_items = self
+            .messages
+            .iter()
+            .flatten()
+            .map(PostMessage::items)
+            .collect::<Vec<_>>();
+        report.topics.push(self.topic_stats(&messages_items));
+    }
+
+    /// Stores a summary on each question; a failing summarizer only costs the summaries.
+    pub async fn summarize_questions(&mut self, summarizer: &dyn Summarizer) {
+        for message in self.messages.iter_mut().flatten() {
+            if message.is_answer() || message.summary.is_some() {
+                continue;
+            }
+            match summarizer.summarize(&message.text()).await {
+                Ok(summary) => message.summary = Some(summary),
+                Err(e) => warn!("Failed to summarize a question of {}: {:#}", self.url, e),
+            }
+        }
+    }
+
+    /// Stores a translation on each message; a failing translator only costs the translations.
+    pub async fn translate_messages(&mut self, translator: &dyn Translator) {
+        for message in self.messages.iter_mut().flatten() {
+            if message.translation.is_some() {
+                continue;
+            }
+            match translator.translate(&message.text()).await {
+                Ok(translation) => message.translation = Some(translation),
+                Err(e) => warn!("Failed to translate a message of {}: {:#}", self.url, e),
+            }
+        }
+    }
+
+    /// Size of the topic, from the parsed messages.
+    pub fn topic_stats(&self, messages_items: &[Vec<MessageItem>]) -> TopicStats {
+        let words: usize = messages_items
+            .iter()
+            .map(|items| word_count(items.iter().filter_map(MessageItem::as_run)))
+            .sum();
+
+        TopicStats {
+            url: self.url.clone(),
+            title: self.title.clone(),
+            category: self.category.to_string(),
+            replies: self.replies,
+            views: self.views,
+            messages: messages_items.len(),
+            words,
+            reading_minutes: words.div_ceil(READING_WORDS_PER_MINUTE),
+        }
+    }
+
+    /// Messages grouped by exchange: a question with the answers following it.
+    pub fn exchange_groups(&self) -> Vec<Vec<&PostMessage>> {
+        let mut groups: Vec<Vec<&PostMessage>> = Vec::new();
+        let mut answered = true;
+        for message in self.messages.iter().flatten() {
+            match groups.last_mut() {
+                Some(group) if !answered || message.is_answer() => group.push(message),
+                _ => groups.push(vec![message]),
+            }
+            answered = message.is_answer();
+        }
+
+        groups
+    }
+
+    /// Text of each question with the answers following it.
+    pub fn exchanges(&self) -> Vec<String> {
+        self.exchange_groups()
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|message| message.text())
+                    .collect::<Vec<_>>()
+                    .join("\n\n")
+            })
+            .collect()
+    }
+
+    /// Forum id of the topic, the topics being numbered in the order they were posted.
+    pub fn topic_id(&self) -> Option<u64> {
+        // "viewtopic.php?t=101", or "sujet-t101.html" once rewritten
+        let (_, rest) = self
+            .url
+            .split_once("?t=")
+            .or_else(|| self.url.split_once("&t="))
+            .or_else(|| self.url.rsplit_once("-t"))?;
+        rest.split(|c: char| !c.is_ascii_digit())
+            .next()
+            .and_then(|id| id.parse().ok())
+    }
+
+    /// Line giving the replies and views of the topic, e.g. "3 réponses · 154 vues", none when
+    /// the search did not list them.
+    pub fn activity(&self) -> Option<String> {
+        let count = |count: u32, noun: &str| {
+            format!("{} {}{}", count, noun, if count > 1 { "s" } else { "" })
+        };
+        match (self.replies, self.views) {
+            (None, None) => None,
+            (replies, views) => Some(
+                replies
+                    .map(|replies| count(replies, "réponse"))
+                    .into_iter()
+                    .chain(views.map(|views| count(views, "vue")))
+                    .collect::<Vec<_>>()
+                    .join(" · "),
+            ),
+        }
+    }
+
+    /// Line naming the first topic of the series the topic continues, e.g. "Suite de « Le libre
+    /// arbitre »", none when the topic is merged into its chapter.
+    pub fn series_line(&self, titles: &TitlesConfig) -> Option<String> {
+        self.series
+            .as_ref()
+            .filter(|series| !series.merged)
+            .map(|series| {
+                format!(
+                    "Suite de «\u{A0}{}\u{A0}»",
+                    heading_title(&series.first_title, titles)
+                )
+            })
+    }
+
+    /// Whether the topic is exported in the chapter of the topic it continues, without a heading.
+    pub fn is_merged(&self) -> bool {
+        self.series.as_ref().is_some_and(|series| series.merged)
+    }
+
+    /// Line crediting the forum thread, dated of the day it was consulted.
+    pub fn source_footer(&self) -> String {
+        format!(
+            "Source: {} (consulté le {})",
+            self.url,
+            chrono::Local::now().format("%d/%m/%Y")
+        )
     }
 
-    fn _messages_to_word(&mut self) -> Result<()> {
-        let docx_file = DocxFile::from_file(format!(
-            "files_generated/{}.docx",
-            self.category
-                .escape_default()
-                .collect::<String>()
-                .replace("/", "_")
-        ));
-
-        let file;
-        let mut docx = if docx_file.is_ok() {
-            file = docx_file.unwrap();
-            file.parse().unwrap()
+    pub fn output_path(&self, output_dir: &Path) -> PathBuf {
+        self.output_path_with_extension(output_dir, "docx")
+    }
+
+    /// File of the category of the topic for the given format.
+    pub fn output_path_with_extension(&self, output_dir: &Path, extension: &str) -> PathBuf {
+        category_output_path(output_dir, &self.category.to_string(), extension)
+    }
+
+    /// Appends the title and messages of the topic to the document at `path`.
+    ///
+    /// Messages over `options.long_messages.max_chars` are added to `report`.
+    pub fn messages_to_word(
+        &mut self,
+        path: &Path,
+        options: &ExportConfig,
+        report: &mut Report,
+    ) -> Result<()> {
+        // An unreadable document is an error, not a reason to start a new one over it
+        let docx_file = if path.exists() {
+            Some(DocxFile::from_file(path).map_err(|e| docx_error(e, "open", path))?)
         } else {
-            Docx::default()
+            None
+        };
+
+        // The topics already in the document have to be separated from this one
+        let first_topic = docx_file.is_none();
+        let mut docx = match &docx_file {
+            Some(file) => file.parse().map_err(|e| docx_error(e, "parse", path))?,
+            None => {
+                let mut docx = Docx::default();
+                if let Some(ref front_matter) = options.front_matter {
+                    push_front_matter(&mut docx, front_matter, &self.category.to_string())?;
+                }
+                docx
+            }
         };
 
-        docx.document.push(
-            Paragraph::default()
+        let mut title = heading_title(&self.title, &options.titles);
+        if options.docx.french_typography {
+            title = french_typography(&title);
+        }
+
+        let mut glossary = options
+            .glossary
+            .as_ref()
+            .map(Glossary::from_config)
+            .transpose()?;
+        let mut cross_references = options
+            .cross_references
+            .then(|| CrossReferences::new(&self.url))
+            .flatten();
+        #[cfg(feature = "annotations")]
+        let annotations = options
+            .annotations
+            .as_ref()
+            .map(Annotations::load)
+            .transpose()?;
+
+        let formatting = Formatting::from_config(options);
+        let mut title_property = with_outline_level(
+            ParagraphProperty::default().justification(JustificationVal::Center),
+            formatting.title_level,
+        );
+        // A topic merged into the chapter of the one it continues has no heading of its own
+        if self.is_merged() {
+            docx.document.push(keep_with_next(
+                Paragraph::default()
+                    .push(
+                        Run::default()
+                            .push_text(SERIES_MERGED_LINE)
+                            .property(CharacterProperty::default().italics(true)),
+                    )
+                    .property(ParagraphProperty::default().justification(JustificationVal::Center)),
+            ));
+        } else {
+            match options.topic_break {
+                _ if first_topic => {}
+                TopicBreak::Blank => {}
+                TopicBreak::Page => {
+                    title_property.page_break_before = Some(PageBreakBefore { value: Some(true) });
+                }
+                // Ends the section of the previous topic
+                TopicBreak::Section => {
+                    docx.document
+                        .push(Paragraph::default().property(ParagraphProperty {
+                            section_property: Some(SectionProperty {
+                                ty: Some(SectionTypeP {
+                                    ty: Some(SectionType::NextPage),
+                                }),
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        }));
+                }
+            }
+            let mut title_run = Run::default();
+            for _ in 0..formatting.title_breaks {
+                title_run = title_run.push_break(BreakType::TextWrapping);
+            }
+            let mut title_p = Paragraph::default()
                 .push(
-                    Run::default()
-                        .push_break(BreakType::TextWrapping)
-                        .push_break(BreakType::TextWrapping)
-                        .push_text(
-                            self.title
-                                .to_owned()
-                                .chars()
-                                .take(1)
-                                .collect::<String>()
-                                .to_uppercase()
-                                + &self.title.to_owned().chars().skip(1).collect::<String>(),
-                        )
-                        .property(CharacterProperty::default().bold(true).size(32 as u8)),
+                    title_run.push_text(title).property(
+                        CharacterProperty::default()
+                            .bold(true)
+                            .size(formatting.title_size),
+                    ),
                 )
-                .property(ParagraphProperty::default().justification(JustificationVal::Center)),
-        );
+                .property(title_property);
+            if options.docx.numbering {
+                title_p = numbered(title_p, heading_numbering(&mut docx, 0));
+            }
+            docx.document.push(keep_with_next(title_p));
+        }
 
-        for message in self.messages.as_ref().unwrap() {
-            let author_p = if message.author.contains("Binyamin Wattenberg") {
-                if self.last_author.is_some()
-                    && self
-                        .last_author
-                        .as_ref()
-                        .unwrap()
-                        .contains("Binyamin Wattenberg")
-                {
-                    Paragraph::default().push(Run::default().push_text(""))
-                } else {
-                    Paragraph::default().push(
+        let messages = self.messages.as_deref().unwrap_or_default();
+        let messages_items = messages.iter().map(PostMessage::items).collect::<Vec<_>>();
+
+        let stats = self.topic_stats(&messages_items);
+        let (words, reading_minutes) = (stats.words, stats.reading_minutes);
+        report.topics.push(stats);
+
+        if options.docx.word_count && !self.is_merged() {
+            docx.document.push(
+                Paragraph::default()
+                    .push(
                         Run::default()
-                            .push_break(BreakType::TextWrapping)
-                            .push_text("Réponse:")
-                            .property(
-                                CharacterProperty::default()
-                                    .bold(true)
-                                    .size(24 as u8)
-                                    .underline(UnderlineStyle::Single),
-                            )
-                            .push_break(BreakType::TextWrapping),
+                            .push_text(format!(
+                                "{} mots · {} min de lecture",
+                                words, reading_minutes
+                            ))
+                            .property(CharacterProperty::default().italics(true)),
                     )
-                }
+                    .property(ParagraphProperty::default().justification(JustificationVal::Center)),
+            );
+        }
+
+        let activity = self
+            .activity()
+            .filter(|_| options.activity && !self.is_merged());
+        for line in activity
+            .into_iter()
+            .chain(self.series_line(&options.titles))
+        {
+            docx.document.push(
+                Paragraph::default()
+                    .push(
+                        Run::default()
+                            .push_text(line)
+                            .property(CharacterProperty::default().italics(true)),
+                    )
+                    .property(ParagraphProperty::default().justification(JustificationVal::Center)),
+            );
+        }
+
+        let bilingual = options.docx.translations.is_some()
+            || messages.iter().any(|message| message.translation.is_some());
+
+        // Summary of the last question, waiting for its answer in answers-only mode
+        let mut question = None;
+        let mut answered = false;
+        let mut previous_is_answer = None;
+        let mut previous = None;
+
+        for (message, message_items) in messages.iter().zip(messages_items) {
+            if options.answers_only && !message.is_answer() {
+                question = Some(summarize_question(
+                    &item_runs(message_items),
+                    message.summary.as_deref(),
+                    &self.title,
+                    options.question_summary,
+                ));
+                continue;
+            }
+
+            // Same grouping as `exchange_groups`, an answer per question in answers-only mode
+            let starts_exchange = if options.answers_only {
+                question.is_some() || !answered
             } else {
-                let author_anonymized = anonymize_author(message.author.to_owned());
+                previous_is_answer.is_none_or(|answer| answer && !message.is_answer())
+            };
+            previous_is_answer = Some(message.is_answer());
 
-                Paragraph::default().push(
-                    Run::default()
-                        .push_break(BreakType::TextWrapping)
-                        .push_text(
-                            author_anonymized
-                                .to_lowercase()
-                                .starts_with("rav ")
-                                .then(|| format!("Réponse par {}", author_anonymized))
-                                .unwrap_or(format!("Question par {}", author_anonymized)),
+            let mut author_p = if options.answers_only {
+                let summary = question
+                    .take()
+                    .or_else(|| (!answered).then(|| self.title.clone()));
+                answered = true;
+
+                match summary {
+                    Some(summary) => Paragraph::default()
+                        .push(
+                            Run::default()
+                                .push_break(BreakType::TextWrapping)
+                                .push_text("Question : ")
+                                .property(CharacterProperty::default().bold(true)),
                         )
-                        .property(
-                            CharacterProperty::default()
-                                .bold(true)
-                                .size(24 as u8)
-                                .underline(UnderlineStyle::Single),
+                        .push(
+                            Run::default()
+                                .push_text(summary)
+                                .property(CharacterProperty::default().italics(true))
+                                .push_break(BreakType::TextWrapping),
+                        ),
+                    None => Paragraph::default().push(Run::default().push_text("")),
+                }
+            } else {
+                // Same headings as the other exports
+                match message.heading(previous) {
+                    Some(heading) => Paragraph::default()
+                        .push(
+                            Run::default()
+                                .push_break(BreakType::TextWrapping)
+                                .push_text(heading)
+                                .property(
+                                    formatting
+                                        .heading_property()
+                                        .underline(UnderlineStyle::Single),
+                                )
+                                .push_break(BreakType::TextWrapping),
                         )
-                        .push_break(BreakType::TextWrapping),
-                )
+                        .property(with_outline_level(
+                            ParagraphProperty::default(),
+                            formatting.heading_level,
+                        )),
+                    None => Paragraph::default().push(Run::default().push_text("")),
+                }
             };
 
             self.last_author = Some(message.author.clone());
+            previous = Some(message);
+            if options.docx.numbering && starts_exchange {
+                author_p = numbered(author_p, heading_numbering(&mut docx, 1));
+            }
 
-            let message_p: Vec<Run> = message.to_owned().into();
+            // A comment per review note, with the formatting of the run it flags for a quote not
+            // to be split
+            let mut message_p = Vec::with_capacity(message_items.len());
+            let mut notes = Vec::new();
+            for item in message_items {
+                match item {
+                    MessageItem::Run(run) => {
+                        message_p.extend(notes.drain(..).map(|note| {
+                            MessageItem::Run(Run {
+                                property: run.property.clone(),
+                                ..note
+                            })
+                        }));
+                        message_p.push(MessageItem::Run(run));
+                    }
+                    MessageItem::ReviewNote(reason) if options.docx.review_comments => {
+                        notes.push(push_comment(&mut docx, REVIEW_AUTHOR, reason));
+                    }
+                    MessageItem::ReviewNote(_) => {}
+                    align => message_p.push(align),
+                }
+            }
+            message_p.extend(notes.into_iter().map(MessageItem::Run));
 
-            docx.document.push(author_p);
+            let limits = &options.long_messages;
+            let chars = text_len(message_p.iter().filter_map(MessageItem::as_run));
+            if chars > limits.max_chars {
+                warn!(
+                    "Message by {} in {} is {} characters long",
+                    message.author, self.url, chars
+                );
+                report.long_messages.push(LongMessage {
+                    topic_url: self.url.clone(),
+                    author: message.author.clone(),
+                    date: message.date.clone(),
+                    chars,
+                    truncated: limits.truncate,
+                });
+                if limits.truncate {
+                    message_p = truncate_runs(message_p, limits.max_chars, &self.url);
+                }
+            }
+            if let Some(ref mut glossary) = glossary {
+                message_p = map_runs(message_p, |runs| glossary.annotate(runs, &mut docx));
+            }
+            if let Some(ref mut cross_references) = cross_references {
+                message_p = map_runs(message_p, |runs| cross_references.annotate(runs, &mut docx));
+            }
+            #[cfg(feature = "annotations")]
+            if let Some(ref annotations) = annotations {
+                message_p.extend(
+                    annotations
+                        .annotate(message.id.as_deref(), Vec::new(), &mut docx)
+                        .into_iter()
+                        .map(MessageItem::Run),
+                );
+            }
+            if options.docx.french_typography {
+                message_p = map_runs(message_p, typography::apply_to_runs);
+            }
 
-            // Adding the date
-            docx.document.push(
+            docx.document.push(keep_with_next(author_p));
+
+            // Adding the date, kept with the heading above and the text below
+            docx.document.push(keep_with_next(
                 Paragraph::default().push(
                     Run::default()
-                        .push_text(format!("Le {}", message.date.replace("Posté le: ", "")))
+                        .push_text(options.dates.heading(&message.date, message.is_answer()))
                         .property(
                             CharacterProperty::default()
                                 .bold(true)
@@ -150,125 +880,203 @@ impl Post {
                         )
                         .push_break(BreakType::TextWrapping),
                 ),
-            );
+            ));
 
-            let mut messages_iter = message_p.into_iter();
-            while let Some(run) = messages_iter.next() {
-                // Is this run a citation?
-                if is_citation(&run) {
-                    let mut p = Paragraph::default().property(ParagraphProperty::default().indent(
-                        Indent {
-                            left: Some(300),
-                            ..Default::default()
-                        },
-                    ));
-                    p = p.push(run);
-                    let mut last_run = None;
-                    while let Some(next_run) = messages_iter.next() {
-                        if !is_citation(&next_run) {
-                            last_run = Some(next_run);
-                            break;
-                        }
-                        p = p.push(next_run);
+            // A paragraph per sequence of citation or plain runs, split at the alignment marks
+            let mut body = Vec::new();
+            let mut justification = None;
+            let mut paragraph: Option<(bool, Paragraph)> = None;
+            for item in message_p {
+                let run = match item {
+                    MessageItem::Run(run) => formatting.style_run(run),
+                    MessageItem::Align(mark) => {
+                        body.extend(paragraph.take().map(|(_, p)| p));
+                        justification = mark;
+                        continue;
                     }
-                    docx.document.push(p);
-                    if let Some(last_run) = last_run {
-                        docx.document.push(Paragraph::default().push(last_run));
+                    // Already turned into comments
+                    MessageItem::ReviewNote(_) => continue,
+                };
+                let citation = is_citation(&run);
+                match paragraph {
+                    Some((in_citation, ref mut p)) if in_citation == citation => {
+                        *p = std::mem::take(p).push(run);
                     }
-                } else {
-                    let mut p = Paragraph::default();
-                    p = p.push(run);
-                    let mut last_run = None;
-                    while let Some(next_run) = messages_iter.next() {
-                        if is_citation(&next_run) {
-                            last_run = Some(next_run);
-                            break;
+                    _ => {
+                        body.extend(paragraph.take().map(|(_, p)| p));
+                        let mut p = Paragraph::default();
+                        let property = match (formatting.body_property(citation), &justification) {
+                            (property, Some(justification)) => Some(
+                                property
+                                    .unwrap_or_default()
+                                    .justification(justification.clone()),
+                            ),
+                            (property, None) => property,
+                        };
+                        if let Some(property) = property {
+                            p = p.property(property);
                         }
-                        p = p.push(next_run);
+                        paragraph = Some((citation, p.push(run)));
                     }
-                    docx.document.push(p);
+                }
+            }
+            body.extend(paragraph.map(|(_, p)| p));
 
-                    if let Some(last_run) = last_run {
-                        docx.document
-                            .push(Paragraph::default().push(last_run).property(
-                                ParagraphProperty::default().indent(Indent {
-                                    left: Some(300),
-                                    ..Default::default()
-                                }),
-                            ));
-                    }
+            if bilingual {
+                // A translation supplied by hand wins over the machine one
+                let translation = options
+                    .docx
+                    .translations
+                    .as_ref()
+                    .zip(message.id.as_ref())
+                    .and_then(|(translations, id)| {
+                        std::fs::read_to_string(translations.join(format!("{}.txt", id))).ok()
+                    })
+                    .or_else(|| message.translation.clone());
+                docx.document
+                    .push(bilingual_table(body, translation.as_deref()));
+            } else {
+                for p in body {
+                    docx.document.push(p);
                 }
             }
 
-            docx.document
-                .push(Paragraph::default().push(Run::default().push_text("")));
+            if formatting.message_gap {
+                docx.document
+                    .push(Paragraph::default().push(Run::default().push_text("")));
+            }
         }
 
-        docx.document.push(
-            Run::default()
-                .push_break(BreakType::Page)
-                .push_break(BreakType::Page),
-        );
+        if options.source_footer {
+            docx.document.push(
+                Paragraph::default().push(
+                    Run::default().push_text(self.source_footer()).property(
+                        CharacterProperty::default()
+                            .italics(true)
+                            .size(formatting.footer_size),
+                    ),
+                ),
+            );
+        }
 
-        docx.write_file(format!(
-            "files_generated/{}.docx",
-            self.category
-                .escape_default()
-                .collect::<String>()
-                .replace("/", "_")
-        ))
-        .unwrap();
+        docx.write_file(path)
+            .map_err(|e| docx_error(e, "write", path))?;
 
         Ok(())
     }
 
-    async fn _get_messages(&mut self, client: &Client) -> Result<()> {
-        let mut html = self
-            .html
-            .clone()
-            .ok_or_else(|| anyhow::anyhow!("HTML not fetched for post"))?;
+    /// Parses the messages of the fetched `html` into `messages`, fetching the next pages of the
+    /// topic. A topic over the limits of [`set_topic_limits`] fails, without any of its messages.
+    pub async fn get_messages(&mut self, client: &Client) -> Result<()> {
+        let mut html = Html::parse_document(
+            self.html
+                .as_deref()
+                .ok_or_else(|| anyhow::anyhow!("HTML not fetched for post"))?,
+        );
+        let (max_messages, max_pages) = (
+            MAX_MESSAGES.load(Ordering::Relaxed),
+            MAX_PAGES.load(Ordering::Relaxed),
+        );
 
+        let mut messages = Vec::new();
+        let mut page = 1;
         loop {
-            let posts_sel =
-                Selector::parse(".container > .overflow-hidden.border-blue-500 > div > .flex")
-                    .map_err(|e| anyhow::anyhow!("Failed to parse posts selector: {}", e))?;
-            let author_sel = Selector::parse("div strong.block.mb-2")
-                .map_err(|e| anyhow::anyhow!("Failed to parse author selector: {}", e))?;
-            let date_sel = Selector::parse("a.text-blue-link")
-                .map_err(|e| anyhow::anyhow!("Failed to parse date selector: {}", e))?;
-            let message_sel = Selector::parse(".py-4.postrow-message")
-                .map_err(|e| anyhow::anyhow!("Failed to parse message selector: {}", e))?;
-
-            html.select(&posts_sel).for_each(|post| {
-                let author = extract!(post, &author_sel);
-                let date = extract!(post, &date_sel);
-                let message = extract!(post, &message_sel, html);
-
-                // We need to update the messages field of the post
-                let post_message = PostMessage {
-                    author,
-                    date,
-                    message,
-                };
-                self.messages
-                    .get_or_insert_with(Vec::new)
-                    .push(post_message);
-            });
+            messages.extend(
+                parse_messages(&html)
+                    .with_context(|| ScrapeContext::topic(Stage::Parse, &self.url).page(page))?,
+            );
+            if messages.len() > max_messages {
+                return Err(anyhow::anyhow!(
+                    "More than {} messages, see scrape.max_topic_messages",
+                    max_messages
+                ))
+                .with_context(|| ScrapeContext::topic(Stage::Parse, &self.url).page(page));
+            }
 
             // If there are other pages, we need to replace the HTML field with the next page
-            let next_page_sel = Selector::parse("nav.pagination > a[href^='suivante']")
-                .map_err(|e| anyhow::anyhow!("Failed to parse next page selector: {}", e))?;
-
-            let next_page = html.select(&next_page_sel).next();
-            if next_page.is_none() {
+            let Some(next_page) = html.select(&NEXT_PAGE).next() else {
                 break;
-            }
+            };
 
-            let url = next_page.unwrap().value().attr("href").unwrap();
-            let url = format!("{}{}", BASE_URL, url);
-            html = get_html(client, url).await?.0;
+            // Pagination links are relative to the topic page
+            let href = next_page
+                .value()
+                .attr("href")
+                .ok_or_else(|| anyhow::anyhow!("Next page link without href"))
+                .with_context(|| ScrapeContext::topic(Stage::Parse, &self.url).page(page))?;
+            page += 1;
+            let context = || ScrapeContext::topic(Stage::Fetch, &self.url).page(page);
+            if page > max_pages {
+                return Err(anyhow::anyhow!(
+                    "More than {} pages, see scrape.max_topic_pages",
+                    max_pages
+                ))
+                .with_context(context);
+            }
+            let url = Url::parse(&self.url)
+                .and_then(|url| url.join(href))
+                .with_context(context)?;
+            html = get_html(client, url).await.with_context(context)?.0;
         }
+        let all = self.messages.get_or_insert_with(Vec::new);
+        all.extend(messages);
+        classify_roles(all);
 
         Ok(())
     }
 }
+
+/// File of `category` for the given format.
+pub fn category_output_path(output_dir: &Path, category: &str, extension: &str) -> PathBuf {
+    output_dir.join(format!(
+        "{}.{}",
+        safe_file_stem(&category.escape_default().collect::<String>()),
+        extension
+    ))
+}
+
+/// Extracts the messages of one page of a topic, skipping the ones missing their author, date or
+/// content.
+pub fn parse_messages(html: &Html) -> Result<Vec<PostMessage>> {
+    Ok(html
+        .select(&POSTS)
+        .filter_map(|post| {
+            let (Some(author), Some(date), Some(message)) = (
+                extract!(post, &AUTHOR),
+                extract!(post, &DATE),
+                extract!(post, &POST_MESSAGE, html),
+            ) else {
+                warn!("Message without its author, date or content, skipped");
+                return None;
+            };
+            let message = sanitize_message(&message);
+            // "viewtopic.php?p=5001#5001"
+            let id = post
+                .select(&DATE)
+                .next()
+                .and_then(|link| link.attr("href"))
+                .and_then(|href| href.split_once("p="))
+                .map(|(_, rest)| rest.split(['&', '#']).next().unwrap_or_default().to_owned())
+                .filter(|id| !id.is_empty());
+
+            let author_profile = post
+                .select(&AUTHOR)
+                .next()
+                .and_then(|author| author.select(&PROFILE).next())
+                .and_then(|link| link.attr("href"))
+                .map(str::to_owned);
+
+            Some(PostMessage {
+                id,
+                author,
+                author_profile,
+                date,
+                message,
+                summary: None,
+                translation: None,
+                deleted: false,
+                role: None,
+            })
+        })
+        .collect())
+}
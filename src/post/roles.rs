@@ -0,0 +1,96 @@
+use crate::post::post::{PostMessage, MESSAGE};
+use clap::ValueEnum;
+use ego_tree::NodeRef;
+use regex::Regex;
+use scraper::{CaseSensitivity, ElementRef, Html, Node};
+use serde::Deserialize;
+use std::sync::LazyLock;
+
+/// Words of thanks, in French and in the Hebrew and English expressions used on the forum
+static THANKS: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\b(?:merci|remercie|remerciements?|toda|todah|thanks?|yasher|yeyasher|tizkou|tizkeh|hakavod|chkoyah)\b",
+    )
+    .expect("valid regex")
+});
+
+/// Messages only pushing the topic back up the forum, "up", "UP !!", "+1"
+static BUMP: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)^\W*(?:up|bump|\+1)\W*$").expect("valid regex"));
+
+/// Longest message of thanks, in words of its own: a longer one says something more
+const THANKS_MAX_WORDS: usize = 25;
+
+/// Part a message plays in its topic, see [`classify_roles`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Role {
+    /// Asked before the first answer
+    Question,
+    /// Asked or added after an answer
+    FollowUp,
+    /// A few words of thanks after an answer, "Merci beaucoup !"
+    Thanks,
+    /// Nothing but "up" or emojis, to push the topic back up the forum
+    Bump,
+    /// Posted by a Rav
+    Answer,
+}
+
+/// Labels the messages of a topic, in the order they were posted, with their role: by their
+/// author, their place before or after the first answer, and their words of thanks or of bump.
+pub fn classify_roles(messages: &mut [PostMessage]) {
+    let mut answered = false;
+    for (index, message) in messages.iter_mut().enumerate() {
+        let role = if message.is_answer() {
+            Role::Answer
+        } else if index > 0 && is_bump(message) {
+            Role::Bump
+        } else if !answered {
+            Role::Question
+        } else if is_thanks(message) {
+            Role::Thanks
+        } else {
+            Role::FollowUp
+        };
+        answered |= role == Role::Answer;
+        message.role = Some(role);
+    }
+}
+
+/// Whether the words of `message`, its quotes left out, are short thanks without a question.
+fn is_thanks(message: &PostMessage) -> bool {
+    let text = own_text(message);
+    text.split_whitespace().count() <= THANKS_MAX_WORDS
+        && !text.contains('?')
+        && THANKS.is_match(&text)
+}
+
+/// Whether the words of `message`, its quotes left out, are only "up" or emojis.
+fn is_bump(message: &PostMessage) -> bool {
+    let text = own_text(message);
+    let text = text.trim();
+    BUMP.is_match(text) || (!text.is_empty() && !text.chars().any(char::is_alphanumeric))
+}
+
+/// Text of `message` outside of the messages it quotes.
+fn own_text(message: &PostMessage) -> String {
+    let html = Html::parse_fragment(&message.message);
+    let Some(container) = html.select(&MESSAGE).next() else {
+        return String::new();
+    };
+    container
+        .descendants()
+        .filter(|node| !node.ancestors().any(is_quote))
+        .filter_map(|node| node.value().as_text())
+        .map(|text| &**text)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_quote(node: NodeRef<Node>) -> bool {
+    ElementRef::wrap(node).is_some_and(|el| {
+        el.value()
+            .has_class("border-blue-500", CaseSensitivity::CaseSensitive)
+    })
+}
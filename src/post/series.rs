@@ -0,0 +1,159 @@
+use crate::post::category::Category;
+use crate::post::post::Post;
+use crate::utils::functions::title_key;
+use regex::Regex;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::sync::LazyLock;
+
+/// Ending of the title of a topic continuing an earlier one: "… (suite)", "… suite et fin",
+/// "… 2", "… - partie 3"
+static CONTINUATION: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)^(?P<base>.*?\S)[\s\-–—:,]*\(?\s*(?:\bsuite(?:\s+(?P<suite>\d{1,2}))?(?:\s+et\s+fin)?|(?:partie\s*|part\.?\s*)?(?P<part>\b\d{1,2}))\s*\)?[\s?!.]*$",
+    )
+    .expect("valid regex")
+});
+
+/// Most parts of a series, the larger numbers ending a title being years or quantities
+const MAX_PART: u32 = 20;
+
+/// Place of a topic in the series of the topics continuing a first one, see `export.series`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SeriesLink {
+    pub first_url: String,
+    pub first_title: String,
+    /// From 2, "… (suite)" being the second part
+    pub part: u32,
+    /// Whether the topic is exported in the chapter of the first one, right after it
+    pub merged: bool,
+}
+
+/// Title of the first topic and part of a topic continuing it, `None` for the other titles.
+pub fn continuation_of(title: &str) -> Option<(&str, u32)> {
+    let captures = CONTINUATION.captures(title.trim())?;
+    let base = captures.name("base")?.as_str();
+    let part = match (captures.name("suite"), captures.name("part")) {
+        (Some(suite), _) => suite.as_str().parse::<u32>().ok()? + 1,
+        (None, Some(part)) => part.as_str().parse().ok()?,
+        (None, None) => 2,
+    };
+    let named = base.chars().any(char::is_alphabetic);
+    (named && (2..=MAX_PART).contains(&part)).then_some((base, part))
+}
+
+/// Links the topics continuing an earlier one of the same category to it, returning their
+/// number. The first topic is the one titled as the others without their ending, posted before
+/// them.
+pub fn link_series(posts: &mut BTreeMap<String, Post>) -> usize {
+    // Category and title key -> url, title and id of the first topic of the series
+    let mut firsts: HashMap<(Category, String), (&str, &str, Option<u64>)> = HashMap::new();
+    for post in posts.values() {
+        let first = (post.url.as_str(), post.title.as_str(), post.topic_id());
+        firsts
+            .entry((post.category.clone(), title_key(&post.title)))
+            .and_modify(|known| {
+                if first.2 < known.2 {
+                    *known = first;
+                }
+            })
+            .or_insert(first);
+    }
+
+    let mut links = posts
+        .values()
+        .filter_map(|post| {
+            let (base, part) = continuation_of(&post.title)?;
+            let &(first_url, first_title, first_id) =
+                firsts.get(&(post.category.clone(), title_key(base)))?;
+            let posted_before = match (first_id, post.topic_id()) {
+                (Some(first_id), Some(id)) => first_id < id,
+                _ => first_url != post.url,
+            };
+            posted_before.then(|| {
+                (
+                    post.url.clone(),
+                    SeriesLink {
+                        first_url: first_url.to_owned(),
+                        first_title: first_title.to_owned(),
+                        part,
+                        merged: false,
+                    },
+                )
+            })
+        })
+        .collect::<HashMap<_, _>>();
+
+    // "… 2 (suite)" continues the series of "… 2"
+    let roots = links
+        .keys()
+        .map(|url| {
+            let mut root = &links[url];
+            for _ in 0..MAX_PART {
+                match links.get(&root.first_url) {
+                    Some(link) => root = link,
+                    None => break,
+                }
+            }
+            (
+                url.clone(),
+                (root.first_url.clone(), root.first_title.clone()),
+            )
+        })
+        .collect::<Vec<_>>();
+    for (url, (first_url, first_title)) in roots {
+        if let Some(link) = links.get_mut(&url) {
+            (link.first_url, link.first_title) = (first_url, first_title);
+        }
+    }
+
+    let linked = links.len();
+    for (url, link) in links {
+        if let Some(post) = posts.get_mut(&url) {
+            post.series = Some(link);
+        }
+    }
+    linked
+}
+
+/// Moves the topics continuing a first one exported by the run right after it, in the order of
+/// their parts, and marks them merged into its chapter.
+pub fn merge_series(post_urls: &mut Vec<String>, posts: &mut BTreeMap<String, Post>) {
+    let exported = post_urls.iter().cloned().collect::<HashSet<_>>();
+    // First url -> its continuations, by part then by id
+    let mut continuations: BTreeMap<String, Vec<(u32, Option<u64>, String)>> = BTreeMap::new();
+    for url in post_urls.iter() {
+        let post = &posts[url];
+        if let Some(ref series) = post.series {
+            if exported.contains(&series.first_url) {
+                continuations
+                    .entry(series.first_url.clone())
+                    .or_default()
+                    .push((series.part, post.topic_id(), url.clone()));
+            }
+        }
+    }
+    if continuations.is_empty() {
+        return;
+    }
+
+    let merged = continuations
+        .values()
+        .flatten()
+        .map(|(_, _, url)| url.clone())
+        .collect::<HashSet<_>>();
+    let mut ordered = Vec::with_capacity(post_urls.len());
+    for url in post_urls.drain(..) {
+        if merged.contains(&url) {
+            continue;
+        }
+        let parts = continuations.remove(&url);
+        ordered.push(url);
+        for (_, _, url) in parts.into_iter().flatten().collect::<BTreeSet<_>>() {
+            if let Some(series) = posts.get_mut(&url).and_then(|post| post.series.as_mut()) {
+                series.merged = true;
+            }
+            ordered.push(url);
+        }
+    }
+    *post_urls = ordered;
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::panic::PanicHookInfo;
+use std::path::Path;
+use std::sync::{Mutex, PoisonError};
+use tracing::error;
+
+// Url of the topic being exported, see `set_current_topic`
+static CURRENT_TOPIC: Mutex<Option<String>> = Mutex::new(None);
+// Last panic seen by the hook, see `take_crash`
+static CRASH: Mutex<Option<Crash>> = Mutex::new(None);
+
+/// A panic of the scraper, written as the failure report of the run that it ended.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Crash {
+    /// Topic being exported when it panicked, `None` outside of the export
+    pub topic_url: Option<String>,
+    pub message: String,
+    /// E.g. "src/parser/parser.rs:120:9"
+    pub location: Option<String>,
+    pub at: DateTime<Utc>,
+}
+
+impl Crash {
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize crash")?;
+        std::fs::write(path.as_ref(), json)
+            .with_context(|| format!("Failed to write crash report {}", path.as_ref().display()))?;
+
+        Ok(())
+    }
+}
+
+/// Records the topic being exported, named by the panic hook if the scraper panics meanwhile.
+pub fn set_current_topic(url: Option<&str>) {
+    *CURRENT_TOPIC.lock().unwrap_or_else(PoisonError::into_inner) = url.map(str::to_owned);
+}
+
+/// Logs the panics with the topic being exported and keeps them for [`take_crash`], before the
+/// hook that was installed, printing the backtrace, runs.
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let crash = crash_of(info);
+        error!(
+            "Panicked while exporting {}: {}",
+            crash.topic_url.as_deref().unwrap_or("no topic"),
+            crash.message
+        );
+        *CRASH.lock().unwrap_or_else(PoisonError::into_inner) = Some(crash);
+        previous(info);
+    }));
+}
+
+/// The last panic recorded by the hook, if any since the previous call.
+pub fn take_crash() -> Option<Crash> {
+    CRASH.lock().unwrap_or_else(PoisonError::into_inner).take()
+}
+
+fn crash_of(info: &PanicHookInfo) -> Crash {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_owned());
+    Crash {
+        topic_url: CURRENT_TOPIC
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone(),
+        message,
+        location: info.location().map(|location| location.to_string()),
+        at: Utc::now(),
+    }
+}
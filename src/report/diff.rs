@@ -0,0 +1,117 @@
+use crate::post::post::Post;
+use crate::report::report::ChangedMessage;
+use crate::state::state::StoredTopic;
+
+/// Cells of the longest common subsequence table above which the middles are not compared word
+/// by word, a rewritten long message being given as removed and added as a whole
+const MAX_DIFF_CELLS: usize = 4 << 20;
+
+/// Words of `old` and `new` compared, in the notation of wdiff: "[-removed-]" and "{+added+}",
+/// the unchanged words kept as they are.
+pub fn word_diff(old: &str, new: &str) -> String {
+    let old = old.split_whitespace().collect::<Vec<_>>();
+    let new = new.split_whitespace().collect::<Vec<_>>();
+
+    // Edits are usually a few words: the common start and end are left out of the table
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let (old_middle, new_middle) = (
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    );
+
+    // Length of the longest common subsequence of the ends of the two middles, not computed
+    // past the cap on its size
+    let compared = old_middle.len().saturating_mul(new_middle.len()) <= MAX_DIFF_CELLS;
+    let mut lengths = Vec::new();
+    if compared {
+        lengths = vec![vec![0usize; new_middle.len() + 1]; old_middle.len() + 1];
+        for i in (0..old_middle.len()).rev() {
+            for j in (0..new_middle.len()).rev() {
+                lengths[i][j] = if old_middle[i] == new_middle[j] {
+                    lengths[i + 1][j + 1] + 1
+                } else {
+                    lengths[i + 1][j].max(lengths[i][j + 1])
+                };
+            }
+        }
+    }
+
+    let mut parts = old[..prefix]
+        .iter()
+        .map(|word| word.to_string())
+        .collect::<Vec<_>>();
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    let flush = |parts: &mut Vec<String>, removed: &mut Vec<&str>, added: &mut Vec<&str>| {
+        if !removed.is_empty() {
+            parts.push(format!("[-{}-]", removed.join(" ")));
+            removed.clear();
+        }
+        if !added.is_empty() {
+            parts.push(format!("{{+{}+}}", added.join(" ")));
+            added.clear();
+        }
+    };
+    let (mut i, mut j) = (0, 0);
+    if !compared {
+        removed.extend_from_slice(old_middle);
+        added.extend_from_slice(new_middle);
+        (i, j) = (old_middle.len(), new_middle.len());
+    }
+    while i < old_middle.len() || j < new_middle.len() {
+        if i < old_middle.len() && j < new_middle.len() && old_middle[i] == new_middle[j] {
+            flush(&mut parts, &mut removed, &mut added);
+            parts.push(old_middle[i].to_owned());
+            i += 1;
+            j += 1;
+        } else if j == new_middle.len()
+            || (i < old_middle.len() && lengths[i + 1][j] >= lengths[i][j + 1])
+        {
+            removed.push(old_middle[i]);
+            i += 1;
+        } else {
+            added.push(new_middle[j]);
+            j += 1;
+        }
+    }
+    flush(&mut parts, &mut removed, &mut added);
+    parts.extend(
+        old[old.len() - suffix..]
+            .iter()
+            .map(|word| word.to_string()),
+    );
+
+    parts.join(" ")
+}
+
+/// Messages of `post` whose text differs from the one stored when `topic` was last exported,
+/// edited since on the forum. Messages exported before their texts were stored are not compared.
+pub fn changed_messages(post: &Post, topic: &StoredTopic) -> Vec<ChangedMessage> {
+    post.messages
+        .iter()
+        .flatten()
+        .filter_map(|message| {
+            let key = message.key();
+            let previous = &topic.messages.get(&key)?.text;
+            let text = message.text();
+            let same_words = previous.split_whitespace().eq(text.split_whitespace());
+            (!same_words).then(|| ChangedMessage {
+                topic_url: post.url.clone(),
+                author: message.author.clone(),
+                date: message.date.clone(),
+                diff: word_diff(previous, &text),
+                key,
+                text,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,67 @@
+use crate::parser::hooks;
+use crate::post::post::{PostMessage, MESSAGE};
+use ego_tree::NodeRef;
+use scraper::{CaseSensitivity, ElementRef, Html, Node};
+use std::collections::BTreeMap;
+
+/// Words of the HTML of `message` missing from the text the parser made of it, lowercased.
+///
+/// The text of the HTML goes through the same text hooks as the parser, only the words a
+/// transform removes on purpose are not reported.
+pub fn lost_words(message: &PostMessage) -> Vec<String> {
+    let html = Html::parse_fragment(&message.message);
+    let Some(container) = html.select(&MESSAGE).next() else {
+        return Vec::new();
+    };
+    let source = container
+        .descendants()
+        .filter(|node| !node.ancestors().any(is_citation_header))
+        .filter_map(|node| node.value().as_text())
+        .map(|text| hooks::transform_text(text.trim().into()))
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    let mut parsed = word_counts(&message.text());
+    let mut lost = Vec::new();
+    for word in words(&source) {
+        match parsed.get_mut(&word) {
+            Some(count) if *count > 0 => *count -= 1,
+            _ => lost.push(word),
+        }
+    }
+    lost
+}
+
+/// "X a écrit:" above a quote, left out of the documents on purpose.
+fn is_citation_header(node: NodeRef<Node>) -> bool {
+    let Some(el) = ElementRef::wrap(node) else {
+        return false;
+    };
+    let in_citation = node
+        .parent()
+        .and_then(ElementRef::wrap)
+        .is_some_and(|parent| {
+            parent
+                .value()
+                .has_class("border-blue-500", CaseSensitivity::CaseSensitive)
+        });
+    el.value().name() == "div"
+        && in_citation
+        && el
+            .next_siblings()
+            .any(|sibling| sibling.value().is_element())
+}
+
+fn words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+}
+
+fn word_counts(text: &str) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for word in words(text) {
+        *counts.entry(word).or_default() += 1;
+    }
+    counts
+}
@@ -0,0 +1,6 @@
+pub mod crash;
+pub mod diff;
+pub mod fidelity;
+#[allow(clippy::module_inception)]
+pub mod report;
+pub mod summary;
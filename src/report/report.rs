@@ -0,0 +1,123 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+/// What happened during a run that deserves a human look, written next to the manifest.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Report {
+    pub topics: Vec<TopicStats>,
+    pub long_messages: Vec<LongMessage>,
+    #[serde(default)]
+    pub protected_documents: Vec<ProtectedDocument>,
+    /// Messages whose words did not all make it into the documents, see `--verify`
+    #[serde(default)]
+    pub fidelity_losses: Vec<FidelityLoss>,
+    /// Messages edited on the forum since they were exported
+    #[serde(default)]
+    pub changed_messages: Vec<ChangedMessage>,
+    /// Messages deleted from the forum since they were exported, kept in the documents
+    #[serde(default)]
+    pub deleted_messages: Vec<DeletedMessage>,
+}
+
+/// Size of an exported topic, used to plan the printed volumes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TopicStats {
+    pub url: String,
+    pub title: String,
+    pub category: String,
+    /// Replies and views listed by the search
+    #[serde(default)]
+    pub replies: Option<u32>,
+    #[serde(default)]
+    pub views: Option<u32>,
+    pub messages: usize,
+    pub words: usize,
+    pub reading_minutes: usize,
+}
+
+/// A message over the configured size, usually an accidental paste of a whole text.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LongMessage {
+    pub topic_url: String,
+    pub author: String,
+    pub date: String,
+    pub chars: usize,
+    pub truncated: bool,
+}
+
+/// A topic left out of its document, which is protected against rewrites.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProtectedDocument {
+    pub path: String,
+    pub topic_url: String,
+    pub reason: String,
+}
+
+/// Words of a message the parser lost on the way to the documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FidelityLoss {
+    pub topic_url: String,
+    pub author: String,
+    pub date: String,
+    pub lost_words: Vec<String>,
+}
+
+/// A message whose text changed since it was exported, often a silent edit by a moderator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChangedMessage {
+    pub topic_url: String,
+    pub author: String,
+    pub date: String,
+    pub key: String,
+    /// Words removed as "[-…-]" and added as "{+…+}"
+    pub diff: String,
+    /// Text of the message as now published
+    pub text: String,
+}
+
+/// A message no longer on the forum, its last exported text kept in the state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeletedMessage {
+    pub topic_url: String,
+    pub author: String,
+    pub date: String,
+    pub key: String,
+}
+
+impl Report {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read report {}", path.display()))?;
+
+        serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse report {}", path.display()))
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize report")?;
+        std::fs::write(path.as_ref(), json)
+            .with_context(|| format!("Failed to write report {}", path.as_ref().display()))?;
+
+        Ok(())
+    }
+
+    /// Adds what `other` holds, e.g. the report of a single topic, to this one.
+    pub fn append(&mut self, other: Report) {
+        let Report {
+            topics,
+            long_messages,
+            protected_documents,
+            fidelity_losses,
+            changed_messages,
+            deleted_messages,
+        } = other;
+        self.topics.extend(topics);
+        self.long_messages.extend(long_messages);
+        self.protected_documents.extend(protected_documents);
+        self.fidelity_losses.extend(fidelity_losses);
+        self.changed_messages.extend(changed_messages);
+        self.deleted_messages.extend(deleted_messages);
+    }
+}
@@ -0,0 +1,97 @@
+use crate::parser::coverage::Coverage;
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+use std::time::Instant;
+use tracing::info;
+
+/// Counters of a run, written as JSON for the automation wrapping the scraper.
+#[derive(Debug, Serialize)]
+pub struct RunSummary {
+    /// Search result pages requested, the empty page ending the results included
+    pub search_pages: usize,
+    pub topics_discovered: usize,
+    /// Topics listed with the same last message as when they were exported, not fetched
+    pub topics_unchanged: usize,
+    pub topics_fetched: usize,
+    pub topics_exported: usize,
+    /// Topics left to the next runs by `--max-requests`
+    pub topics_deferred: usize,
+    /// Topics that could not be fetched or exported
+    pub topics_failed: usize,
+    pub bytes_downloaded: u64,
+    /// Failures that did not stop the run
+    pub errors: usize,
+    /// What the parser made of the markup of the messages
+    pub parser_coverage: Coverage,
+    pub duration_seconds: f64,
+    pub stages: Vec<StageDuration>,
+    #[serde(skip)]
+    started: Instant,
+    #[serde(skip)]
+    stage_started: Instant,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StageDuration {
+    pub name: &'static str,
+    pub seconds: f64,
+}
+
+impl Default for RunSummary {
+    fn default() -> Self {
+        Self {
+            search_pages: 0,
+            topics_discovered: 0,
+            topics_unchanged: 0,
+            topics_fetched: 0,
+            topics_exported: 0,
+            topics_deferred: 0,
+            topics_failed: 0,
+            bytes_downloaded: 0,
+            errors: 0,
+            parser_coverage: Coverage::default(),
+            duration_seconds: 0.0,
+            stages: Vec::new(),
+            started: Instant::now(),
+            stage_started: Instant::now(),
+        }
+    }
+}
+
+impl RunSummary {
+    /// Records the time spent since the end of the previous stage.
+    pub fn end_stage(&mut self, name: &'static str) {
+        let now = Instant::now();
+        self.stages.push(StageDuration {
+            name,
+            seconds: (now - self.stage_started).as_secs_f64(),
+        });
+        self.stage_started = now;
+        self.duration_seconds = (now - self.started).as_secs_f64();
+    }
+
+    pub fn log(&self) {
+        info!(
+            "Run summary: {} search pages, {} topics discovered, {} unchanged, {} fetched, {} exported, {} failed, {} bytes downloaded, {} errors, markup {:.1}% handled, {:.1}% degraded, {:.1}% dropped in {:.1}s",
+            self.search_pages,
+            self.topics_discovered,
+            self.topics_unchanged,
+            self.topics_fetched,
+            self.topics_exported,
+            self.topics_failed,
+            self.bytes_downloaded,
+            self.errors,
+            self.parser_coverage.handled_percent,
+            self.parser_coverage.degraded_percent,
+            self.parser_coverage.dropped_percent,
+            self.duration_seconds
+        );
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize run summary")?;
+        std::fs::write(path.as_ref(), json)
+            .with_context(|| format!("Failed to write run summary {}", path.as_ref().display()))
+    }
+}
@@ -0,0 +1,170 @@
+use crate::config::config::DatesConfig;
+use crate::export::core_properties::read_title;
+use crate::export::docx::docx_error;
+use crate::post::post::Post;
+use crate::state::state::{State, StoredTopic};
+use crate::utils::functions::{parse_post_date, title_key};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use docx_rust::document::{BodyContent, ParagraphContent};
+use docx_rust::formatting::JustificationVal;
+use docx_rust::DocxFile;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A topic read back from a document generated before the state was kept.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedTopic {
+    pub title: String,
+    pub category: String,
+    /// From its "Source:" line, when the document has one
+    pub url: Option<String>,
+    /// Of its messages, in the time zone of the forum
+    pub dates: Vec<NaiveDateTime>,
+}
+
+struct ParagraphText {
+    text: String,
+    centered: bool,
+    bold: bool,
+}
+
+/// Topics of a generated docx: each starts with its centered bold title, each of its messages
+/// with a "Le {date}" line.
+pub fn read_document(path: &Path, dates: &DatesConfig) -> Result<Vec<ImportedTopic>> {
+    let category = match read_title(path)? {
+        Some(title) if !title.is_empty() => title,
+        _ => path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default(),
+    };
+    let file = DocxFile::from_file(path).map_err(|e| docx_error(e, "open", path))?;
+    let docx = file.parse().map_err(|e| docx_error(e, "parse", path))?;
+
+    let paragraphs = docx
+        .document
+        .body
+        .content
+        .iter()
+        .filter_map(|content| match content {
+            BodyContent::Paragraph(paragraph) => Some(paragraph),
+            _ => None,
+        })
+        .map(|paragraph| {
+            let runs = paragraph
+                .content
+                .iter()
+                .filter_map(|content| match content {
+                    ParagraphContent::Run(run) => Some(run),
+                    _ => None,
+                })
+                .filter(|run| run.iter_text().any(|text| !text.trim().is_empty()))
+                .collect::<Vec<_>>();
+            ParagraphText {
+                text: runs
+                    .iter()
+                    .flat_map(|run| run.iter_text())
+                    .map(|text| text.to_string())
+                    .collect::<String>()
+                    .trim()
+                    .to_owned(),
+                centered: paragraph
+                    .property
+                    .as_ref()
+                    .and_then(|property| property.justification.as_ref())
+                    .is_some_and(|justification| {
+                        matches!(justification.value, JustificationVal::Center)
+                    }),
+                bold: !runs.is_empty()
+                    && runs.iter().all(|run| {
+                        run.property
+                            .as_ref()
+                            .and_then(|property| property.bold.as_ref())
+                            .is_some_and(|bold| bold.value != Some(false))
+                    }),
+            }
+        });
+
+    let mut topics: Vec<ImportedTopic> = Vec::new();
+    for paragraph in paragraphs {
+        if paragraph.text.is_empty() {
+            continue;
+        }
+        if paragraph.centered && paragraph.bold {
+            topics.push(ImportedTopic {
+                title: paragraph.text,
+                category: category.clone(),
+                url: None,
+                dates: Vec::new(),
+            });
+            continue;
+        }
+        let Some(topic) = topics.last_mut() else {
+            continue;
+        };
+        if let Some(url) = paragraph.text.strip_prefix("Source: ") {
+            topic.url = url.split_whitespace().next().map(str::to_owned);
+        } else if let Some(date) = paragraph.text.strip_prefix("Le ") {
+            // Without the Hebrew date following it
+            let date = date.split(" (").next().unwrap_or(date);
+            topic.dates.extend(dates.parse(date));
+        }
+    }
+
+    Ok(topics)
+}
+
+/// Records the topics of the documents in `state`, finding their url among the topics listed by
+/// the search when the documents do not give it, and returns the ones not found.
+///
+/// The forum ids of the messages are not in the documents: a delta run exports the imported
+/// topics again.
+pub fn import_topics(
+    state: &mut State,
+    topics: Vec<ImportedTopic>,
+    listed: &BTreeMap<String, Post>,
+) -> Vec<ImportedTopic> {
+    let mut unmatched = Vec::new();
+    for topic in topics {
+        let post = match topic.url {
+            Some(ref url) => listed.get(url),
+            None => listed.values().find(|post| {
+                post.category.matches(&topic.category)
+                    && title_key(&post.title) == title_key(&topic.title)
+            }),
+        };
+        let Some(url) = topic
+            .url
+            .clone()
+            .or_else(|| post.map(|post| post.url.clone()))
+        else {
+            unmatched.push(topic);
+            continue;
+        };
+
+        let last_message = topic.dates.iter().max().copied();
+        // A topic listed with the last message of the document has nothing new to fetch
+        let last_post = post
+            .and_then(|post| post.last_post.clone())
+            .filter(|listed| {
+                let date = listed.split_whitespace().take(2).collect::<Vec<_>>();
+                last_message.is_some() && parse_post_date(&date.join(" ")) == last_message
+            });
+        state.topics.insert(
+            url.clone(),
+            StoredTopic {
+                title: post.map_or_else(|| topic.title.clone(), |post| post.title.clone()),
+                category: topic.category.clone(),
+                first_message: topic.dates.iter().min().copied(),
+                last_message,
+                last_post,
+                replies: post.and_then(|post| post.replies),
+                views: post.and_then(|post| post.views),
+                messages: BTreeMap::new(),
+            },
+        );
+        state.exported.entry(url).or_default();
+    }
+    unmatched
+}
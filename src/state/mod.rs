@@ -0,0 +1,6 @@
+pub mod import;
+pub mod pauses;
+pub mod schedule;
+#[allow(clippy::module_inception)]
+pub mod state;
+pub mod tombstones;
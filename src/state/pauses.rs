@@ -0,0 +1,98 @@
+use crate::config::config::PausesConfig;
+use crate::utils::hebrew::{HebrewDate, NISSAN, SIVAN, TICHRI};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Utc, Weekday};
+use serde::Deserialize;
+use std::str::FromStr;
+
+/// A time of the week, e.g. `"friday 18:00"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct WeekTime {
+    pub weekday: Weekday,
+    pub time: NaiveTime,
+}
+
+impl FromStr for WeekTime {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let error = || {
+            format!(
+                "Invalid time of the week {:?}, expected \"friday 18:00\"",
+                text
+            )
+        };
+        let (weekday, time) = text.trim().split_once(' ').ok_or_else(error)?;
+        Ok(WeekTime {
+            weekday: weekday.parse().map_err(|_| error())?,
+            time: NaiveTime::parse_from_str(time.trim(), "%H:%M").map_err(|_| error())?,
+        })
+    }
+}
+
+impl TryFrom<String> for WeekTime {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse()
+    }
+}
+
+/// Whether the day is a day of Yom Tov outside of Israel, when the forum is not read.
+pub fn is_holiday(date: NaiveDate) -> bool {
+    let hebrew = HebrewDate::from_gregorian(date);
+    match hebrew.month {
+        // Rosh Hachana, Yom Kippour, Souccot, Chemini Atseret and Sim'hat Torah
+        TICHRI => matches!(hebrew.day, 1 | 2 | 10 | 15 | 16 | 22 | 23),
+        // Pessa'h
+        NISSAN => matches!(hebrew.day, 15 | 16 | 21 | 22),
+        // Chavouot
+        SIVAN => matches!(hebrew.day, 6 | 7),
+        _ => false,
+    }
+}
+
+/// Pauses, in wall-clock times, in effect around the day `day`.
+fn pauses_around(config: &PausesConfig, day: NaiveDate) -> Vec<(NaiveDateTime, NaiveDateTime)> {
+    let days = (-8..=8).map(|offset| day + Duration::days(offset));
+    let mut pauses = Vec::new();
+    for window in &config.weekly {
+        for start in days
+            .clone()
+            .filter(|date| date.weekday() == window.from.weekday)
+        {
+            let start = start.and_time(window.from.time);
+            let mut end = start.date() + Duration::days(window.days_until_end() as i64);
+            if end.and_time(window.to.time) <= start {
+                end += Duration::days(7);
+            }
+            pauses.push((start, end.and_time(window.to.time)));
+        }
+    }
+    if config.holidays {
+        // From the eve of each day, so that consecutive days join up
+        for date in days.filter(|date| is_holiday(*date)) {
+            pauses.push((
+                (date - Duration::days(1)).and_time(config.holiday_start),
+                date.and_time(config.holiday_end),
+            ));
+        }
+    }
+    pauses
+}
+
+/// End of the pause `now` falls in, following the pauses that start before it ends (a Yom Tov
+/// right after Chabbat), `None` when scraping is allowed.
+pub fn pause_until(config: &PausesConfig, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let start = config.timezone.from_utc(now);
+    let mut until = start;
+    while let Some(end) = pauses_around(config, until.date())
+        .into_iter()
+        .filter(|(from, to)| *from <= until && until < *to)
+        .map(|(_, to)| to)
+        .max()
+    {
+        until = end;
+    }
+    (until > start).then(|| config.timezone.to_utc(until))
+}
@@ -0,0 +1,33 @@
+use crate::cli::args::Strategy;
+use crate::post::post::Post;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Picks the topics a run limited to `budget` requests fetches, at least one request each,
+/// returned in the order they were posted.
+pub fn schedule_topics(
+    posts: &BTreeMap<String, Post>,
+    strategy: Strategy,
+    exported: &BTreeMap<String, BTreeSet<String>>,
+    budget: usize,
+) -> Vec<String> {
+    let mut candidates: Vec<&Post> = match strategy {
+        Strategy::Newest => posts.values().collect(),
+        // Topics already in the documents wait for a run in the default mode
+        Strategy::Backfill => posts
+            .values()
+            .filter(|post| !exported.contains_key(&post.url))
+            .collect(),
+    };
+    candidates.sort_by_key(|post| (post.topic_id(), post.url.clone()));
+    if strategy == Strategy::Newest {
+        candidates.reverse();
+    }
+    candidates.truncate(budget);
+
+    let mut urls: Vec<String> = candidates
+        .into_iter()
+        .map(|post| post.url.clone())
+        .collect();
+    urls.sort_by_key(|url| (posts[url].topic_id(), url.clone()));
+    urls
+}
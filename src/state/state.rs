@@ -0,0 +1,144 @@
+use crate::export::feed::FeedEntry;
+use crate::post::post::Post;
+use crate::utils::functions::{number_days_since_2020, parse_post_date};
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+/// What is remembered between two runs of the scraper.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    pub last_successful_run: Option<DateTime<Utc>>,
+    /// Latest answers published in the watch mode feed
+    pub feed: Vec<FeedEntry>,
+    /// Keys of the messages already exported, per topic url, see `export.delta`
+    pub exported: BTreeMap<String, BTreeSet<String>>,
+    /// Topics exported so far, per url, for the `export` command to regenerate documents
+    pub topics: BTreeMap<String, StoredTopic>,
+}
+
+/// What the search told about a topic, not found again on the topic page.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredTopic {
+    pub title: String,
+    pub category: String,
+    /// Dates of its first and last message, for the index of the documents
+    #[serde(default)]
+    pub first_message: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub last_message: Option<NaiveDateTime>,
+    /// Last message listed by the search, a topic listed with the same one has nothing new
+    #[serde(default)]
+    pub last_post: Option<String>,
+    #[serde(default)]
+    pub replies: Option<u32>,
+    #[serde(default)]
+    pub views: Option<u32>,
+    /// Its messages as exported, per message key, to catch their later edits and keep them
+    /// once deleted from the forum
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub messages: BTreeMap<String, StoredMessage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StoredMessage {
+    pub author: String,
+    pub date: String,
+    pub text: String,
+    /// No longer on the forum, its tombstone standing for it in the documents
+    #[serde(default)]
+    pub deleted: bool,
+}
+
+impl StoredTopic {
+    pub fn from_post(post: &Post) -> Self {
+        let dates = post
+            .messages
+            .iter()
+            .flatten()
+            .filter_map(|message| parse_post_date(&message.date))
+            .collect::<Vec<_>>();
+
+        Self {
+            title: post.title.clone(),
+            category: post.category.to_string(),
+            first_message: dates.iter().min().copied(),
+            last_message: dates.iter().max().copied(),
+            last_post: post.last_post.clone(),
+            replies: post.replies,
+            views: post.views,
+            messages: post
+                .messages
+                .iter()
+                .flatten()
+                // Not the text of a tombstone, the one exported before the deletion is kept
+                .filter(|message| !message.deleted)
+                .map(|message| {
+                    (
+                        message.key(),
+                        StoredMessage {
+                            author: message.author.clone(),
+                            date: message.date.clone(),
+                            text: message.text(),
+                            deleted: false,
+                        },
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+impl State {
+    /// Whether the topic was exported by a previous run and the search lists the same last
+    /// message as then.
+    pub fn is_unchanged(&self, post: &Post) -> bool {
+        post.last_post.is_some()
+            && self.exported.contains_key(&post.url)
+            && self
+                .topics
+                .get(&post.url)
+                .is_some_and(|topic| topic.last_post == post.last_post)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read state file {}", path.display()))?;
+
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse state file {}", path.display()))
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let json = serde_json::to_string_pretty(self).context("Failed to serialize state")?;
+        let path = path.as_ref();
+        // Written aside then renamed, a crash mid-write leaves the previous state whole
+        let tmp = path.with_extension("json.tmp");
+        let mut file = File::create(&tmp)
+            .with_context(|| format!("Failed to create state file {}", tmp.display()))?;
+        file.write_all(json.as_bytes())
+            .and_then(|()| file.sync_all())
+            .with_context(|| format!("Failed to write state file {}", tmp.display()))?;
+        std::fs::rename(&tmp, path)
+            .with_context(|| format!("Failed to write state file {}", path.display()))
+    }
+
+    /// Number of days the forum search has to look back: everything since the last successful
+    /// run (plus a day of margin for posts made while it was running), or the whole archive.
+    pub fn search_window_days(&self) -> i64 {
+        match self.last_successful_run {
+            Some(last_run) => (Utc::now() - last_run).num_days() + 1,
+            None => number_days_since_2020(),
+        }
+    }
+}
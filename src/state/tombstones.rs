@@ -0,0 +1,69 @@
+use crate::post::post::{Post, PostMessage};
+use crate::state::state::{StoredMessage, StoredTopic};
+use crate::utils::constants::DELETED_MESSAGE_NOTE;
+use crate::utils::functions::{parse_post_date, xml_escape};
+
+/// Messages of `topic` no longer in the fetched `post`, deleted from the forum since they were
+/// exported, those deleted by an earlier run included. A topic without any message is more likely
+/// a page that failed to parse than a topic emptied by the moderators: nothing is deleted then.
+pub fn deleted_messages<'a>(
+    post: &Post,
+    topic: &'a StoredTopic,
+) -> Vec<(&'a String, &'a StoredMessage)> {
+    let fetched = post.messages.iter().flatten().collect::<Vec<_>>();
+    if fetched.is_empty() {
+        return Vec::new();
+    }
+    topic
+        .messages
+        .iter()
+        .filter(|(key, _)| !fetched.iter().any(|message| &message.key() == *key))
+        .collect()
+}
+
+/// Message standing in the documents for the deleted `message`, with its text but not its
+/// formatting, followed by "[message supprimé du forum]" when `note` is set.
+pub fn tombstone(key: &str, message: &StoredMessage, note: bool) -> PostMessage {
+    let mut html = message
+        .text
+        .lines()
+        .map(xml_escape)
+        .collect::<Vec<_>>()
+        .join("<br>");
+    if note {
+        html.push_str(&format!("<br><br><em>{}</em>", DELETED_MESSAGE_NOTE));
+    }
+
+    PostMessage {
+        // The key is the id of the message, or its author and date when it had none
+        id: (key != format!("{} {}", message.author, message.date)).then(|| key.to_owned()),
+        author: message.author.clone(),
+        author_profile: None,
+        date: message.date.clone(),
+        message: format!("<div class=\"py-4 postrow-message\">{}</div>", html),
+        summary: None,
+        translation: None,
+        deleted: true,
+        role: None,
+    }
+}
+
+/// Puts the tombstones of the deleted messages of `topic` back among the messages of `post`,
+/// where they were posted.
+pub fn restore_deleted(post: &mut Post, topic: &StoredTopic, note: bool) {
+    let tombstones = deleted_messages(post, topic)
+        .into_iter()
+        .map(|(key, message)| tombstone(key, message, note))
+        .collect::<Vec<_>>();
+    let Some(messages) = post.messages.as_mut() else {
+        return;
+    };
+    for tombstone in tombstones {
+        let date = parse_post_date(&tombstone.date);
+        let position = messages
+            .iter()
+            .position(|message| date.is_some() && parse_post_date(&message.date) > date)
+            .unwrap_or(messages.len());
+        messages.insert(position, tombstone);
+    }
+}
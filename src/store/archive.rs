@@ -0,0 +1,152 @@
+use crate::export::manifest::sha256_hex;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::OpenOptions;
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+const INDEX_FILE: &str = "pages.jsonl";
+/// Compression level of the stored pages, zstd's highest short of its slow `--ultra` levels
+const ZSTD_LEVEL: i32 = 19;
+
+/// One fetch of a page, appended to the index of the archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedPage {
+    pub url: String,
+    /// SHA-256 of the raw page, naming its object in the archive
+    pub sha256: String,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Raw pages as fetched, compressed with zstd and stored under the hash of their content: a page that did
+/// not change since a previous run is only stored once.
+#[derive(Debug, Clone)]
+pub struct RawArchive {
+    dir: PathBuf,
+    // Hash of the latest version of each url: the index read by `open`, then the pages stored
+    latest: HashMap<String, String>,
+}
+
+impl RawArchive {
+    /// Archive to store pages into, not reading the pages stored by the previous runs.
+    pub fn new<P: AsRef<Path>>(dir: P) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+            latest: HashMap::new(),
+        }
+    }
+
+    /// Archive to replay pages from, its index read once.
+    pub fn open<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let mut archive = Self::new(dir);
+        archive.latest = archive
+            .pages()?
+            .into_iter()
+            .map(|page| (page.url, page.sha256))
+            .collect();
+
+        Ok(archive)
+    }
+
+    fn object_path(&self, sha256: &str) -> PathBuf {
+        self.dir
+            .join("objects")
+            .join(&sha256[..2])
+            .join(format!("{}.html.zst", &sha256[2..]))
+    }
+
+    /// Stores the raw page fetched from `url` and records the fetch, returning its hash.
+    pub fn store(&mut self, url: &str, raw: &[u8]) -> Result<String> {
+        let sha256 = sha256_hex(raw);
+        let path = self.object_path(&sha256);
+        if !path.exists() {
+            let parent = path.parent().expect("objects are in a directory");
+            std::fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+
+            let compressed = zstd::encode_all(raw, ZSTD_LEVEL)
+                .with_context(|| format!("Failed to compress {}", url))?;
+            // Written aside then renamed, an interrupted run never leaves a truncated object
+            let tmp_path = path.with_extension("zst.tmp");
+            std::fs::write(&tmp_path, compressed)
+                .with_context(|| format!("Failed to write {}", tmp_path.display()))?;
+            std::fs::rename(&tmp_path, &path)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        let page = ArchivedPage {
+            url: url.to_owned(),
+            sha256: sha256.clone(),
+            fetched_at: Utc::now(),
+        };
+        let index = self.dir.join(INDEX_FILE);
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(&index)
+            .with_context(|| format!("Failed to open {}", index.display()))?;
+        // After the line a crash cut short, the page goes on a line of its own
+        let mut line = serde_json::to_string(&page)?;
+        if file.metadata()?.len() > 0 {
+            let mut last = [0];
+            file.seek(SeekFrom::End(-1))?;
+            file.read_exact(&mut last)?;
+            if last[0] != b'\n' {
+                line.insert(0, '\n');
+            }
+        }
+        writeln!(file, "{}", line)
+            .with_context(|| format!("Failed to write {}", index.display()))?;
+        self.latest.insert(page.url, sha256.clone());
+
+        Ok(sha256)
+    }
+
+    /// Raw page stored under `sha256`.
+    pub fn load(&self, sha256: &str) -> Result<Vec<u8>> {
+        let path = self.object_path(sha256);
+        let file = std::fs::File::open(&path)
+            .with_context(|| format!("No page {} in the archive", sha256))?;
+        zstd::decode_all(file).with_context(|| format!("Failed to decompress {}", path.display()))
+    }
+
+    /// Latest version of the page fetched from `url`, if it was archived before `open` or since.
+    pub fn latest(&self, url: &str) -> Result<Option<Vec<u8>>> {
+        match self.latest.get(url) {
+            Some(sha256) => self.load(sha256).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Every fetch recorded in the archive, oldest first. A malformed line, such as the last one
+    /// of a run that crashed while appending it, is skipped.
+    pub fn pages(&self) -> Result<Vec<ArchivedPage>> {
+        let index = self.dir.join(INDEX_FILE);
+        if !index.exists() {
+            return Ok(Vec::new());
+        }
+
+        Ok(std::fs::read_to_string(&index)
+            .with_context(|| format!("Failed to read {}", index.display()))?
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .filter_map(|(number, line)| match serde_json::from_str(line) {
+                Ok(page) => Some(page),
+                Err(e) => {
+                    warn!(
+                        "Skipping malformed line {} of {}: {}",
+                        number + 1,
+                        index.display(),
+                        e
+                    );
+                    None
+                }
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,25 @@
+use crate::state::state::State;
+use crate::store::Store;
+use anyhow::Result;
+use std::path::PathBuf;
+
+/// The state as a JSON file, `state.json` in the output directory by default.
+pub struct JsonFile {
+    path: PathBuf,
+}
+
+impl JsonFile {
+    pub fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+impl Store for JsonFile {
+    fn load(&self) -> Result<State> {
+        State::load(&self.path)
+    }
+
+    fn save(&self, state: &State) -> Result<()> {
+        State::save(state, &self.path)
+    }
+}
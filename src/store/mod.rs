@@ -0,0 +1,41 @@
+pub mod archive;
+pub mod files;
+#[cfg(feature = "sqlite")]
+pub mod sqlite;
+
+use crate::config::config::StoreConfig;
+use crate::state::state::State;
+#[cfg(feature = "sqlite")]
+use crate::utils::constants::DATABASE_FILE;
+use crate::utils::constants::STATE_FILE;
+use anyhow::Result;
+use std::path::Path;
+
+/// Where the state of the scraper is kept between runs. The flat JSON file suits a laptop; a
+/// database shared by the runs of a server is another implementation.
+pub trait Store {
+    /// The stored state, the default one when nothing was stored yet.
+    fn load(&self) -> Result<State>;
+    fn save(&self, state: &State) -> Result<()>;
+}
+
+/// The store configured in `[store]`, relative to the output directory by default.
+pub fn open_store(config: &StoreConfig, output_dir: &Path) -> Result<Box<dyn Store>> {
+    match config {
+        StoreConfig::Files { path } => Ok(Box::new(files::JsonFile::new(
+            path.clone().unwrap_or_else(|| output_dir.join(STATE_FILE)),
+        ))),
+        #[cfg(feature = "sqlite")]
+        StoreConfig::Sqlite { database } => Ok(Box::new(sqlite::Database::open(
+            database
+                .clone()
+                .unwrap_or_else(|| output_dir.join(DATABASE_FILE)),
+        )?)),
+        #[cfg(not(feature = "sqlite"))]
+        StoreConfig::Sqlite { .. } => {
+            anyhow::bail!(
+                "SQLite store configured but the scraper was built without the `sqlite` feature"
+            )
+        }
+    }
+}
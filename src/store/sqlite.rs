@@ -0,0 +1,239 @@
+use crate::http::profiles::Profile;
+use crate::state::state::State;
+use crate::store::Store;
+use anyhow::{Context, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::de::DeserializeOwned;
+use std::path::Path;
+
+/// SQLite database kept next to the generated documents.
+pub struct Database {
+    conn: Connection,
+}
+
+/// A stored embedding with the topic it belongs to.
+#[derive(Debug, Clone)]
+pub struct StoredEmbedding {
+    pub topic_url: String,
+    pub title: String,
+    pub category: String,
+    pub exchange: usize,
+    pub vector: Vec<f32>,
+}
+
+impl Database {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let conn = Connection::open(path)
+            .with_context(|| format!("Failed to open database {}", path.display()))?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS embeddings (
+                topic_url TEXT NOT NULL,
+                exchange INTEGER NOT NULL,
+                title TEXT NOT NULL,
+                category TEXT NOT NULL,
+                model TEXT NOT NULL,
+                vector BLOB NOT NULL,
+                PRIMARY KEY (topic_url, exchange)
+            );",
+        )
+        .context("Failed to create the embeddings table")?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS profiles (
+                url TEXT PRIMARY KEY,
+                author TEXT NOT NULL,
+                registered TEXT,
+                posts INTEGER,
+                fetched_at TEXT NOT NULL
+            );",
+        )
+        .context("Failed to create the profiles table")?;
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS state_values (
+                key TEXT PRIMARY KEY,
+                value TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS state_topics (
+                url TEXT PRIMARY KEY,
+                topic TEXT NOT NULL
+            );
+            CREATE TABLE IF NOT EXISTS state_exported (
+                topic_url TEXT NOT NULL,
+                message_key TEXT NOT NULL,
+                PRIMARY KEY (topic_url, message_key)
+            );",
+        )
+        .context("Failed to create the state tables")?;
+
+        Ok(Self { conn })
+    }
+
+    /// Replaces the vector of an exchange, topics being re-exported when they get new answers.
+    pub fn upsert_embedding(&self, embedding: &StoredEmbedding, model: &str) -> Result<()> {
+        let blob = embedding
+            .vector
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<_>>();
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO embeddings
+                    (topic_url, exchange, title, category, model, vector)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    embedding.topic_url,
+                    embedding.exchange as i64,
+                    embedding.title,
+                    embedding.category,
+                    model,
+                    blob
+                ],
+            )
+            .with_context(|| format!("Failed to store embedding of {}", embedding.topic_url))?;
+
+        Ok(())
+    }
+
+    /// Whether the profile at `url` was already fetched by a previous run.
+    pub fn has_profile(&self, url: &str) -> Result<bool> {
+        self.conn
+            .query_row(
+                "SELECT EXISTS(SELECT 1 FROM profiles WHERE url = ?1)",
+                params![url],
+                |row| row.get(0),
+            )
+            .with_context(|| format!("Failed to look up profile {}", url))
+    }
+
+    pub fn upsert_profile(&self, profile: &Profile) -> Result<()> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO profiles (url, author, registered, posts, fetched_at)
+                VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    profile.url,
+                    profile.author,
+                    profile.registered,
+                    profile.posts.map(|posts| posts as i64),
+                    chrono::Utc::now().to_rfc3339()
+                ],
+            )
+            .with_context(|| format!("Failed to store profile {}", profile.url))?;
+
+        Ok(())
+    }
+
+    /// JSON value stored under `key` in `state_values`.
+    fn state_value<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        let value: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT value FROM state_values WHERE key = ?1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .with_context(|| format!("Failed to read {} from the state", key))?;
+        value
+            .map(|value| serde_json::from_str(&value))
+            .transpose()
+            .with_context(|| format!("Failed to parse {} from the state", key))
+    }
+
+    /// Every embedding computed with `model`, vectors of other models not being comparable.
+    pub fn embeddings(&self, model: &str) -> Result<Vec<StoredEmbedding>> {
+        let mut statement = self.conn.prepare(
+            "SELECT topic_url, exchange, title, category, vector FROM embeddings WHERE model = ?1",
+        )?;
+        let rows = statement.query_map(params![model], |row| {
+            let blob: Vec<u8> = row.get(4)?;
+            Ok(StoredEmbedding {
+                topic_url: row.get(0)?,
+                exchange: row.get::<_, i64>(1)? as usize,
+                title: row.get(2)?,
+                category: row.get(3)?,
+                vector: blob
+                    .chunks_exact(4)
+                    .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+                    .collect(),
+            })
+        })?;
+
+        rows.collect::<rusqlite::Result<Vec<_>>>()
+            .context("Failed to read embeddings")
+    }
+}
+
+impl Store for Database {
+    fn load(&self) -> Result<State> {
+        let mut state = State {
+            last_successful_run: self.state_value("last_successful_run")?.flatten(),
+            feed: self.state_value("feed")?.unwrap_or_default(),
+            ..Default::default()
+        };
+
+        let mut statement = self.conn.prepare("SELECT url, topic FROM state_topics")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get::<_, String>(1)?)))?;
+        for row in rows {
+            let (url, topic): (String, String) = row.context("Failed to read the topics")?;
+            let topic = serde_json::from_str(&topic)
+                .with_context(|| format!("Failed to parse the stored topic {}", url))?;
+            state.topics.insert(url, topic);
+        }
+
+        let mut statement = self
+            .conn
+            .prepare("SELECT topic_url, message_key FROM state_exported")?;
+        let rows = statement.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        for row in rows {
+            let (url, key): (String, String) =
+                row.context("Failed to read the exported messages")?;
+            state.exported.entry(url).or_default().insert(key);
+        }
+
+        Ok(state)
+    }
+
+    /// Replaces the whole state in a single transaction.
+    fn save(&self, state: &State) -> Result<()> {
+        let transaction = self.conn.unchecked_transaction()?;
+        transaction
+            .execute_batch(
+                "DELETE FROM state_values; DELETE FROM state_topics; DELETE FROM state_exported;",
+            )
+            .context("Failed to clear the state")?;
+        for (key, value) in [
+            (
+                "last_successful_run",
+                serde_json::to_string(&state.last_successful_run)?,
+            ),
+            ("feed", serde_json::to_string(&state.feed)?),
+        ] {
+            transaction
+                .execute(
+                    "INSERT INTO state_values (key, value) VALUES (?1, ?2)",
+                    params![key, value],
+                )
+                .with_context(|| format!("Failed to store {} in the state", key))?;
+        }
+        for (url, topic) in &state.topics {
+            transaction
+                .execute(
+                    "INSERT INTO state_topics (url, topic) VALUES (?1, ?2)",
+                    params![url, serde_json::to_string(topic)?],
+                )
+                .with_context(|| format!("Failed to store the topic {}", url))?;
+        }
+        for (url, keys) in &state.exported {
+            for key in keys {
+                transaction
+                    .execute(
+                        "INSERT INTO state_exported (topic_url, message_key) VALUES (?1, ?2)",
+                        params![url, key],
+                    )
+                    .with_context(|| format!("Failed to store the exported messages of {}", url))?;
+            }
+        }
+        transaction.commit().context("Failed to save the state")
+    }
+}
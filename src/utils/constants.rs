@@ -1,3 +1,32 @@
 pub const MAX_PAGES: u32 = 1;
 pub const PAGE_SIZE: u32 = 50;
+/// Largest page read, see `scrape.max_page_bytes`
+pub const MAX_PAGE_BYTES: u64 = 16 * 1024 * 1024;
+/// Most messages and pages of a topic, see `scrape.max_topic_messages`
+pub const MAX_TOPIC_MESSAGES: usize = 20_000;
+pub const MAX_TOPIC_PAGES: usize = 1_000;
 pub const BASE_URL: &str = "https://www.techouvot.com/";
+pub const OUTPUT_DIR: &str = "files_generated";
+pub const MANIFEST_FILE: &str = "manifest.json";
+/// List of the category documents, regenerated at the end of every run
+pub const INDEX_MD_FILE: &str = "index.md";
+pub const INDEX_DOCX_FILE: &str = "index.docx";
+pub const REPORT_FILE: &str = "report.json";
+pub const CRASH_FILE: &str = "crash.json";
+pub const VOLUMES_FILE: &str = "volumes.csv";
+pub const CONFIG_FILE: &str = "scraper.toml";
+pub const STATE_FILE: &str = "state.json";
+pub const COOKIES_FILE: &str = "cookies.json";
+pub const NOTICE_FILE: &str = "NOTICE.txt";
+pub const FEED_FILE: &str = "feed.atom";
+/// Where the `export` command rebuilds a document before replacing the previous one
+pub const EXPORT_STAGING_DIR: &str = ".export";
+pub const CALENDAR_FILE: &str = "answers.ics";
+pub const MESSAGES_CSV_FILE: &str = "messages.csv";
+pub const ANKI_FILE: &str = "anki.tsv";
+pub const TRANSLATION_CACHE_FILE: &str = "translations.json";
+pub const DATABASE_FILE: &str = "ribav.sqlite";
+/// Ends the messages deleted from the forum in the documents, see `export.deleted_note`
+pub const DELETED_MESSAGE_NOTE: &str = "[message supprimé du forum]";
+/// Average silent reading speed used for the reading time estimates
+pub const READING_WORDS_PER_MINUTE: usize = 200;
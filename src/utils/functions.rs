@@ -1,8 +1,10 @@
-use chrono::{TimeZone, Utc};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeZone, Utc};
 use docx_rust::{
     document::Run,
     formatting::{CharacterProperty, CharacterStyleId},
 };
+#[cfg(any(feature = "drive", feature = "email"))]
+use std::path::Path;
 
 pub fn number_days_since_2020() -> i64 {
     let today = Utc::now();
@@ -10,6 +12,57 @@ pub fn number_days_since_2020() -> i64 {
     (today - start_of_2020).num_days()
 }
 
+/// Parses the date of a message, e.g. "Posté le: 12/03/2023 10:15".
+pub fn parse_post_date(date: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(date.replace("Posté le:", "").trim(), "%d/%m/%Y %H:%M").ok()
+}
+
+const FRENCH_MONTHS: [&str; 12] = [
+    "janvier",
+    "février",
+    "mars",
+    "avril",
+    "mai",
+    "juin",
+    "juillet",
+    "août",
+    "septembre",
+    "octobre",
+    "novembre",
+    "décembre",
+];
+
+/// A date written out in French, e.g. "3 mars 2021" or "1er mai 2023".
+pub fn french_long_date(date: NaiveDate) -> String {
+    let day = match date.day() {
+        1 => "1er".to_owned(),
+        day => day.to_string(),
+    };
+    format!(
+        "{day} {} {}",
+        FRENCH_MONTHS[date.month0() as usize],
+        date.year()
+    )
+}
+
+pub fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Title as compared with the search: letters and digits, lowercased, so that the capitalized
+/// and typeset titles of the documents match the ones of the forum.
+pub fn title_key(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
 pub fn anonymize_author<S: AsRef<str>>(author: S) -> String {
     if author.as_ref().to_lowercase().starts_with("rav ") {
         return author.as_ref().to_string();
@@ -31,6 +84,66 @@ pub fn is_citation(run: &Run) -> bool {
         .as_ref()
         .unwrap_or(&CharacterStyleId::from(""))
         .value
-        .to_string()
         == "citation"
 }
+
+/// Names Windows refuses for a file, whatever its extension.
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+/// Longest file stem kept, leaving room for the output directory under the 260 characters of a
+/// Windows path.
+pub const MAX_FILE_STEM_CHARS: usize = 100;
+
+/// `name` made usable as a file stem on every platform: characters forbidden by Windows are
+/// replaced, reserved device names prefixed, trailing dots and spaces dropped and the length
+/// capped.
+pub fn safe_file_stem(name: &str) -> String {
+    let mut stem = name
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '/' | '\\' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .take(MAX_FILE_STEM_CHARS)
+        .collect::<String>();
+
+    let trimmed = stem.trim_end_matches(['.', ' ']).len();
+    stem.truncate(trimmed);
+    if stem.is_empty() {
+        return "_".to_owned();
+    }
+
+    let device = stem.split('.').next().unwrap_or_default().trim_end();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| reserved.eq_ignore_ascii_case(device))
+    {
+        stem.insert(0, '_');
+    }
+
+    stem
+}
+
+/// Text with replacement characters or UTF-8 read as Windows-1252 ("Ã©" for "é").
+pub fn looks_mis_encoded(text: &str) -> bool {
+    text.contains('\u{FFFD}')
+        || text.chars().zip(text.chars().skip(1)).any(|(c, next)| {
+            c == 'Ã' && matches!(next, '\u{80}'..='\u{BF}' | '\u{2013}'..='\u{2122}')
+        })
+}
+
+#[cfg(any(feature = "drive", feature = "email"))]
+pub fn mime_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("docx") => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        Some("ics") => "text/calendar",
+        Some("json") => "application/json",
+        Some("odt") => "application/vnd.oasis.opendocument.text",
+        Some("zip") => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
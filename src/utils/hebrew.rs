@@ -0,0 +1,137 @@
+use chrono::{Datelike, NaiveDate};
+use std::fmt;
+
+/// Days before 1 Tichri of the year 1, counted from 1 January of the year 1 of the Gregorian
+/// calendar (Reingold & Dershowitz, Calendrical Calculations).
+const EPOCH: i64 = -1_373_429;
+
+/// A date of the Hebrew calendar. The months are numbered from Nissan (1), Tichri being the 7th
+/// and Adar II the 13th of the leap years.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HebrewDate {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+pub const NISSAN: u32 = 1;
+pub const SIVAN: u32 = 3;
+pub const TICHRI: u32 = 7;
+pub const KISLEV: u32 = 9;
+pub const ADAR: u32 = 12;
+pub const ADAR_II: u32 = 13;
+
+pub fn is_leap_year(year: i64) -> bool {
+    (7 * year + 1).rem_euclid(19) < 7
+}
+
+pub fn last_month(year: i64) -> u32 {
+    if is_leap_year(year) {
+        ADAR_II
+    } else {
+        ADAR
+    }
+}
+
+/// Days from the epoch to 1 Tichri of `year`, with the postponements of Rosh Hachana.
+fn elapsed_days(year: i64) -> i64 {
+    let months =
+        235 * ((year - 1) / 19) + 12 * ((year - 1) % 19) + (7 * ((year - 1) % 19) + 1) / 19;
+    let parts_elapsed = 204 + 793 * (months % 1080);
+    let hours_elapsed = 5 + 12 * months + 793 * (months / 1080) + parts_elapsed / 1080;
+    let day = 1 + 29 * months + hours_elapsed / 24;
+    let parts = 1080 * (hours_elapsed % 24) + parts_elapsed % 1080;
+    let day = if parts >= 19440
+        || (day % 7 == 2 && parts >= 9924 && !is_leap_year(year))
+        || (day % 7 == 1 && parts >= 16789 && is_leap_year(year - 1))
+    {
+        day + 1
+    } else {
+        day
+    };
+    if matches!(day % 7, 0 | 3 | 5) {
+        day + 1
+    } else {
+        day
+    }
+}
+
+fn days_in_year(year: i64) -> i64 {
+    elapsed_days(year + 1) - elapsed_days(year)
+}
+
+pub fn days_in_month(month: u32, year: i64) -> u32 {
+    let days = days_in_year(year);
+    match month {
+        2 | 4 | 6 | 10 | ADAR_II => 29,
+        ADAR if !is_leap_year(year) => 29,
+        // Hechvan has 30 days in the complete years, Kislev 29 in the deficient ones
+        8 if days % 10 != 5 => 29,
+        KISLEV if days % 10 == 3 => 29,
+        _ => 30,
+    }
+}
+
+impl HebrewDate {
+    /// Days since the start of the Gregorian calendar, as `NaiveDate::num_days_from_ce`.
+    fn fixed(self) -> i64 {
+        let mut days = self.day as i64;
+        let month_days = |months: &mut dyn Iterator<Item = u32>| -> i64 {
+            months
+                .map(|month| days_in_month(month, self.year) as i64)
+                .sum()
+        };
+        if self.month < TICHRI {
+            days += month_days(&mut (TICHRI..=last_month(self.year)));
+            days += month_days(&mut (NISSAN..self.month));
+        } else {
+            days += month_days(&mut (TICHRI..self.month));
+        }
+        days + elapsed_days(self.year) + EPOCH
+    }
+
+    pub fn from_gregorian(date: NaiveDate) -> Self {
+        let fixed = date.num_days_from_ce() as i64;
+        let mut year = (fixed - EPOCH) / 366;
+        while fixed >= HebrewDate::new(year + 1, TICHRI, 1).fixed() {
+            year += 1;
+        }
+        let mut month = if fixed < HebrewDate::new(year, NISSAN, 1).fixed() {
+            TICHRI
+        } else {
+            NISSAN
+        };
+        while fixed > HebrewDate::new(year, month, days_in_month(month, year)).fixed() {
+            month += 1;
+        }
+        let day = fixed - HebrewDate::new(year, month, 1).fixed() + 1;
+        HebrewDate::new(year, month, day as u32)
+    }
+
+    pub fn to_gregorian(self) -> Option<NaiveDate> {
+        NaiveDate::from_num_days_from_ce_opt(i32::try_from(self.fixed()).ok()?)
+    }
+
+    pub fn new(year: i64, month: u32, day: u32) -> Self {
+        Self { year, month, day }
+    }
+
+    pub fn month_name(&self) -> &'static str {
+        const MONTHS: [&str; 12] = [
+            "Nissan", "Iyar", "Sivan", "Tamouz", "Av", "Eloul", "Tichri", "Hechvan", "Kislev",
+            "Tevet", "Chevat", "Adar",
+        ];
+        match self.month {
+            ADAR if is_leap_year(self.year) => "Adar I",
+            ADAR_II => "Adar II",
+            month => MONTHS[month as usize - 1],
+        }
+    }
+}
+
+impl fmt::Display for HebrewDate {
+    /// E.g. "19 Adar 5783"
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.day, self.month_name(), self.year)
+    }
+}
@@ -1,17 +1,13 @@
-// extract!(post, &author_sel, text) -> post.select(&author_sel).next().unwrap().text().collect::<String>().trim().to_string()
+// extract!(post, &author_sel) -> post.select(&author_sel).next().map(|el| el.text().collect::<String>().trim().to_string())
 #[macro_export]
 macro_rules! extract {
     ($post:ident, $sel:expr) => {
         $post
             .select($sel)
             .next()
-            .unwrap()
-            .text()
-            .collect::<String>()
-            .trim()
-            .to_string()
+            .map(|el| el.text().collect::<String>().trim().to_string())
     };
     ($post:ident, $sel:expr, $want:ident) => {
-        $post.select($sel).next().unwrap().$want()
+        $post.select($sel).next().map(|el| el.$want())
     };
 }
@@ -1,3 +1,5 @@
 pub mod constants;
 pub mod functions;
+pub mod hebrew;
 pub mod macros;
+pub mod timezone;
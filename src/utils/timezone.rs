@@ -0,0 +1,139 @@
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, TimeZone, Utc,
+    Weekday,
+};
+use serde::Deserialize;
+use std::str::FromStr;
+
+const HOUR: i32 = 3600;
+
+/// Time zone of the dates of the forum or of the exports, e.g. `"Europe/Paris"`, `"UTC"`,
+/// `"local"` or `"+02:00"`.
+///
+/// Without a time zone database, only the zones of the forum and of its readers follow their
+/// summer time rules; any other one has to be given as a fixed offset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Zone {
+    Utc,
+    /// Of the machine running the scraper
+    Local,
+    Fixed(FixedOffset),
+    /// Central European Time, with the summer time of the European Union
+    CentralEurope,
+    /// Israel Standard Time, with the Israeli summer time
+    Israel,
+}
+
+impl FromStr for Zone {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim() {
+            "UTC" | "utc" | "Z" | "Etc/UTC" => Ok(Zone::Utc),
+            "local" => Ok(Zone::Local),
+            "CET" | "Europe/Paris" | "Europe/Brussels" | "Europe/Luxembourg" | "Europe/Monaco"
+            | "Europe/Amsterdam" | "Europe/Berlin" | "Europe/Zurich" | "Europe/Rome"
+            | "Europe/Madrid" | "Europe/Vienna" => Ok(Zone::CentralEurope),
+            "Israel" | "Asia/Jerusalem" | "Asia/Tel_Aviv" => Ok(Zone::Israel),
+            offset => {
+                // FixedOffset only parses "+HH:MM"
+                DateTime::parse_from_str(
+                    &format!("2020-01-01 00:00 {}", offset),
+                    "%Y-%m-%d %H:%M %:z",
+                )
+                .map(|date| Zone::Fixed(*date.offset()))
+                .map_err(|_| {
+                    format!(
+                        "Unknown time zone {:?}, expected UTC, local, an offset like +02:00, \
+                             Europe/Paris or Asia/Jerusalem",
+                        name
+                    )
+                })
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Zone {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+/// Last `weekday` of the `month` of `year`.
+fn last_weekday(year: i32, month: u32, weekday: Weekday) -> NaiveDate {
+    let next_month = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    }
+    .expect("valid month");
+    let mut day = next_month - Duration::days(1);
+    while day.weekday() != weekday {
+        day -= Duration::days(1);
+    }
+    day
+}
+
+impl Zone {
+    /// Offset from UTC at the instant `utc`.
+    pub fn offset_at(&self, utc: NaiveDateTime) -> FixedOffset {
+        let hours = |hours: i32| FixedOffset::east_opt(hours * HOUR).expect("valid offset");
+        let year = utc.year();
+        match self {
+            Zone::Utc => hours(0),
+            Zone::Local => *Local.from_utc_datetime(&utc).offset(),
+            Zone::Fixed(offset) => *offset,
+            // From 01:00 UTC on the last Sunday of March to 01:00 UTC on the last Sunday of October
+            Zone::CentralEurope => {
+                let start = last_weekday(year, 3, Weekday::Sun).and_hms_opt(1, 0, 0);
+                let end = last_weekday(year, 10, Weekday::Sun).and_hms_opt(1, 0, 0);
+                let summer =
+                    start.is_some_and(|start| utc >= start) && end.is_some_and(|end| utc < end);
+                hours(if summer { 2 } else { 1 })
+            }
+            // From 02:00 on the Friday before the last Sunday of March to 02:00 on the last
+            // Sunday of October, local times
+            Zone::Israel => {
+                let start =
+                    (last_weekday(year, 3, Weekday::Sun) - Duration::days(2)).and_hms_opt(0, 0, 0);
+                let end = (last_weekday(year, 10, Weekday::Sun) - Duration::days(1))
+                    .and_hms_opt(23, 0, 0);
+                let summer =
+                    start.is_some_and(|start| utc >= start) && end.is_some_and(|end| utc < end);
+                hours(if summer { 3 } else { 2 })
+            }
+        }
+    }
+
+    /// Instant of the wall-clock time `local` in this zone. A time skipped or repeated by a
+    /// change of time is taken in the offset before the change.
+    pub fn to_utc(&self, local: NaiveDateTime) -> DateTime<Utc> {
+        if let Zone::Local = self {
+            if let Some(date) = Local.from_local_datetime(&local).earliest() {
+                return date.with_timezone(&Utc);
+            }
+        }
+
+        let in_offset =
+            |offset: FixedOffset| local - Duration::seconds(offset.local_minus_utc() as i64);
+        // The changes of time are at night, a day earlier is always before them
+        let before = self.offset_at(local - Duration::days(1));
+        let utc = in_offset(before);
+        let after = self.offset_at(utc);
+        if after != before && self.offset_at(in_offset(after)) == after {
+            in_offset(after).and_utc()
+        } else {
+            utc.and_utc()
+        }
+    }
+
+    /// Wall-clock time of the instant `utc` in this zone.
+    pub fn from_utc(&self, utc: DateTime<Utc>) -> NaiveDateTime {
+        let offset = self.offset_at(utc.naive_utc());
+        utc.naive_utc() + Duration::seconds(offset.local_minus_utc() as i64)
+    }
+}
@@ -0,0 +1,35 @@
+mod common;
+
+use common::docx::DocxSnapshot;
+use common::message_by;
+use scrapper::config::config::ExportConfig;
+use scrapper::post::post::Post;
+use scrapper::report::report::Report;
+
+#[test]
+fn aligned_blocks_are_aligned_paragraphs() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Halakha.docx");
+    let mut post = Post {
+        url: "https://forum.test/viewtopic.php?t=1".to_string(),
+        title: "Bénédictions".to_string(),
+        messages: Some(vec![message_by(
+            "Rav Binyamin Wattenberg",
+            "Avant le repas :<center>Baroukh Ata</center>Puis on mange.\
+             <div style=\"text-align: center\">Bon appétit</div>\
+             <p style=\"text-align: right\">Rav B. W.</p>",
+        )]),
+        category: "Halakha".into(),
+        ..Default::default()
+    };
+    post.messages_to_word(&path, &ExportConfig::default(), &mut Report::default())
+        .unwrap();
+
+    let docx = DocxSnapshot::open(&path);
+    assert!(!docx.paragraph("Avant le repas :").centered);
+    assert!(docx.paragraph("Baroukh Ata").centered);
+    assert!(!docx.paragraph("Puis on mange.").centered);
+    assert!(docx.paragraph("Bon appétit").centered);
+    assert!(!docx.paragraph("Rav B. W.").centered);
+    assert!(docx.position("Avant le repas :") < docx.position("Baroukh Ata"));
+}
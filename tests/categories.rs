@@ -0,0 +1,53 @@
+mod common;
+
+use common::{fixture, Harness};
+use scraper::Html;
+use scrapper::http::forums::get_forums_from_index;
+use wiremock::matchers::path;
+use wiremock::{Mock, ResponseTemplate};
+
+#[test]
+fn lists_the_forums_of_the_index() {
+    let index = Html::parse_document(&fixture("index.html"));
+    let forums = get_forums_from_index(&index, "https://www.techouvot.com/").unwrap();
+
+    let listed: Vec<_> = forums
+        .iter()
+        .map(|forum| {
+            (
+                forum.id,
+                forum.section.as_str(),
+                forum.name.as_str(),
+                forum.topics,
+                forum.posts,
+            )
+        })
+        .collect();
+    assert_eq!(
+        listed,
+        [
+            (3, "Questions au Rav", "Halakha", 1204, 5873),
+            (5, "Questions au Rav", "Hachkafa", 412, 1630),
+            (9, "Divers", "Annonces", 0, 0),
+        ]
+    );
+    assert_eq!(forums[1].url, "https://www.techouvot.com/forum-f5.html");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn categories_command_prints_the_forums_as_tsv() {
+    let harness = Harness::start().await;
+    Mock::given(path("/index.php"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("index.html")))
+        .mount(&harness.server)
+        .await;
+
+    let output = harness.run(&["categories"]);
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(lines[0], "id\tsection\tname\ttopics\tposts\turl");
+    assert!(lines[1].starts_with("3\tQuestions au Rav\tHalakha\t1204\t5873\t"));
+    assert_eq!(lines.len(), 4);
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::{fixture, Harness};
+use scrapper::post::category::{Category, CategoryLevel};
+
+#[test]
+fn parses_the_parent_forum_of_a_category() {
+    let category = Category::parse(" Halakha  »  Chabbat ");
+    assert_eq!(category.forum, "Halakha");
+    assert_eq!(category.subforum.as_deref(), Some("Chabbat"));
+    assert_eq!(category.to_string(), "Halakha » Chabbat");
+    assert!(category.matches("chabbat"));
+    assert!(category.matches("Halakha"));
+    assert!(category.matches("Halakha » Chabbat"));
+    assert!(!category.matches("Hachkafa"));
+
+    assert_eq!(
+        Category::parse("Halakha > Chabbat").grouped(CategoryLevel::Forum),
+        Category::parse("Halakha")
+    );
+    assert_eq!(Category::parse("Hachkafa").subforum, None);
+}
+
+/// The search results with the Halakha topic in a Chabbat subforum.
+async fn harness_with_subforum() -> Harness {
+    let results =
+        fixture("search_results.html").replacen(">Halakha</a>", ">Halakha &raquo; Chabbat</a>", 1);
+    Harness::with_page("/search.php", ("search_id", "4242"), results).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn documents_are_made_per_subforum_or_per_forum() {
+    let harness = harness_with_subforum().await;
+    assert!(harness.run(&["--format", "csv"]).status.success());
+    let csv = std::fs::read_to_string(harness.output_dir().join("messages.csv")).unwrap();
+    assert!(csv.contains(",Halakha » Chabbat,12/03/2023 10:15,"));
+
+    let harness = harness_with_subforum().await;
+    assert!(harness.run(&[]).status.success());
+    let subforum = std::fs::read_dir(harness.output_dir())
+        .unwrap()
+        .filter_map(|entry| entry.unwrap().file_name().into_string().ok())
+        .filter(|name| name.contains("Chabbat") && name.ends_with(".docx"))
+        .count();
+    assert_eq!(subforum, 1);
+
+    let harness = harness_with_subforum().await;
+    let output = harness.run_with_config("[export]\ngroup_by = \"forum\"\n", &[]);
+    assert!(output.status.success());
+    assert!(harness.output_dir().join("Halakha.docx").exists());
+}
@@ -0,0 +1,38 @@
+use scrapper::config::config::CharactersConfig;
+use scrapper::parser::characters::{take_unmapped, CharacterMap};
+use std::collections::BTreeMap;
+
+#[test]
+fn maps_the_configured_characters_and_counts_the_others() {
+    let config = CharactersConfig {
+        map: BTreeMap::from([
+            ("\u{E001}".to_owned(), ":)".to_owned()),
+            ("\u{0301}".to_owned(), String::new()),
+        ]),
+    };
+    let map = CharacterMap::from_config(&config).unwrap();
+
+    assert_eq!(map.apply("Merci Rav \u{E001}"), "Merci Rav :)");
+    assert_eq!(map.apply("Te\u{0301}chouva"), "Techouva");
+    // Word renders a combining mark following a letter
+    assert_eq!(map.apply("Cafe\u{0300}"), "Cafe\u{0300}");
+    assert_eq!(
+        map.apply("\u{E002} \u{E002} \u{0308}x"),
+        "\u{E002} \u{E002} \u{0308}x"
+    );
+
+    assert_eq!(
+        take_unmapped(),
+        BTreeMap::from([('\u{0308}', 1), ('\u{E002}', 2)])
+    );
+    assert!(take_unmapped().is_empty());
+}
+
+#[test]
+fn rejects_keys_of_several_characters() {
+    let config = CharactersConfig {
+        map: BTreeMap::from([(":-)".to_owned(), "🙂".to_owned())]),
+    };
+
+    assert!(CharacterMap::from_config(&config).is_err());
+}
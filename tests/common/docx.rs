@@ -0,0 +1,140 @@
+use docx_rust::document::{BodyContent, ParagraphContent};
+use docx_rust::formatting::JustificationVal;
+use docx_rust::DocxFile;
+use std::path::Path;
+
+/// Owned view of a generated document, flattened to what the exports care about.
+#[derive(Debug, Clone, Default)]
+pub struct DocxSnapshot {
+    pub paragraphs: Vec<ParagraphSnapshot>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ParagraphSnapshot {
+    pub runs: Vec<RunSnapshot>,
+    pub centered: bool,
+    pub indent_left: Option<isize>,
+    pub outline_level: Option<isize>,
+    pub spacing_after: Option<isize>,
+    pub keep_next: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RunSnapshot {
+    pub text: String,
+    pub bold: bool,
+    pub italics: bool,
+    pub underline: bool,
+    pub size: Option<isize>,
+    pub strike: bool,
+    /// `Superscript` or `Subscript`
+    pub vertical_align: Option<String>,
+    pub style: Option<String>,
+}
+
+impl DocxSnapshot {
+    pub fn open(path: &Path) -> Self {
+        let file = DocxFile::from_file(path)
+            .unwrap_or_else(|e| panic!("Failed to open {}: {:?}", path.display(), e));
+        let docx = file
+            .parse()
+            .unwrap_or_else(|e| panic!("Failed to parse {}: {:?}", path.display(), e));
+
+        let paragraphs = docx
+            .document
+            .body
+            .content
+            .iter()
+            .filter_map(|content| match content {
+                BodyContent::Paragraph(paragraph) => Some(paragraph),
+                _ => None,
+            })
+            .map(|paragraph| {
+                let property = paragraph.property.as_ref();
+                ParagraphSnapshot {
+                    centered: property
+                        .and_then(|p| p.justification.as_ref())
+                        .is_some_and(|j| matches!(j.value, JustificationVal::Center)),
+                    indent_left: property
+                        .and_then(|p| p.indent.as_ref())
+                        .and_then(|indent| indent.left),
+                    outline_level: property
+                        .and_then(|p| p.outline_lvl.as_ref())
+                        .map(|level| level.value),
+                    spacing_after: property
+                        .and_then(|p| p.spacing.as_ref())
+                        .and_then(|spacing| spacing.after),
+                    keep_next: property
+                        .and_then(|p| p.keep_next.as_ref())
+                        .is_some_and(|keep| keep.value != Some(false)),
+                    runs: paragraph
+                        .content
+                        .iter()
+                        .filter_map(|content| match content {
+                            ParagraphContent::Run(run) => Some(run),
+                            _ => None,
+                        })
+                        .map(|run| {
+                            let cp = run.property.clone().unwrap_or_default();
+                            RunSnapshot {
+                                text: run.iter_text().map(|t| t.to_string()).collect(),
+                                bold: cp.bold.is_some_and(|b| b.value != Some(false)),
+                                italics: cp.italics.is_some_and(|i| i.value != Some(false)),
+                                underline: cp.underline.is_some(),
+                                size: cp.size.map(|s| s.value),
+                                strike: cp.strike.is_some_and(|s| s.value != Some(false)),
+                                vertical_align: cp
+                                    .vertical_align
+                                    .and_then(|v| v.value)
+                                    .map(|v| format!("{:?}", v)),
+                                style: cp.style_id.map(|s| s.value.to_string()),
+                            }
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        Self { paragraphs }
+    }
+
+    /// Texts of the non-empty paragraphs, in document order.
+    pub fn texts(&self) -> Vec<String> {
+        self.paragraphs
+            .iter()
+            .map(|p| p.text())
+            .filter(|text| !text.trim().is_empty())
+            .collect()
+    }
+
+    /// First paragraph containing `needle`, panicking with the document texts otherwise.
+    pub fn paragraph(&self, needle: &str) -> &ParagraphSnapshot {
+        self.paragraphs
+            .iter()
+            .find(|p| p.text().contains(needle))
+            .unwrap_or_else(|| panic!("No paragraph contains {:?} in {:#?}", needle, self.texts()))
+    }
+
+    /// First run whose text is `needle`, ignoring surrounding whitespace.
+    pub fn run(&self, needle: &str) -> &RunSnapshot {
+        self.paragraphs
+            .iter()
+            .flat_map(|p| &p.runs)
+            .find(|run| run.text.trim() == needle)
+            .unwrap_or_else(|| panic!("No run contains {:?} in {:#?}", needle, self.texts()))
+    }
+
+    /// Position of the first paragraph containing `needle`, to assert on ordering.
+    pub fn position(&self, needle: &str) -> usize {
+        self.paragraphs
+            .iter()
+            .position(|p| p.text().contains(needle))
+            .unwrap_or_else(|| panic!("No paragraph contains {:?} in {:#?}", needle, self.texts()))
+    }
+}
+
+impl ParagraphSnapshot {
+    pub fn text(&self) -> String {
+        self.runs.iter().map(|run| run.text.as_str()).collect()
+    }
+}
@@ -0,0 +1,167 @@
+#![allow(dead_code)]
+
+pub mod docx;
+
+use scrapper::post::post::PostMessage;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+use tempfile::TempDir;
+use wiremock::matchers::{method, path, query_param};
+use wiremock::{Mock, MockServer, ResponseTemplate};
+
+/// A local copy of the forum serving the pages recorded in `tests/fixtures`, plus a scratch
+/// directory the scraper binary runs in.
+pub struct Harness {
+    pub server: MockServer,
+    pub dir: TempDir,
+}
+
+impl Harness {
+    pub async fn start() -> Self {
+        let server = MockServer::start().await;
+
+        Mock::given(method("POST"))
+            .and(path("/search.php"))
+            .and(query_param("mode", "results"))
+            .respond_with(html("search_form.html"))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/search.php"))
+            .and(query_param("search_id", "4242"))
+            .respond_with(html("search_results.html"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/viewtopic.php"))
+            .and(query_param("t", "101"))
+            .respond_with(html("topic_101.html"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/suivante-t101-2.html"))
+            .respond_with(html("topic_101_page2.html"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/viewtopic.php"))
+            .and(query_param("t", "102"))
+            .respond_with(html("topic_102.html"))
+            .mount(&server)
+            .await;
+
+        Self {
+            server,
+            dir: tempfile::tempdir().expect("Failed to create temp dir"),
+        }
+    }
+
+    /// Like `start`, serving `body` in place of the recorded page at `page` with the query
+    /// parameter `query`, e.g. search results or a topic changed for a test.
+    pub async fn with_page(page: &str, (name, value): (&str, &str), body: String) -> Self {
+        let harness = Self::start().await;
+        Mock::given(path(page))
+            .and(query_param(name, value))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/html; charset=utf-8"))
+            .with_priority(1)
+            .mount(&harness.server)
+            .await;
+        harness
+    }
+
+    pub fn output_dir(&self) -> PathBuf {
+        self.dir.path().join("out")
+    }
+
+    /// Command running the scraper against the local server; `extra_config` is appended to the
+    /// config file.
+    pub fn command(&self, extra_config: &str, args: &[&str]) -> Command {
+        let config_path = self.dir.path().join("scraper.toml");
+        std::fs::write(
+            &config_path,
+            format!(
+                "[scrape]\nbase_url = \"{}/\"\noutput_dir = \"{}\"\n\n{}",
+                self.server.uri(),
+                self.output_dir().display(),
+                extra_config
+            ),
+        )
+        .expect("Failed to write config");
+
+        let mut command = Command::new(env!("CARGO_BIN_EXE_scrapper"));
+        command
+            .current_dir(self.dir.path())
+            .arg("--config")
+            .arg(&config_path)
+            .args(args);
+        command
+    }
+
+    pub fn run_with_config(&self, extra_config: &str, args: &[&str]) -> Output {
+        let output = self
+            .command(extra_config, args)
+            .output()
+            .expect("Failed to run scraper");
+
+        if !output.status.success() {
+            eprintln!("{}", String::from_utf8_lossy(&output.stdout));
+            eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+        }
+
+        output
+    }
+
+    pub fn run(&self, args: &[&str]) -> Output {
+        self.run_with_config("", args)
+    }
+}
+
+pub fn fixture(name: &str) -> String {
+    std::fs::read_to_string(
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/fixtures")
+            .join(name),
+    )
+    .unwrap_or_else(|e| panic!("Failed to read fixture {}: {}", name, e))
+}
+
+/// Message by `author` as parsed from a topic page, its content block holding `html`.
+pub fn message_by(author: &str, html: &str) -> PostMessage {
+    PostMessage {
+        id: None,
+        author: author.to_string(),
+        author_profile: None,
+        date: "Posté le: 12/03/2023 10:15".to_string(),
+        message: format!("<div class=\"py-4 postrow-message\">{}</div>", html),
+        summary: None,
+        translation: None,
+        deleted: false,
+        role: None,
+    }
+}
+
+/// Message of the person asking, see [`message_by`].
+pub fn message(html: &str) -> PostMessage {
+    message_by("Yossef Cohen", html)
+}
+
+fn html(name: &str) -> ResponseTemplate {
+    ResponseTemplate::new(200)
+        .insert_header("content-type", "text/html; charset=utf-8")
+        .set_body_string(fixture(name))
+}
+
+/// Concatenated text of `word/document.xml`, good enough to assert some content made it to the document.
+pub fn docx_xml(path: &Path) -> String {
+    docx_part(path, "word/document.xml")
+}
+
+/// Raw XML of a part of the docx archive, e.g. `word/comments.xml`.
+pub fn docx_part(path: &Path, name: &str) -> String {
+    let file = std::fs::File::open(path).expect("Failed to open docx");
+    let mut archive = zip::ZipArchive::new(file).expect("Invalid docx archive");
+    let mut part = archive
+        .by_name(name)
+        .unwrap_or_else(|_| panic!("Missing {}", name));
+
+    let mut xml = String::new();
+    std::io::Read::read_to_string(&mut part, &mut xml).expect("Invalid XML part");
+    xml
+}
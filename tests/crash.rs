@@ -0,0 +1,29 @@
+use scrapper::report::crash::{install_panic_hook, set_current_topic, take_crash, Crash};
+
+#[test]
+fn panics_are_recorded_with_the_topic_being_exported() {
+    install_panic_hook();
+    set_current_topic(Some("https://forum/viewtopic.php?t=101"));
+
+    let panicked = std::panic::catch_unwind(|| panic!("Unexpected table in {}", "message 3"));
+    assert!(panicked.is_err());
+    let crash = take_crash().expect("Panic not recorded");
+    assert_eq!(
+        crash.topic_url.as_deref(),
+        Some("https://forum/viewtopic.php?t=101")
+    );
+    assert_eq!(crash.message, "Unexpected table in message 3");
+    assert!(crash.location.unwrap().starts_with("tests/crash.rs:"));
+    assert!(take_crash().is_none());
+
+    set_current_topic(None);
+    let _ = std::panic::catch_unwind(|| panic!("After the export"));
+    let crash = take_crash().unwrap();
+    assert_eq!(crash.topic_url, None);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("crash.json");
+    crash.write(&path).unwrap();
+    let written: Crash = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(written, crash);
+}
@@ -0,0 +1,94 @@
+mod common;
+
+use common::{docx_part, docx_xml, message_by};
+use scrapper::config::config::ExportConfig;
+use scrapper::export::cross_refs::{set_known_topics, CrossReferences};
+use scrapper::post::post::Post;
+use scrapper::report::report::Report;
+
+fn known_topics() {
+    set_known_topics([
+        ("Le libre arbitre", "https://forum.test/viewtopic.php?t=10"),
+        (
+            "Le libre arbitre (suite)",
+            "https://forum.test/viewtopic.php?t=12",
+        ),
+        (
+            "Allumer une bougie avant chabbat",
+            "https://forum.test/viewtopic.php?t=20",
+        ),
+        ("Chabbat", "https://forum.test/viewtopic.php?t=30"),
+    ]);
+}
+
+#[test]
+fn mentions_are_matched_to_titles_despite_typos() {
+    known_topics();
+    let references = CrossReferences::new("https://forum.test/viewtopic.php?t=1").unwrap();
+
+    assert_eq!(
+        references.resolve("allumer les bougies avant Chabat"),
+        Some((
+            "Allumer une bougie avant chabbat",
+            "https://forum.test/viewtopic.php?t=20"
+        ))
+    );
+    assert_eq!(
+        references.resolve("le libre-arbitre (suite)"),
+        Some((
+            "Le libre arbitre (suite)",
+            "https://forum.test/viewtopic.php?t=12"
+        ))
+    );
+    assert_eq!(
+        references.resolve("Libre arbitre"),
+        Some(("Le libre arbitre", "https://forum.test/viewtopic.php?t=10"))
+    );
+    // One word of the title in a long mention
+    assert_eq!(
+        references.resolve("des lois de chabbat pour les voyageurs en avion"),
+        None
+    );
+    assert_eq!(references.resolve("la cacherout"), None);
+
+    // Not to itself
+    let references = CrossReferences::new("https://forum.test/viewtopic.php?t=30").unwrap();
+    assert_eq!(references.resolve("chabbat"), None);
+}
+
+#[test]
+fn referenced_topics_get_a_footnote_with_their_url() {
+    known_topics();
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Hachkafa.docx");
+    let mut post = Post {
+        url: "https://forum.test/viewtopic.php?t=1".to_string(),
+        title: "Le mérite".to_string(),
+        messages: Some(vec![message_by(
+            "Rav Binyamin Wattenberg",
+            "Voir le sujet « Le libre arbitre » \
+             et cf. le fil allumer une bougie avant chabbat. Voir aussi le sujet \
+             libre arbitre, déjà cité.",
+        )]),
+        category: "Hachkafa".into(),
+        ..Default::default()
+    };
+    let options = ExportConfig {
+        cross_references: true,
+        ..Default::default()
+    };
+    post.messages_to_word(&path, &options, &mut Report::default())
+        .unwrap();
+
+    assert_eq!(docx_xml(&path).matches("w:footnoteReference").count(), 2);
+    let footnotes = docx_part(&path, "word/footnotes.xml");
+    assert!(footnotes.contains("https://forum.test/viewtopic.php?t=10"));
+    assert!(footnotes.contains("https://forum.test/viewtopic.php?t=20"));
+    assert!(footnotes.contains("Le libre arbitre"));
+
+    // Off by default
+    let path = dir.path().join("Halakha.docx");
+    post.messages_to_word(&path, &ExportConfig::default(), &mut Report::default())
+        .unwrap();
+    assert!(!docx_xml(&path).contains("w:footnoteReference"));
+}
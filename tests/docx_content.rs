@@ -0,0 +1,100 @@
+mod common;
+
+use common::docx::DocxSnapshot;
+use common::{fixture, Harness};
+use scrapper::config::config::Config;
+use scrapper::post::post::Post;
+use scrapper::report::report::Report;
+use std::sync::Arc;
+
+async fn halakha() -> DocxSnapshot {
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+
+    DocxSnapshot::open(&harness.output_dir().join("Halakha.docx"))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn topic_title_is_a_centered_bold_heading() {
+    let docx = halakha().await;
+
+    let title = docx.paragraph("Allumer une bougie avant chabbat");
+    assert!(title.centered);
+    let run = docx.run("Allumer une bougie avant chabbat");
+    assert!(run.bold);
+    assert_eq!(run.size, Some(32));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn authors_and_dates_are_bold_underlined_headers() {
+    let docx = halakha().await;
+
+    let question = docx.run("Question par YC");
+    assert!(question.bold && question.underline);
+    assert_eq!(question.size, Some(24));
+
+    let answer = docx.run("Réponse:");
+    assert!(answer.bold && answer.underline);
+
+    let date = docx.run("Le 12/03/2023 10:15");
+    assert!(date.bold && date.underline);
+
+    assert!(docx.position("Question par YC") < docx.position("Le 12/03/2023 10:15"));
+    assert!(docx.position("Le 12/03/2023 10:15") < docx.position("Réponse:"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn citations_are_indented_and_styled() {
+    let docx = halakha().await;
+
+    let citation = docx.paragraph("Citation: ");
+    assert_eq!(citation.indent_left, Some(300));
+    assert!(citation
+        .runs
+        .iter()
+        .all(|run| run.style.as_deref() == Some("citation")));
+    assert!(docx.run("Citation:").bold);
+
+    // The answer following the citation is back to a regular paragraph
+    let answer = docx.paragraph("Oui, tant que le soleil");
+    assert_eq!(answer.indent_left, None);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn inline_formatting_is_kept() {
+    let docx = halakha().await;
+
+    assert!(docx.run("juste avant").bold);
+    assert!(docx.run("pas").underline);
+}
+
+// A library caller on the default runtime, with a single thread
+#[tokio::test]
+async fn topic_is_saved_on_a_current_thread_runtime() {
+    let dir = tempfile::tempdir().unwrap();
+    let mut config = Config::default();
+    config.scrape.output_dir = dir.path().to_path_buf();
+    let mut post = Post {
+        url: "https://forum.test/viewtopic.php?t=102".to_owned(),
+        title: "Le libre arbitre".to_owned(),
+        html: Some(fixture("topic_102.html")),
+        category: "Hachkafa".into(),
+        ..Default::default()
+    };
+
+    let path = post
+        .save(
+            &reqwest::Client::new(),
+            &Arc::new(config),
+            &mut Report::default(),
+            None,
+            None,
+            None,
+        )
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert!(DocxSnapshot::open(&path).position("Hilkhot Techouva") > 0);
+    assert_eq!(post.messages.map(|messages| messages.len()), Some(2));
+}
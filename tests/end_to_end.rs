@@ -0,0 +1,1638 @@
+mod common;
+
+use common::{docx_xml, fixture, Harness};
+use scrapper::export::custom_properties::{mark_generated, read_custom_properties};
+use scrapper::report::diff::word_diff;
+use scrapper::store::archive::RawArchive;
+use serde_json::Value;
+use std::io::Read;
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use wiremock::matchers::{body_string_contains, method, path, query_param};
+use wiremock::{Mock, ResponseTemplate};
+
+#[tokio::test(flavor = "multi_thread")]
+async fn scrapes_topics_into_category_documents() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+
+    let out = harness.output_dir();
+    let halakha = docx_xml(&out.join("Halakha.docx"));
+    assert!(halakha.contains("Allumer une bougie avant chabbat"));
+    assert!(halakha.contains("juste avant"));
+    assert!(halakha.contains("Question par YC"));
+    // Second page of the topic
+    assert!(halakha.contains("Et pour une femme qui allume en retard ?"));
+
+    let hachkafa = docx_xml(&out.join("Hachkafa.docx"));
+    assert!(hachkafa.contains("Le libre arbitre"));
+    assert!(hachkafa.contains("Hilkhot Techouva"));
+    assert!(!hachkafa.contains("Sarah Levy"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exports_the_topics_fetched_one_at_a_time() {
+    let harness = Harness::start().await;
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "101"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_raw(fixture("topic_101.html"), "text/html; charset=utf-8")
+                .set_delay(Duration::from_millis(300)),
+        )
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    let path = harness.dir.path().join("summary.json");
+
+    let output =
+        harness.run_with_config("fetch_ahead = 1\n", &["--summary", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    let summary: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(summary["topics_fetched"], 2);
+    assert_eq!(summary["topics_exported"], 2);
+    let halakha = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(halakha.contains("Et pour une femme qui allume en retard ?"));
+    assert!(docx_xml(&harness.output_dir().join("Hachkafa.docx")).contains("Hilkhot Techouva"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_manifest_with_checksums() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+
+    let out = harness.output_dir();
+    let manifest: Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("manifest.json")).unwrap()).unwrap();
+    let files = manifest["files"].as_array().unwrap();
+    let documents = files
+        .iter()
+        .filter(|file| file["run_topic_count"] != 0)
+        .collect::<Vec<_>>();
+    assert_eq!(documents.len(), 2);
+
+    for file in documents {
+        assert_eq!(file["topic_count"], 1);
+        assert_eq!(file["run_topic_count"], 1);
+        assert_eq!(file["source_urls"].as_array().unwrap().len(), 1);
+
+        let bytes = std::fs::read(file["path"].as_str().unwrap()).unwrap();
+        assert_eq!(file["sha256"].as_str().unwrap().len(), 64);
+        assert!(!bytes.is_empty());
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manifest_lists_the_index_notice_and_calendar() {
+    let harness = Harness::start().await;
+    let config =
+        "[export]\nical = true\n\n[export.notice]\nattribution = \"Avec l'autorisation du Rav\"\n";
+
+    assert!(harness.run_with_config(config, &[]).status.success());
+
+    let out = harness.output_dir();
+    let manifest: Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("manifest.json")).unwrap()).unwrap();
+    for name in ["index.md", "NOTICE.txt", "answers.ics"] {
+        let path = out.join(name);
+        let file = manifest["files"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|file| file["path"] == path.display().to_string())
+            .unwrap_or_else(|| panic!("{} not in {}", name, manifest));
+        assert!(file["topic_count"].is_null());
+        assert_eq!(file["run_topic_count"], 0);
+        assert_eq!(
+            file["sha256"],
+            scrapper::export::manifest::sha256_hex(&std::fs::read(&path).unwrap())
+        );
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reports_messages_edited_since_they_were_exported() {
+    let harness = Harness::start().await;
+    let report = || -> Value {
+        serde_json::from_str(
+            &std::fs::read_to_string(harness.output_dir().join("report.json")).unwrap(),
+        )
+        .unwrap()
+    };
+    assert!(harness.run(&[]).status.success());
+    assert_eq!(report()["changed_messages"], serde_json::json!([]));
+
+    let topic = fixture("topic_102.html").replace("chapitre 5.", "chapitres 5 et 6.");
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "102"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(topic, "text/html; charset=utf-8"))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    // Also when delta mode leaves the message out of the documents
+    assert!(harness.run(&["--full", "--delta"]).status.success());
+    let changed = report()["changed_messages"].as_array().unwrap().clone();
+    assert_eq!(changed.len(), 1);
+    assert_eq!(
+        changed[0]["topic_url"],
+        format!("{}/viewtopic.php?t=102", harness.server.uri())
+    );
+    assert_eq!(
+        changed[0]["diff"],
+        "Voir le Rambam, Hilkhot Techouva [-chapitre 5.-] {+chapitres 5 et 6.+}"
+    );
+
+    // The edit is reported once
+    assert!(harness.run(&["--full"]).status.success());
+    assert_eq!(report()["changed_messages"], serde_json::json!([]));
+}
+
+#[test]
+fn diffs_long_rewritten_messages_as_a_whole() {
+    // 3000 words rewritten in 3000 others, past the size of the compared table
+    let old = (0..3000)
+        .map(|i| format!("a{i}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let new = (0..3000)
+        .map(|i| format!("b{i}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let diff = word_diff(
+        &format!("Chalom {old} Kol touv"),
+        &format!("Chalom {new} Kol touv"),
+    );
+    assert_eq!(diff, format!("Chalom [-{old}-] {{+{new}+}} Kol touv"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keeps_the_messages_deleted_from_the_forum() {
+    let harness = Harness::start().await;
+    let config = "[export]\ndeleted_note = true\n";
+    let read = |name: &str| -> Value {
+        serde_json::from_str(&std::fs::read_to_string(harness.output_dir().join(name)).unwrap())
+            .unwrap()
+    };
+    assert!(harness.run_with_config(config, &[]).status.success());
+
+    // The question is deleted by the moderators
+    let topic = fixture("topic_102.html");
+    let question = topic.find("<div class=\"flex\">").unwrap();
+    let answer = question + 1 + topic[question + 1..].find("<div class=\"flex\">").unwrap();
+    let topic = format!("{}{}", &topic[..question], &topic[answer..]);
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "102"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(topic, "text/html; charset=utf-8"))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    assert!(harness
+        .run_with_config(config, &["--full"])
+        .status
+        .success());
+    let deleted = read("report.json")["deleted_messages"]
+        .as_array()
+        .unwrap()
+        .clone();
+    assert_eq!(deleted.len(), 1);
+    assert_eq!(deleted[0]["author"], "Sarah Levy");
+    assert_eq!(deleted[0]["key"], "6001");
+    let url = format!("{}/viewtopic.php?t=102", harness.server.uri());
+    assert_eq!(
+        read("state.json")["topics"][&url]["messages"]["6001"]["deleted"],
+        true
+    );
+
+    // Reported once, kept in the documents with its note
+    assert!(harness
+        .run_with_config(config, &["--full"])
+        .status
+        .success());
+    assert_eq!(
+        read("report.json")["deleted_messages"],
+        serde_json::json!([])
+    );
+    let document = docx_xml(&harness.output_dir().join("Hachkafa.docx"));
+    assert_eq!(
+        document
+            .matches("Comment concilier le libre arbitre et la connaissance divine ?")
+            .count(),
+        3
+    );
+    assert_eq!(document.matches("[message supprimé du forum]").count(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn topics_listed_with_the_same_last_message_are_not_fetched_again() {
+    let harness = Harness::start().await;
+    let path = harness.dir.path().join("summary.json");
+    let summary =
+        || -> Value { serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap() };
+    let topic_requests = || async {
+        harness
+            .server
+            .received_requests()
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|request| request.url.path().ends_with("viewtopic.php"))
+            .count()
+    };
+
+    assert!(harness.run(&[]).status.success());
+    let fetched = topic_requests().await;
+    let halakha = std::fs::read(harness.output_dir().join("Halakha.docx")).unwrap();
+
+    let output = harness.run(&["--summary", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert_eq!(summary()["topics_discovered"], 2);
+    assert_eq!(summary()["topics_unchanged"], 2);
+    assert_eq!(summary()["topics_fetched"], 0);
+    assert_eq!(topic_requests().await, fetched);
+    assert_eq!(
+        std::fs::read(harness.output_dir().join("Halakha.docx")).unwrap(),
+        halakha
+    );
+
+    // Unless asked to
+    let output = harness.run(&["--full", "--summary", path.to_str().unwrap()]);
+    assert!(output.status.success());
+    assert_eq!(summary()["topics_unchanged"], 0);
+    assert_eq!(summary()["topics_fetched"], 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn state_is_replaced_whole() {
+    let harness = Harness::start().await;
+    let out = harness.output_dir();
+    assert!(harness.run(&[]).status.success());
+    let state = std::fs::read_to_string(out.join("state.json")).unwrap();
+
+    // A write cut short by a crash is left aside and does not touch the state
+    std::fs::write(out.join("state.json.tmp"), &state[..state.len() / 2]).unwrap();
+    assert!(harness.run(&[]).status.success());
+    let state: Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("state.json")).unwrap()).unwrap();
+    assert!(state["last_successful_run"].is_string());
+    assert!(!out.join("state.json.tmp").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn second_run_only_searches_since_last_run() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+    assert!(harness.output_dir().join("state.json").exists());
+    assert!(harness.run(&[]).status.success());
+
+    let searches = harness
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.query() == Some("mode=results"))
+        .map(|request| String::from_utf8(request.body).unwrap())
+        .collect::<Vec<_>>();
+
+    assert_eq!(searches.len(), 2);
+    assert!(!searches[0].contains("search_time=1&"));
+    assert!(searches[1].contains("search_time=1&"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn packages_outputs_into_zip() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&["--package", "zip"]).status.success());
+
+    let archive = std::fs::read_dir(harness.output_dir())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.extension().is_some_and(|ext| ext == "zip"))
+        .expect("No archive generated");
+
+    let archive = zip::ZipArchive::new(std::fs::File::open(archive).unwrap()).unwrap();
+    let mut names = archive.file_names().collect::<Vec<_>>();
+    names.sort();
+    assert_eq!(
+        names,
+        [
+            "Hachkafa.docx",
+            "Halakha.docx",
+            "index.docx",
+            "index.md",
+            "manifest.json"
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn rejects_invalid_config() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config("[scrape]\npage_size = 0\n", &[]);
+    assert!(!output.status.success());
+
+    let output = harness.run_with_config("max_page_bytes = 0\n", &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("scrape.max_page_bytes"));
+
+    for font_size in [
+        "factor = 0",
+        "factor = -1.5",
+        "factor = nan",
+        "threshold = 0",
+    ] {
+        let output = harness.run_with_config(&format!("[export.font_size]\n{}\n", font_size), &[]);
+        assert!(!output.status.success(), "{}", font_size);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("export.font_size"));
+    }
+
+    let output = harness.run_with_config("[export.dates]\nformat = \"%d/%m/%Y %Q\"\n", &[]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("export.dates.format"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn applies_configured_text_transforms() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config(
+        r#"
+[[transforms]]
+kind = "abbreviations"
+entries = { Rambam = "Rambam (Maïmonide)" }
+
+[[transforms]]
+kind = "regex"
+pattern = "(?i)chabbat"
+replacement = "Chabbath"
+
+[[transforms]]
+kind = "typography"
+"#,
+        &[],
+    );
+    assert!(output.status.success());
+
+    let out = harness.output_dir();
+    let halakha = docx_xml(&out.join("Halakha.docx"));
+    assert!(halakha.contains("juste avant</w:t>"));
+    assert!(halakha.contains("Chabbath ?"));
+    assert!(halakha.contains("s’est pas couché."));
+
+    let hachkafa = docx_xml(&out.join("Hachkafa.docx"));
+    assert!(hachkafa.contains("Voir le Rambam (Maïmonide),"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn french_typography_is_opt_in_per_exporter() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+    let plain = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(plain.contains("chabbat ?"));
+
+    let harness = Harness::start().await;
+    let output = harness.run_with_config("[export.docx]\nfrench_typography = true\n", &[]);
+    assert!(output.status.success());
+    let typeset = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(typeset.contains("chabbat\u{202F}?"));
+    assert!(typeset.contains("Le 12/03/2023 10:15"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn glossary_expands_first_occurrence_in_brackets() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config(
+        "[export.glossary]\nentries = { chabbat = \"jour de repos\" }\n",
+        &[],
+    );
+    assert!(output.status.success());
+
+    let halakha = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert_eq!(halakha.matches("chabbat (jour de repos)").count(), 1);
+    assert!(halakha.matches("chabbat").count() > 1);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn glossary_footnotes_survive_appends() {
+    let harness = Harness::start().await;
+    let config = "[export.glossary]\nmode = \"footnote\"\nentries = { Rambam = \"Maïmonide\" }\n";
+
+    assert!(harness
+        .run_with_config(config, &["--full"])
+        .status
+        .success());
+    assert!(harness
+        .run_with_config(config, &["--full"])
+        .status
+        .success());
+
+    let path = harness.output_dir().join("Hachkafa.docx");
+    assert_eq!(docx_xml(&path).matches("w:footnoteReference").count(), 2);
+
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&path).unwrap()).unwrap();
+    let mut footnotes = String::new();
+    std::io::Read::read_to_string(
+        &mut archive.by_name("word/footnotes.xml").unwrap(),
+        &mut footnotes,
+    )
+    .unwrap();
+    assert_eq!(footnotes.matches("Maïmonide").count(), 2);
+    assert!(footnotes.contains("w:id=\"1\""));
+    assert!(footnotes.contains("w:id=\"2\""));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reports_and_truncates_long_messages() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config(
+        "[export.long_messages]\nmax_chars = 40\ntruncate = true\n",
+        &[],
+    );
+    assert!(output.status.success());
+
+    let out = harness.output_dir();
+    let report: Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("report.json")).unwrap()).unwrap();
+    let long_messages = report["long_messages"].as_array().unwrap();
+    assert!(!long_messages.is_empty());
+    assert!(long_messages
+        .iter()
+        .all(|message| message["chars"].as_u64().unwrap() > 40 && message["truncated"] == true));
+
+    let halakha = docx_xml(&out.join("Halakha.docx"));
+    assert!(halakha.contains("Message tronqué, texte complet : "));
+    assert!(halakha.contains("viewtopic.php?t=101"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn keeps_the_replies_and_views_listed_by_the_search() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config("[export]\nactivity = true\n", &[]);
+    assert!(output.status.success());
+
+    let out = harness.output_dir();
+    let read = |name: &str| -> Value {
+        serde_json::from_str(&std::fs::read_to_string(out.join(name)).unwrap()).unwrap()
+    };
+    let report = read("report.json");
+    let halakha = report["topics"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|topic| topic["category"] == "Halakha")
+        .unwrap();
+    assert_eq!(halakha["replies"], 3);
+    assert_eq!(halakha["views"], 154);
+
+    let manifest = read("manifest.json");
+    let hachkafa = manifest["files"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|file| file["path"].as_str().unwrap().ends_with("Hachkafa.docx"))
+        .unwrap();
+    assert_eq!(hachkafa["replies"], 1);
+    assert_eq!(hachkafa["views"], 87);
+
+    let state = read("state.json");
+    let topic = &state["topics"][format!("{}/viewtopic.php?t=102", harness.server.uri())];
+    assert_eq!(topic["views"], 87);
+
+    assert!(docx_xml(&out.join("Halakha.docx")).contains("3 réponses · 154 vues"));
+    assert!(docx_xml(&out.join("Hachkafa.docx")).contains("1 réponse · 87 vues"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reports_topic_word_counts() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config("[export.docx]\nword_count = true\n", &[]);
+    assert!(output.status.success());
+
+    let out = harness.output_dir();
+    let report: Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("report.json")).unwrap()).unwrap();
+    let topics = report["topics"].as_array().unwrap();
+    assert_eq!(topics.len(), 2);
+
+    let halakha = topics
+        .iter()
+        .find(|topic| topic["category"] == "Halakha")
+        .unwrap();
+    let words = halakha["words"].as_u64().unwrap();
+    assert!(words > 0);
+    assert_eq!(halakha["messages"], 4);
+    assert_eq!(halakha["reading_minutes"], 1);
+
+    let document = docx_xml(&out.join("Halakha.docx"));
+    assert!(document.contains(&format!("{} mots · 1 min de lecture", words)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn plans_volumes_from_the_report() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+    let output = harness.run(&[
+        "plan",
+        "--pages-per-volume",
+        "1",
+        "--words-per-page",
+        "10000",
+    ]);
+    assert!(output.status.success());
+
+    let plan = std::fs::read_to_string(harness.output_dir().join("volumes.csv")).unwrap();
+    let lines = plan.lines().collect::<Vec<_>>();
+    assert_eq!(lines[0], "volume,first_page,pages,words,category,title,url");
+    // One page each, so every topic gets its own volume, in category order
+    assert!(lines[1].starts_with("1,1,1,"));
+    assert!(lines[1].contains(",Hachkafa,Le libre arbitre,"));
+    assert!(lines[2].starts_with("2,1,1,"));
+    assert!(lines[2].contains(",Halakha,"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn adds_front_matter_to_new_documents_only() {
+    let harness = Harness::start().await;
+    let preface = harness.dir.path().join("preface.txt");
+    std::fs::write(
+        &preface,
+        "Ce recueil rassemble les réponses\nde la catégorie {category}.\n\nBonne lecture.\n",
+    )
+    .unwrap();
+    let config = format!(
+        "[export.front_matter]\ntitle = \"Recueil\"\npreface = \"{}\"\n",
+        preface.display()
+    );
+
+    assert!(harness.run_with_config(&config, &[]).status.success());
+    assert!(harness.run_with_config(&config, &[]).status.success());
+
+    let halakha = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert_eq!(halakha.matches("Recueil").count(), 1);
+    assert_eq!(
+        halakha
+            .matches("Ce recueil rassemble les réponses de la catégorie Halakha.")
+            .count(),
+        1
+    );
+    assert!(halakha.find("Bonne lecture.").unwrap() < halakha.find("Allumer une bougie").unwrap());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn answers_only_keeps_answers_with_question_summaries() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&["--answers-only"]).status.success());
+
+    let halakha = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(halakha.contains("Peut-on allumer une bougie juste avant chabbat ?"));
+    assert!(halakha.contains("tant que le soleil ne s"));
+    assert!(halakha.contains("Et pour une femme qui allume en retard ?"));
+    assert!(!halakha.contains("Question par"));
+    assert!(!halakha.contains("Bonjour Rav"));
+    assert_eq!(halakha.matches("Question : ").count(), 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn watch_mode_publishes_answers_in_atom_feed() {
+    let harness = Harness::start().await;
+    let feed_path = harness.output_dir().join("feed.atom");
+
+    let mut child = harness
+        .command("", &["--watch"])
+        .stdout(Stdio::null())
+        .spawn()
+        .unwrap();
+    let started = Instant::now();
+    let written =
+        || std::fs::read_to_string(&feed_path).is_ok_and(|feed| feed.ends_with("</feed>\n"));
+    while !written() && started.elapsed() < Duration::from_secs(30) {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    // Still waiting for the next interval
+    assert!(child.try_wait().unwrap().is_none());
+    child.kill().unwrap();
+    child.wait().unwrap();
+
+    let feed = std::fs::read_to_string(&feed_path).unwrap();
+    assert!(feed.starts_with("<?xml"));
+    // One entry per answer
+    assert_eq!(feed.matches("<entry>").count(), 3);
+    assert!(feed.contains("<title>Le libre arbitre</title>"));
+    assert!(feed.contains("viewtopic.php?t=101#5002"));
+    assert!(feed.contains("<updated>2023-03-13T07:30:00+00:00</updated>"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_calendar_of_answer_dates() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config("[export]\nical = true\n", &[]);
+    assert!(output.status.success());
+    assert!(harness
+        .run_with_config("[export]\nical = true\n", &[])
+        .status
+        .success());
+
+    let calendar = std::fs::read_to_string(harness.output_dir().join("answers.ics")).unwrap();
+    assert!(calendar.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(calendar.ends_with("END:VCALENDAR\r\n"));
+    // Merged with the previous run instead of duplicated
+    assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+    assert!(calendar.contains("DTSTART;VALUE=DATE:20230313"));
+    assert!(calendar.contains("SUMMARY:Le libre arbitre"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn csv_format_writes_one_row_per_message() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&["--format", "csv"]).status.success());
+
+    let out = harness.output_dir();
+    assert!(!out.join("Halakha.docx").exists());
+    let csv = std::fs::read_to_string(out.join("messages.csv")).unwrap();
+    assert!(csv.starts_with("topic,url,category,date,author,role,text\r\n"));
+    // Header, then 4 messages for the first topic and 2 for the second
+    assert_eq!(csv.matches("\r\n").count(), 7);
+    assert_eq!(csv.matches(",answer,").count(), 3);
+    assert!(csv.contains(",Halakha,12/03/2023 10:15,YC,question,\"Bonjour Rav,\n"));
+    assert!(!csv.contains("Yossef Cohen,"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dates_are_converted_to_the_configured_time_zone() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config(
+        "[export.dates]\ntimezone = \"Asia/Jerusalem\"\nformat = \"%Y-%m-%d %H:%M\"\n",
+        &["--format", "csv"],
+    );
+    assert!(output.status.success());
+
+    let csv = std::fs::read_to_string(harness.output_dir().join("messages.csv")).unwrap();
+    // 10:15 in Paris (UTC+1) is 11:15 in Jerusalem (UTC+2)
+    assert!(csv.contains(",Halakha,2023-03-12 11:15,YC,question,"));
+    // 22:10 in summer time, UTC+2 in Paris and UTC+3 in Jerusalem
+    assert!(csv.contains(",Hachkafa,2023-05-01 23:10,"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn dates_can_be_written_out_in_french_or_iso() {
+    let harness = Harness::start().await;
+
+    let output =
+        harness.run_with_config("[export.dates]\nstyle = \"long\"\n", &["--format", "csv"]);
+    assert!(output.status.success());
+    let csv = std::fs::read_to_string(harness.output_dir().join("messages.csv")).unwrap();
+    assert!(csv.contains(",Halakha,12 mars 2023 à 10:15,YC,question,"));
+    assert!(csv.contains(",Hachkafa,1er mai 2023 à 22:10,"));
+
+    let harness = Harness::start().await;
+    let output = harness.run_with_config("[export.dates]\nstyle = \"iso\"\n", &["--format", "csv"]);
+    assert!(output.status.success());
+    let csv = std::fs::read_to_string(harness.output_dir().join("messages.csv")).unwrap();
+    assert!(csv.contains(",Halakha,2023-03-12T10:15:00+01:00,YC,question,"));
+    assert!(csv.contains(",Hachkafa,2023-05-01T22:10:00+02:00,"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn anki_deck_has_one_card_per_exchange() {
+    let harness = Harness::start().await;
+
+    let output = harness.run_with_config(
+        "[export.anki]\nfront = \"summary\"\ncategories = [\"Halakha\"]\n",
+        &["--format", "anki"],
+    );
+    assert!(output.status.success());
+
+    let deck = std::fs::read_to_string(harness.output_dir().join("anki.tsv")).unwrap();
+    assert!(deck.starts_with("#separator:tab\n#html:true\n#tags column:3\n"));
+    let cards = deck
+        .lines()
+        .filter(|line| !line.starts_with('#'))
+        .map(|line| line.split('\t').collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+    assert_eq!(cards.len(), 2);
+    assert!(cards
+        .iter()
+        .all(|card| card.len() == 3 && card[2] == "Halakha"));
+    assert_eq!(
+        cards[0][0],
+        "Peut-on allumer une bougie juste avant chabbat ?"
+    );
+    assert!(cards[0][1].contains("tant que le soleil"));
+    assert!(!deck.contains("libre arbitre"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn latex_format_appends_sections_to_a_book_per_category() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&["--format", "latex"]).status.success());
+    // Unchanged topics are only appended again by a full run
+    assert!(harness
+        .run(&["--format", "latex", "--full"])
+        .status
+        .success());
+
+    let tex = std::fs::read_to_string(harness.output_dir().join("Halakha.tex")).unwrap();
+    assert!(tex.starts_with("\\documentclass[12pt]{book}"));
+    assert!(tex.contains("\\usepackage[french]{babel}"));
+    assert_eq!(tex.matches("\\begin{document}").count(), 1);
+    assert!(tex.ends_with("\\end{document}\n"));
+    assert_eq!(tex.matches("\\section{").count(), 2);
+    assert!(tex.contains("\\textbf{juste avant}"));
+    assert!(tex.contains("\\begin{quote}"));
+    assert!(tex.contains("\\subsection*{Question par YC}"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn odt_format_mirrors_the_docx_layout() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&["--format", "odt"]).status.success());
+    assert!(harness.run(&["--format", "odt", "--full"]).status.success());
+
+    let file = std::fs::File::open(harness.output_dir().join("Halakha.odt")).unwrap();
+    let mut archive = zip::ZipArchive::new(file).unwrap();
+    {
+        let mimetype = archive.by_index(0).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), zip::CompressionMethod::Stored);
+    }
+    let mut content = String::new();
+    archive
+        .by_name("content.xml")
+        .unwrap()
+        .read_to_string(&mut content)
+        .unwrap();
+
+    assert_eq!(content.matches("</office:text>").count(), 1);
+    assert_eq!(
+        content
+            .matches("<text:p text:style-name=\"PTitle\">Allumer une bougie avant chabbat</text:p>")
+            .count(),
+        2
+    );
+    assert!(content.contains("<text:span text:style-name=\"Tb\">juste avant</text:span>"));
+    assert!(content.contains("<text:p text:style-name=\"PHeading\">Question par YC</text:p>"));
+    assert!(content.contains("text:style-name=\"PQuote\""));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bilingual_layout_puts_translations_next_to_messages() {
+    let harness = Harness::start().await;
+    let translations = harness.dir.path().join("translations");
+    std::fs::create_dir_all(&translations).unwrap();
+    std::fs::write(translations.join("5002.txt"), "Answer of the Rav").unwrap();
+
+    let output = harness.run_with_config(
+        &format!(
+            "[export.docx]\ntranslations = {:?}\n",
+            translations.display().to_string()
+        ),
+        &[],
+    );
+    assert!(output.status.success());
+
+    let halakha = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert_eq!(halakha.matches("<w:tbl>").count(), 4);
+    assert!(halakha.contains("Answer of the Rav"));
+    assert!(halakha.contains("tant que le soleil ne s"));
+}
+
+#[cfg(feature = "annotations")]
+#[tokio::test(flavor = "multi_thread")]
+async fn annotations_become_comments_or_footnotes() {
+    let harness = Harness::start().await;
+    let annotations = harness.dir.path().join("annotations.yaml");
+    std::fs::write(
+        &annotations,
+        "5002: Voir aussi le Michna Beroura\n\"999\": Message absent\n",
+    )
+    .unwrap();
+    let config = |mode: &str| {
+        format!(
+            "[export.annotations]\npath = {:?}\nmode = \"{}\"\n",
+            annotations.display().to_string(),
+            mode
+        )
+    };
+
+    assert!(harness
+        .run_with_config(&config("comment"), &[])
+        .status
+        .success());
+    let path = harness.output_dir().join("Halakha.docx");
+    let comments = common::docx_part(&path, "word/comments.xml");
+    assert!(comments.contains("Voir aussi le Michna Beroura"));
+    assert!(!comments.contains("Message absent"));
+    assert_eq!(docx_xml(&path).matches("w:commentReference").count(), 1);
+
+    std::fs::remove_file(&path).unwrap();
+    assert!(harness
+        .run_with_config(&config("footnote"), &[])
+        .status
+        .success());
+    assert!(common::docx_part(&path, "word/footnotes.xml").contains("Voir aussi le Michna Beroura"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn delta_mode_writes_only_new_messages_to_a_separate_file() {
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+    let out = harness.output_dir();
+    let master = std::fs::read(out.join("Halakha.docx")).unwrap();
+
+    // Forget the answer of the Rav, as if it had been posted after the first run
+    let state_path = out.join("state.json");
+    let mut state: Value =
+        serde_json::from_str(&std::fs::read_to_string(&state_path).unwrap()).unwrap();
+    let halakha = state["exported"]
+        .as_object_mut()
+        .unwrap()
+        .iter_mut()
+        .find(|(url, _)| url.contains("t=101"))
+        .unwrap()
+        .1;
+    halakha.as_array_mut().unwrap().retain(|key| key != "5002");
+    std::fs::write(&state_path, state.to_string()).unwrap();
+
+    assert!(harness.run(&["--full", "--delta"]).status.success());
+
+    assert_eq!(std::fs::read(out.join("Halakha.docx")).unwrap(), master);
+    assert!(!out.join("Hachkafa.delta.docx").exists());
+    let delta = docx_xml(&out.join("Halakha.delta.docx"));
+    assert!(delta.contains("tant que le soleil ne s"));
+    assert!(!delta.contains("Bonjour Rav"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn manually_edited_documents_are_not_rewritten() {
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+    let out = harness.output_dir();
+    let halakha = out.join("Halakha.docx");
+
+    let mut properties = read_custom_properties(&halakha).unwrap();
+    assert_eq!(properties["RibavGenerated"], "true");
+    assert_eq!(properties["ManuallyEdited"], "false");
+
+    properties.insert("ManuallyEdited".to_owned(), "true".to_owned());
+    mark_generated(&halakha, properties).unwrap();
+    let edited = std::fs::read(&halakha).unwrap();
+    // Made by hand, without the properties of the scraper
+    let hachkafa = out.join("Hachkafa.docx");
+    std::fs::remove_file(&hachkafa).unwrap();
+    docx_rust::Docx::default().write_file(&hachkafa).unwrap();
+    let handmade = std::fs::read(&hachkafa).unwrap();
+
+    assert!(harness.run(&["--full"]).status.success());
+
+    assert_eq!(std::fs::read(&halakha).unwrap(), edited);
+    assert_eq!(std::fs::read(&hachkafa).unwrap(), handmade);
+    let report: Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("report.json")).unwrap()).unwrap();
+    let reasons = report["protected_documents"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|protected| protected["reason"].as_str().unwrap().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(reasons.len(), 2);
+    assert!(reasons
+        .iter()
+        .any(|reason| reason.contains("ManuallyEdited")));
+    assert!(reasons
+        .iter()
+        .any(|reason| reason.contains("not generated")));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn interrupted_appends_are_rolled_back() {
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+    let out = harness.output_dir();
+    let halakha = out.join("Halakha.docx");
+
+    // A crash in the middle of an append: backup and journal written, document truncated
+    std::fs::copy(&halakha, out.join("Halakha.docx.orig")).unwrap();
+    std::fs::write(
+        out.join("Halakha.docx.journal"),
+        r#"{"topic_url":"viewtopic.php?t=101","backed_up":true}"#,
+    )
+    .unwrap();
+    std::fs::write(&halakha, b"PK\x03\x04").unwrap();
+    // And one before the journal of a new document could be written
+    std::fs::write(out.join("Hachkafa.docx.journal"), b"{\"topic").unwrap();
+
+    assert!(harness.run(&["--full"]).status.success());
+
+    assert!(docx_xml(&halakha).contains("tant que le soleil ne s"));
+    let leftovers = std::fs::read_dir(&out)
+        .unwrap()
+        .filter_map(|entry| entry.unwrap().file_name().into_string().ok())
+        .filter(|name| name.ends_with(".journal") || name.ends_with(".orig"))
+        .collect::<Vec<_>>();
+    assert!(leftovers.is_empty(), "{:?}", leftovers);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_a_run_summary() {
+    let harness = Harness::start().await;
+    let path = harness.dir.path().join("summary.json");
+
+    let output = harness.run(&["--summary", path.to_str().unwrap()]);
+    assert!(output.status.success());
+
+    let summary: Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(summary["topics_discovered"], 2);
+    assert_eq!(summary["topics_fetched"], 2);
+    assert_eq!(summary["topics_exported"], 2);
+    assert_eq!(summary["errors"], 0);
+    assert!(summary["bytes_downloaded"].as_u64().unwrap() > 0);
+    let coverage = &summary["parser_coverage"];
+    assert!(coverage["handled"].as_u64().unwrap() > 0);
+    let percents = ["handled_percent", "degraded_percent", "dropped_percent"]
+        .iter()
+        .map(|key| coverage[key].as_f64().unwrap())
+        .sum::<f64>();
+    assert!((percents - 100.0).abs() < 1e-6);
+    let stages = summary["stages"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|stage| stage["name"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(stages, ["search", "export", "outputs", "delivery"]);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pages_over_the_size_limit_are_not_read() {
+    let harness = Harness::start().await;
+    let topic = fixture("topic_102.html").replace(
+        "</body>",
+        &format!("<!-- {} --></body>", "x".repeat(10_000)),
+    );
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "102"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(topic, "text/html; charset=utf-8"))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    let output = harness.run_with_config("max_page_bytes = 5000\n", &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(harness.output_dir().join("Halakha.docx").exists());
+    assert!(!harness.output_dir().join("Hachkafa.docx").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn topics_over_the_sanity_limits_fail() {
+    let harness = Harness::start().await;
+    let output = harness.run_with_config("max_topic_pages = 1\n", &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!harness.output_dir().join("Halakha.docx").exists());
+    assert!(harness.output_dir().join("Hachkafa.docx").exists());
+    let logs = String::from_utf8_lossy(&output.stdout);
+    assert!(logs.contains("More than 1 pages"), "{}", logs);
+
+    let harness = Harness::start().await;
+    let output = harness.run_with_config("max_topic_messages = 1\n", &[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(!harness.output_dir().join("Halakha.docx").exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn failures_tell_the_topic_page_and_stage() {
+    let harness = Harness::start().await;
+    Mock::given(path("/suivante-t101-2.html"))
+        .respond_with(ResponseTemplate::new(500))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    let output = harness.run(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    let logs = String::from_utf8_lossy(&output.stdout);
+    let failure = logs
+        .lines()
+        .find(|line| line.contains("page 2 failed"))
+        .expect("No failure logged for the second page");
+    assert!(failure.contains("fetch of "), "{}", failure);
+    assert!(failure.contains("t=101"), "{}", failure);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn exit_codes_reflect_what_failed() {
+    let harness = Harness::start().await;
+    let output = harness.run_with_config("[scrape]\npage_size = 0\n", &[]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let harness = Harness::start().await;
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "102"))
+        .respond_with(ResponseTemplate::new(500))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    let output = harness.run(&[]);
+    assert_eq!(output.status.code(), Some(2));
+    assert!(harness.output_dir().join("Halakha.docx").exists());
+    assert!(!harness.output_dir().join("Hachkafa.docx").exists());
+
+    let harness = Harness::start().await;
+    Mock::given(method("POST"))
+        .and(path("/search.php"))
+        .respond_with(ResponseTemplate::new(503).insert_header("Retry-After", "0"))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    let output = harness.run(&[]);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("topic discovery failed"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn waits_for_retry_after_on_429() {
+    let harness = Harness::start().await;
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "102"))
+        .respond_with(ResponseTemplate::new(429).insert_header("Retry-After", "1"))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    let started = Instant::now();
+    let output = harness.run(&[]);
+    assert!(output.status.success());
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert!(docx_xml(&harness.output_dir().join("Hachkafa.docx")).contains("Hilkhot Techouva"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn reuses_the_session_cookie_of_the_previous_run() {
+    let harness = Harness::start().await;
+    Mock::given(method("POST"))
+        .and(path("/search.php"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/html; charset=utf-8")
+                .insert_header("set-cookie", "phpbb2_sid=abc123; Path=/")
+                .set_body_string(common::fixture("search_form.html")),
+        )
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    assert!(harness.run(&[]).status.success());
+    let cookies = std::fs::read_to_string(harness.output_dir().join("cookies.json")).unwrap();
+    assert!(cookies.contains("phpbb2_sid=abc123"));
+
+    let first_run = harness.server.received_requests().await.unwrap().len();
+    assert!(harness.run(&[]).status.success());
+    let requests = harness.server.received_requests().await.unwrap();
+    let search = &requests[first_run];
+    assert_eq!(search.method.as_str(), "POST");
+    assert_eq!(
+        search
+            .headers
+            .get("cookie")
+            .map(|value| value.to_str().unwrap()),
+        Some("phpbb2_sid=abc123")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn searches_again_when_the_search_session_expired() {
+    let harness = Harness::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search.php"))
+        .and(query_param("search_id", "4242"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            "<html><body><span class=\"gen\">Désolé, votre session de recherche a expiré.</span></body></html>",
+        ))
+        .up_to_n_times(1)
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    assert!(harness.run(&[]).status.success());
+    assert!(harness.output_dir().join("Halakha.docx").exists());
+    assert!(harness.output_dir().join("Hachkafa.docx").exists());
+
+    let searches = harness
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.query() == Some("mode=results"))
+        .count();
+    assert_eq!(searches, 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn stops_at_the_first_empty_search_page() {
+    let harness = Harness::start().await;
+    Mock::given(method("GET"))
+        .and(path("/search.php"))
+        .and(query_param("start", "50"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(
+            "<html><body><table class=\"forumline\"><tr><th>Sujets</th></tr></table></body></html>",
+        ))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    let summary = harness.dir.path().join("summary.json");
+    let output = harness.run_with_config(
+        // Still in the [scrape] table of the harness
+        "max_pages = 10\n",
+        &["--summary", summary.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+
+    let pages = harness
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.query_pairs().any(|(key, _)| key == "search_id"))
+        .count();
+    assert_eq!(pages, 2);
+    let summary: Value = serde_json::from_str(&std::fs::read_to_string(summary).unwrap()).unwrap();
+    assert_eq!(summary["search_pages"], 2);
+    assert_eq!(summary["topics_discovered"], 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn merges_the_topics_of_several_searches() {
+    let harness = Harness::start().await;
+    Mock::given(method("POST"))
+        .and(path("/search.php"))
+        .and(body_string_contains("search_keywords=techouva"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_string(common::fixture("search_form.html").replace("4242", "4343")),
+        )
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/search.php"))
+        .and(query_param("search_id", "4343"))
+        .respond_with(
+            ResponseTemplate::new(200).set_body_string(common::fixture("search_results.html")),
+        )
+        .mount(&harness.server)
+        .await;
+
+    let summary = harness.dir.path().join("summary.json");
+    let output = harness.run_with_config(
+        "[[scrape.searches]]\nauthor = \"Rav Binyamin Wattenberg\"\n\n\
+         [[scrape.searches]]\nauthor = \"\"\nkeywords = \"techouva\"\n",
+        &["--summary", summary.to_str().unwrap()],
+    );
+    assert!(output.status.success());
+
+    let requests = harness.server.received_requests().await.unwrap();
+    let searches: Vec<_> = requests
+        .iter()
+        .filter(|request| request.url.query() == Some("mode=results"))
+        .map(|request| String::from_utf8_lossy(&request.body).into_owned())
+        .collect();
+    assert_eq!(searches.len(), 2);
+    assert!(searches[0].contains("search_author=Rav+Binyamin+Wattenberg"));
+    assert!(searches[1].contains("search_keywords=techouva&"));
+    assert!(requests
+        .iter()
+        .any(|request| request.url.query() == Some("search_id=4343&start=0")));
+
+    // Both searches find the same two topics, fetched once
+    let summary: Value = serde_json::from_str(&std::fs::read_to_string(summary).unwrap()).unwrap();
+    assert_eq!(summary["topics_discovered"], 2);
+    assert_eq!(summary["topics_fetched"], 2);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn source_footer_ends_each_topic() {
+    let harness = Harness::start().await;
+    let today = chrono::Local::now().format("%d/%m/%Y").to_string();
+    let footer = format!(
+        "Source: {}/viewtopic.php?t=101 (consulté le {})",
+        harness.server.uri(),
+        today
+    );
+
+    assert!(harness
+        .run_with_config("[export]\nsource_footer = true\n", &[])
+        .status
+        .success());
+    let halakha = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(halakha.contains(&footer));
+    assert!(halakha.find(&footer) > halakha.find("tant que le soleil ne s"));
+
+    assert!(harness
+        .run_with_config("[export]\nsource_footer = true\n", &["--format", "latex"])
+        .status
+        .success());
+    let tex = std::fs::read_to_string(harness.output_dir().join("Halakha.tex")).unwrap();
+    assert!(tex.contains(&format!(
+        "Source: \\url{{{}/viewtopic.php?t=101}} (consulté le {})",
+        harness.server.uri(),
+        today
+    )));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn writes_the_notice_and_attributes_every_document() {
+    let harness = Harness::start().await;
+    let config = "[export.notice]\nattribution = \"Avec l'autorisation du Rav\"\n";
+
+    assert!(harness.run_with_config(config, &[]).status.success());
+    let notice = std::fs::read_to_string(harness.output_dir().join("NOTICE.txt")).unwrap();
+    assert!(notice.starts_with("Avec l'autorisation du Rav"));
+    assert!(notice.contains(&harness.server.uri()));
+
+    let properties = read_custom_properties(&harness.output_dir().join("Halakha.docx")).unwrap();
+    assert_eq!(
+        properties.get("Attribution").map(String::as_str),
+        Some("Avec l'autorisation du Rav")
+    );
+
+    assert!(harness
+        .run_with_config(config, &["--format", "odt"])
+        .status
+        .success());
+    let meta = common::docx_part(&harness.output_dir().join("Halakha.odt"), "meta.xml");
+    assert!(meta.contains(
+        r#"<meta:user-defined meta:name="Attribution">Avec l&apos;autorisation du Rav</meta:user-defined>"#
+    ));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn sets_the_core_properties_of_the_documents() {
+    let harness = Harness::start().await;
+    let halakha = harness.output_dir().join("Halakha.docx");
+    let created = |core: &str| {
+        let start = core.find("<dcterms:created").unwrap();
+        core[start..core.find("</dcterms:created>").unwrap()].to_owned()
+    };
+
+    assert!(harness.run(&[]).status.success());
+    let core = common::docx_part(&halakha, "docProps/core.xml");
+    assert!(core.contains("<dc:title>Halakha</dc:title>"));
+    assert!(core.contains("<dc:creator>Rav Binyamin Wattenberg</dc:creator>"));
+    assert!(core.contains("<cp:keywords>Halakha</cp:keywords>"));
+    assert!(core.contains("<dcterms:modified"));
+
+    std::thread::sleep(Duration::from_millis(1100));
+    assert!(harness.run(&["--full"]).status.success());
+    let appended = common::docx_part(&halakha, "docProps/core.xml");
+    assert_eq!(created(&appended), created(&core));
+    assert_eq!(appended.matches("<cp:coreProperties").count(), 1);
+    assert!(docx_xml(&halakha).contains("tant que le soleil ne s"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn regenerated_documents_are_identical() {
+    let first = Harness::start().await;
+    let second = Harness::start().await;
+    assert!(first.run(&[]).status.success());
+    assert!(second.run(&[]).status.success());
+
+    for category in ["Halakha.docx", "Hachkafa.docx"] {
+        // The urls differ by the port of the local server only
+        let text = |harness: &Harness| {
+            docx_xml(&harness.output_dir().join(category)).replace(&harness.server.uri(), "")
+        };
+        assert_eq!(text(&first), text(&second));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pages_not_in_utf8_are_read_as_windows_1252() {
+    let harness = Harness::start().await;
+    let topic = fixture("topic_102.html").replace("chapitre 5.", "chapitre 5, à étudier.");
+    let (body, _, _) = encoding_rs::WINDOWS_1252.encode(&topic);
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "102"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(body.into_owned(), "text/html"))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+
+    assert!(harness.run(&[]).status.success());
+
+    let xml = docx_xml(&harness.output_dir().join("Hachkafa.docx"));
+    assert!(xml.contains("à étudier"), "{}", xml);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn archives_each_distinct_page_once() {
+    let harness = Harness::start().await;
+    let archive_dir = harness.dir.path().join("archive");
+    let config = format!("archive_dir = \"{}\"\n", archive_dir.display());
+    let objects = || {
+        walkdir(&archive_dir.join("objects"))
+            .into_iter()
+            .filter(|path| path.extension().is_some_and(|ext| ext == "zst"))
+            .count()
+    };
+
+    assert!(harness.run_with_config(&config, &[]).status.success());
+    let archive = RawArchive::new(&archive_dir);
+    let first_run = archive.pages().unwrap();
+    // Search form, results, two pages of topic 101 and topic 102
+    assert_eq!(first_run.len(), 5);
+    assert_eq!(objects(), 5);
+
+    assert!(harness
+        .run_with_config(&config, &["--full"])
+        .status
+        .success());
+    assert_eq!(archive.pages().unwrap().len(), 10);
+    assert_eq!(objects(), 5);
+
+    // Cut by a crash while appending to the index
+    let mut index = std::fs::OpenOptions::new()
+        .append(true)
+        .open(archive_dir.join("pages.jsonl"))
+        .unwrap();
+    std::io::Write::write_all(&mut index, b"{\"url\":\"http://").unwrap();
+    assert_eq!(archive.pages().unwrap().len(), 10);
+    RawArchive::new(&archive_dir)
+        .store("http://forum.test/", b"<html></html>")
+        .unwrap();
+    assert_eq!(archive.pages().unwrap().len(), 11);
+
+    let topic = first_run
+        .iter()
+        .find(|page| page.url.ends_with("viewtopic.php?t=102"))
+        .unwrap();
+    let raw = archive.load(&topic.sha256).unwrap();
+    assert!(String::from_utf8(raw).unwrap().contains("Hilkhot Techouva"));
+}
+
+fn walkdir(dir: &std::path::Path) -> Vec<std::path::PathBuf> {
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            files.extend(walkdir(&path));
+        } else {
+            files.push(path);
+        }
+    }
+    files
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bounded_runs_fetch_newest_topics_then_backfill_the_oldest() {
+    let harness = Harness::start().await;
+    let out = harness.output_dir();
+
+    // The search uses two requests, leaving one topic
+    assert!(harness.run(&["--max-requests", "3"]).status.success());
+    assert!(out.join("Hachkafa.docx").exists());
+    assert!(!out.join("Halakha.docx").exists());
+
+    assert!(harness
+        .run(&["--max-requests", "3", "--strategy", "backfill"])
+        .status
+        .success());
+    assert!(out.join("Halakha.docx").exists());
+    // Already exported, not appended again by the backfill
+    assert_eq!(
+        docx_xml(&out.join("Hachkafa.docx"))
+            .matches("Hilkhot Techouva")
+            .count(),
+        1
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn category_filter_limits_the_run_to_some_sections() {
+    let harness = Harness::start().await;
+    let out = harness.output_dir();
+
+    assert!(harness
+        .run(&["--category", "hachkafa,Kachrout"])
+        .status
+        .success());
+    assert!(out.join("Hachkafa.docx").exists());
+    assert!(!out.join("Halakha.docx").exists());
+    assert!(!harness
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .any(|request| request.url.query() == Some("t=101")));
+
+    // Not a complete run, the next one still searches the whole archive
+    let state: Value =
+        serde_json::from_str(&std::fs::read_to_string(out.join("state.json")).unwrap()).unwrap();
+    assert!(state["last_successful_run"].is_null());
+}
+
+#[tokio::test]
+async fn export_regenerates_the_selected_category_from_the_archive() {
+    let harness = Harness::start().await;
+    let archive_dir = harness.dir.path().join("archive");
+    let config = format!("archive_dir = \"{}\"\n", archive_dir.display());
+    assert!(harness.run_with_config(&config, &[]).status.success());
+
+    let halakha = harness.output_dir().join("Halakha.docx");
+    let hachkafa = harness.output_dir().join("Hachkafa.docx");
+    let hachkafa_before = std::fs::read(&hachkafa).unwrap();
+    std::fs::remove_file(&halakha).unwrap();
+
+    // Only the archive is read, the forum is gone
+    harness.server.reset().await;
+    let output = harness.run_with_config(&config, &["export", "--category", "halakha"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert!(harness.server.received_requests().await.unwrap().is_empty());
+
+    assert!(docx_xml(&halakha).contains("tant que le soleil ne s"));
+    assert_eq!(std::fs::read(&hachkafa).unwrap(), hachkafa_before);
+    assert!(!harness.output_dir().join(".export").exists());
+
+    let output = harness.run_with_config(&config, &["export", "--category", "Kabbale"]);
+    assert!(!output.status.success());
+}
+
+#[tokio::test]
+async fn export_replays_the_pages_fetched_through_a_redirect() {
+    let harness = Harness::start().await;
+    Mock::given(path("/suivante-t101-2.html"))
+        .respond_with(
+            ResponseTemplate::new(302).insert_header("location", "/viewtopic.php?t=101&start=15"),
+        )
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("start", "15"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/html; charset=utf-8")
+                .set_body_string(fixture("topic_101_page2.html")),
+        )
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    let archive_dir = harness.dir.path().join("archive");
+    let config = format!("archive_dir = \"{}\"\n", archive_dir.display());
+    assert!(harness.run_with_config(&config, &[]).status.success());
+    let halakha = harness.output_dir().join("Halakha.docx");
+    let before = docx_xml(&halakha);
+
+    harness.server.reset().await;
+    let output = harness.run_with_config(&config, &["export", "--category", "halakha"]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(docx_xml(&halakha), before);
+}
+
+#[tokio::test]
+async fn corrupted_documents_are_quarantined_and_regenerated() {
+    let harness = Harness::start().await;
+    let archive_dir = harness.dir.path().join("archive");
+    let config = format!("archive_dir = \"{}\"\n", archive_dir.display());
+    assert!(harness.run_with_config(&config, &[]).status.success());
+
+    let halakha = harness.output_dir().join("Halakha.docx");
+    std::fs::write(&halakha, b"PK\x03\x04 truncated by a crash").unwrap();
+
+    let output = harness.run_with_config(&config, &[]);
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let quarantined = std::fs::read_dir(harness.output_dir().join("quarantine"))
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(quarantined.len(), 1);
+    assert_eq!(
+        std::fs::read(&quarantined[0]).unwrap(),
+        b"PK\x03\x04 truncated by a crash"
+    );
+    assert!(docx_xml(&halakha).contains("tant que le soleil ne s"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn protected_documents_and_delta_documents_are_not_quarantined() {
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+    let out = harness.output_dir();
+
+    // Edited in Word, then left with a body docx-rust cannot parse
+    let halakha = out.join("Halakha.docx");
+    let mut properties = read_custom_properties(&halakha).unwrap();
+    properties.insert("ManuallyEdited".to_owned(), "true".to_owned());
+    mark_generated(&halakha, properties).unwrap();
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(&halakha).unwrap()).unwrap();
+    let mut edited = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    for index in 0..archive.len() {
+        let mut part = archive.by_index(index).unwrap();
+        let name = part.name().to_owned();
+        let mut content = Vec::new();
+        part.read_to_end(&mut content).unwrap();
+        if name == "word/document.xml" {
+            content = b"<w:document><w:body>".to_vec();
+        }
+        edited
+            .start_file(name, zip::write::SimpleFileOptions::default())
+            .unwrap();
+        std::io::Write::write_all(&mut edited, &content).unwrap();
+    }
+    let edited = edited.finish().unwrap().into_inner();
+    std::fs::write(&halakha, &edited).unwrap();
+    let delta = out.join("Hachkafa.delta.docx");
+    std::fs::write(&delta, b"PK\x03\x04 truncated by a crash").unwrap();
+
+    assert!(harness.run(&[]).status.success());
+
+    assert!(!out.join("quarantine").exists());
+    assert_eq!(std::fs::read(&halakha).unwrap(), edited);
+    assert!(delta.exists());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn lists_the_category_documents_in_an_index() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+
+    let markdown = std::fs::read_to_string(harness.output_dir().join("index.md")).unwrap();
+    assert!(markdown.contains("| [Halakha](<Halakha.docx>) | 1 | 12/03/2023 – 14/03/2023 |"));
+    assert!(markdown.contains("| [Hachkafa](<Hachkafa.docx>) | 1 | 01/05/2023 – 02/05/2023 |"));
+    let xml = docx_xml(&harness.output_dir().join("index.docx"));
+    assert!(xml.contains("Halakha.docx"));
+    assert!(xml.contains("12/03/2023 – 14/03/2023"));
+
+    // Still listed once a later run exports nothing new into it
+    std::fs::remove_file(harness.output_dir().join("index.md")).unwrap();
+    assert!(harness.run(&[]).status.success());
+    let markdown = std::fs::read_to_string(harness.output_dir().join("index.md")).unwrap();
+    assert!(markdown.contains("[Halakha](<Halakha.docx>)"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_read_only_output_directory_is_an_error_not_a_document_in_use() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let denied = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+    assert!(!scrapper::export::docx::is_in_use_error(&denied));
+
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+    let out = harness.output_dir();
+    let set_mode = |mode| {
+        std::fs::set_permissions(&out, std::fs::Permissions::from_mode(mode)).unwrap();
+    };
+    set_mode(0o555);
+    // Root writes anyway, nothing to check then
+    if std::fs::write(out.join("probe"), "").is_ok() {
+        std::fs::remove_file(out.join("probe")).unwrap();
+        set_mode(0o755);
+        return;
+    }
+
+    let output = harness.run(&["--full"]);
+    set_mode(0o755);
+
+    assert!(!output.status.success());
+    let logs = String::from_utf8_lossy(&output.stdout);
+    assert!(!logs.contains("the topic is appended"), "{}", logs);
+    assert!(!out.join("Halakha.delta.docx").exists());
+    assert!(!out.join("Hachkafa.delta.docx").exists());
+}
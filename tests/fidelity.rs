@@ -0,0 +1,22 @@
+mod common;
+
+use common::message;
+use scrapper::report::fidelity::lost_words;
+
+#[test]
+fn nothing_is_lost_from_supported_markup() {
+    let html = "Bonjour <span style=\"font-weight: bold\">Rav</span>,<br>\
+        <div class=\"border-l-4 border-blue-500\"><div>David a écrit:</div><div>Peut-on prier assis ?</div></div>\
+        <a href=\"https://example.org\">la source</a><br><marquee>Chabbat Chalom</marquee>";
+
+    assert_eq!(lost_words(&message(html)), Vec::<String>::new());
+}
+
+#[test]
+fn lists_the_words_the_parser_dropped() {
+    let html = "Merci <div class=\"postrow-message\">Message cité deux fois</div>";
+    assert_eq!(
+        lost_words(&message(html)),
+        ["message", "cité", "deux", "fois"]
+    );
+}
@@ -0,0 +1,47 @@
+use scrapper::utils::functions::{safe_file_stem, MAX_FILE_STEM_CHARS};
+
+#[test]
+fn keeps_ordinary_category_names() {
+    assert_eq!(safe_file_stem("Halakha"), "Halakha");
+    assert_eq!(safe_file_stem("Chabbat et fêtes"), "Chabbat et fêtes");
+}
+
+#[test]
+fn replaces_characters_forbidden_by_windows() {
+    assert_eq!(
+        safe_file_stem("Kachrout: lait/viande? \"oui\" <non> a|b*c\\d"),
+        "Kachrout_ lait_viande_ _oui_ _non_ a_b_c_d"
+    );
+    assert_eq!(safe_file_stem("Tab\there"), "Tab_here");
+}
+
+#[test]
+fn prefixes_reserved_device_names() {
+    for name in [
+        "CON",
+        "con",
+        "Prn",
+        "AUX",
+        "nul",
+        "COM1",
+        "lpt9",
+        "con.backup",
+    ] {
+        assert_eq!(safe_file_stem(name), format!("_{}", name));
+    }
+    assert_eq!(safe_file_stem("Console"), "Console");
+    assert_eq!(safe_file_stem("COM10"), "COM10");
+}
+
+#[test]
+fn drops_trailing_dots_and_spaces() {
+    assert_eq!(safe_file_stem("Divers. . "), "Divers");
+    assert_eq!(safe_file_stem("..."), "_");
+    assert_eq!(safe_file_stem(""), "_");
+}
+
+#[test]
+fn caps_the_length() {
+    let long = "é".repeat(MAX_FILE_STEM_CHARS * 2);
+    assert_eq!(safe_file_stem(&long).chars().count(), MAX_FILE_STEM_CHARS);
+}
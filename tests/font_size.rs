@@ -0,0 +1,49 @@
+mod common;
+
+use common::message_by;
+use docx_rust::document::Run;
+use scrapper::config::config::{FontSizeConfig, FontSizeMode};
+use scrapper::parser::parser::set_font_size;
+
+/// Size in half-points of the runs of a message made of a 10px disclaimer and 20px text.
+fn sizes() -> Vec<isize> {
+    let runs: Vec<Run> = message_by(
+        "Rav Binyamin Wattenberg",
+        "<span style=\"font-size:20px\">Réponse</span>\
+         <span style=\"font-size:10px\">Sous réserve</span>",
+    )
+    .into();
+
+    runs.iter()
+        .filter_map(|run| run.property.as_ref()?.size.as_ref())
+        .map(|size| size.value)
+        .collect()
+}
+
+// The font size policy is process-wide, so everything runs in a single test
+#[test]
+fn small_fonts_follow_the_configured_mode() {
+    assert_eq!(sizes(), vec![30, 24]);
+
+    set_font_size(FontSizeConfig {
+        mode: FontSizeMode::Keep,
+        ..Default::default()
+    });
+    assert_eq!(sizes(), vec![30, 15]);
+
+    set_font_size(FontSizeConfig {
+        threshold: 12,
+        minimum: 18,
+        mode: FontSizeMode::Scale,
+        ..Default::default()
+    });
+    // 15px
+    assert_eq!(sizes(), vec![30, 23]);
+
+    set_font_size(FontSizeConfig {
+        threshold: 8,
+        factor: 2.0,
+        ..Default::default()
+    });
+    assert_eq!(sizes(), vec![60, 30]);
+}
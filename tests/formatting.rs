@@ -0,0 +1,99 @@
+mod common;
+
+use common::docx::DocxSnapshot;
+use common::Harness;
+
+const TITLE: &str = "Allumer une bougie avant chabbat";
+
+#[tokio::test(flavor = "multi_thread")]
+async fn screen_is_the_default_layout() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+    let docx = DocxSnapshot::open(&harness.output_dir().join("Halakha.docx"));
+
+    let title = docx.paragraph(TITLE);
+    assert_eq!(title.outline_level, None);
+    assert_eq!(docx.run(TITLE).size, Some(32));
+    assert_eq!(docx.run("Question par YC").size, Some(24));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn print_profile_spaces_and_outlines_the_document() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&["--formatting", "print"]).status.success());
+    let docx = DocxSnapshot::open(&harness.output_dir().join("Halakha.docx"));
+
+    assert_eq!(docx.paragraph(TITLE).outline_level, Some(0));
+    assert_eq!(docx.run(TITLE).size, Some(36));
+    assert_eq!(docx.paragraph("Question par YC").outline_level, Some(1));
+    assert_eq!(docx.run("Question par YC").size, Some(26));
+
+    let body = docx.paragraph("tant que le soleil ne s");
+    assert_eq!(body.spacing_after, Some(120));
+    assert!(body.runs.iter().all(|run| run.size.is_some()));
+
+    let citation = docx
+        .paragraphs
+        .iter()
+        .find(|p| p.indent_left.is_some())
+        .expect("no citation");
+    assert_eq!(citation.indent_left, Some(567));
+    assert!(citation.runs.iter().all(|run| run.italics));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn compact_profile_drops_the_blank_lines() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+    let screen = DocxSnapshot::open(&harness.output_dir().join("Halakha.docx"));
+    std::fs::remove_dir_all(harness.output_dir()).unwrap();
+    assert!(harness
+        .run_with_config("[export]\nformatting = \"compact\"", &[])
+        .status
+        .success());
+    let compact = DocxSnapshot::open(&harness.output_dir().join("Halakha.docx"));
+
+    assert!(compact.paragraphs.len() < screen.paragraphs.len());
+    assert_eq!(compact.run(TITLE).size, Some(28));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn odt_follows_the_profile() {
+    let harness = Harness::start().await;
+
+    assert!(harness
+        .run(&["--format", "odt", "--formatting", "print"])
+        .status
+        .success());
+    let content = common::docx_part(&harness.output_dir().join("Halakha.odt"), "content.xml");
+
+    assert!(content.contains(&format!(
+        "<text:h text:style-name=\"PTitle\" text:outline-level=\"1\">{}</text:h>",
+        TITLE
+    )));
+    assert!(content.contains("fo:font-size=\"18pt\""));
+    assert!(content.contains("fo:margin-left=\"1.00cm\""));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn paragraph_spacing_is_configurable() {
+    let harness = Harness::start().await;
+
+    let config =
+        "[export.paragraphs]\nbefore = 3\nafter = 6\nline_spacing = 1.15\nwidow_control = true";
+    assert!(harness.run_with_config(config, &[]).status.success());
+    let halakha = harness.output_dir().join("Halakha.docx");
+
+    let docx = DocxSnapshot::open(&halakha);
+    assert_eq!(
+        docx.paragraph("tant que le soleil ne s").spacing_after,
+        Some(120)
+    );
+    let xml = common::docx_xml(&halakha);
+    assert!(xml.contains("w:before=\"60\""));
+    assert!(xml.contains("w:line=\"276\""));
+    assert!(xml.contains("<w:widowControl w:val=\"true\"/>"));
+}
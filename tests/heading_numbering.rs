@@ -0,0 +1,67 @@
+mod common;
+
+use common::docx::DocxSnapshot;
+use common::{docx_part, docx_xml, message_by};
+use scrapper::config::config::ExportConfig;
+use scrapper::post::post::Post;
+use scrapper::report::report::Report;
+
+#[test]
+fn topics_and_exchanges_are_numbered() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Halakha.docx");
+    let mut options = ExportConfig::default();
+    options.docx.numbering = true;
+
+    for (id, title) in [(1, "Bougies de chabbat"), (2, "Kiddouch du soir")] {
+        let mut post = Post {
+            url: format!("https://forum.test/viewtopic.php?t={}", id),
+            title: title.to_string(),
+            messages: Some(vec![
+                message_by("Yossef Cohen", "Quand allumer ?"),
+                message_by("Rav Binyamin Wattenberg", "Avant le coucher du soleil."),
+                message_by("Rav Binyamin Wattenberg", "Au plus tard."),
+                message_by("Yossef Cohen", "Et les femmes ?"),
+                message_by("Rav Binyamin Wattenberg", "De même."),
+            ]),
+            category: "Halakha".into(),
+            ..Default::default()
+        };
+        post.messages_to_word(&path, &options, &mut Report::default())
+            .unwrap();
+    }
+
+    let numbering = docx_part(&path, "word/numbering.xml");
+    assert_eq!(numbering.matches("<w:abstractNum ").count(), 1);
+    assert!(numbering.contains("<w:lvlText w:val=\"%1.%2.\"/>"));
+    assert!(docx_part(&path, "[Content_Types].xml").contains("/word/numbering.xml"));
+
+    let xml = docx_xml(&path);
+    assert_eq!(xml.matches("<w:ilvl w:val=\"0\"/>").count(), 2);
+    // Two questions per topic, the follow-up of the Rav is not an exchange of its own
+    assert_eq!(xml.matches("<w:ilvl w:val=\"1\"/>").count(), 4);
+
+    // The number is on the line of the title, not on the blank lines before it
+    let docx = DocxSnapshot::open(&path);
+    assert_eq!(
+        docx.paragraph("Kiddouch du soir").text(),
+        "Kiddouch du soir"
+    );
+}
+
+#[test]
+fn nothing_is_numbered_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Halakha.docx");
+    let mut post = Post {
+        url: "https://forum.test/viewtopic.php?t=1".to_string(),
+        title: "Bougies de chabbat".to_string(),
+        messages: Some(vec![message_by("Yossef Cohen", "Quand allumer ?")]),
+        category: "Halakha".into(),
+        ..Default::default()
+    };
+    post.messages_to_word(&path, &ExportConfig::default(), &mut Report::default())
+        .unwrap();
+
+    assert!(!docx_xml(&path).contains("w:numPr"));
+}
@@ -0,0 +1,46 @@
+mod common;
+
+use chrono::{Duration, NaiveDate};
+use common::docx::DocxSnapshot;
+use common::Harness;
+use scrapper::utils::hebrew::HebrewDate;
+
+fn hebrew(year: i32, month: u32, day: u32) -> String {
+    HebrewDate::from_gregorian(NaiveDate::from_ymd_opt(year, month, day).unwrap()).to_string()
+}
+
+#[test]
+fn converts_gregorian_dates() {
+    assert_eq!(hebrew(2023, 3, 12), "19 Adar 5783");
+    // Rosh Hachana
+    assert_eq!(hebrew(2023, 9, 16), "1 Tichri 5784");
+    assert_eq!(hebrew(2024, 10, 3), "1 Tichri 5785");
+    // Hanoucca
+    assert_eq!(hebrew(2023, 12, 8), "25 Kislev 5784");
+    // Pourim of a leap year
+    assert_eq!(hebrew(2024, 3, 24), "14 Adar II 5784");
+    assert_eq!(hebrew(2024, 2, 20), "11 Adar I 5784");
+    // Pessa'h
+    assert_eq!(hebrew(2025, 4, 13), "15 Nissan 5785");
+}
+
+#[test]
+fn round_trips_through_the_gregorian_calendar() {
+    let start = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+    for days in 0..12_000 {
+        let date = start + Duration::days(days);
+        assert_eq!(HebrewDate::from_gregorian(date).to_gregorian(), Some(date));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn answers_show_the_hebrew_date() {
+    let harness = Harness::start().await;
+    let output = harness.run_with_config("[export.dates]\nhebrew = true\n", &[]);
+    assert!(output.status.success());
+
+    let docx = DocxSnapshot::open(&harness.output_dir().join("Halakha.docx"));
+    assert!(docx.run("Le 13/03/2023 08:30 (20 Adar 5783)").bold);
+    // Not the questions
+    assert!(docx.run("Le 12/03/2023 10:15").bold);
+}
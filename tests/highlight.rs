@@ -0,0 +1,40 @@
+mod common;
+
+use common::fixture;
+use scraper::Html;
+use scrapper::http::client::get_posts_from_current_page;
+use scrapper::parser::sanitizer::sanitize_message;
+
+#[tokio::test]
+async fn topic_urls_drop_the_highlighted_search_terms() {
+    let html = fixture("search_results.html")
+        .replace(
+            "viewtopic.php?t=101",
+            "viewtopic.php?t=101&amp;highlight=bougie+chabbat",
+        )
+        .replace(
+            "viewtopic.php?t=102",
+            "viewtopic.php?highlight=arbitre&amp;t=102",
+        );
+    let posts = get_posts_from_current_page(&Html::parse_document(&html), "https://forum.test/")
+        .await
+        .unwrap();
+
+    assert_eq!(
+        posts.keys().collect::<Vec<_>>(),
+        [
+            "https://forum.test/viewtopic.php?t=101",
+            "https://forum.test/viewtopic.php?t=102"
+        ]
+    );
+}
+
+#[test]
+fn highlighted_words_are_plain_text() {
+    let html = r#"<div class="py-4 postrow-message">Allumer la <span class="posthilit">bougie</span> avant <span class="posthilit">chabbat</span></div>"#;
+
+    assert_eq!(
+        sanitize_message(html),
+        r#"<div class="py-4 postrow-message">Allumer la bougie avant chabbat</div>"#
+    );
+}
@@ -0,0 +1,51 @@
+mod common;
+
+use common::Harness;
+use serde_json::Value;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn imports_documents_generated_without_a_state() {
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+    let out = harness.output_dir();
+    let state_path = out.join("state.json");
+    std::fs::remove_file(&state_path).unwrap();
+    let halakha = std::fs::read(out.join("Halakha.docx")).unwrap();
+
+    assert!(harness.run(&["import"]).status.success());
+
+    let state: Value =
+        serde_json::from_str(&std::fs::read_to_string(&state_path).unwrap()).unwrap();
+    let url = format!("{}/viewtopic.php?t=101", harness.server.uri());
+    let topic = &state["topics"][&url];
+    assert_eq!(topic["title"], "allumer une bougie avant chabbat");
+    assert_eq!(topic["category"], "Halakha");
+    assert_eq!(topic["first_message"], "2023-03-12T10:15:00");
+    assert_eq!(topic["last_message"], "2023-03-14T09:02:00");
+    assert_eq!(
+        topic["last_post"],
+        "14/03/2023 09:02 Rav Binyamin Wattenberg"
+    );
+    assert!(state["exported"][&url].is_array());
+    assert_eq!(state["topics"].as_object().unwrap().len(), 2);
+    // Searched without fetching a topic
+    let topic_requests = harness
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|request| request.url.path().ends_with("viewtopic.php"))
+        .count();
+    assert_eq!(topic_requests, 2);
+
+    // The next run has nothing to fetch
+    let summary = harness.dir.path().join("summary.json");
+    assert!(harness
+        .run(&["--summary", summary.to_str().unwrap()])
+        .status
+        .success());
+    let summary: Value = serde_json::from_str(&std::fs::read_to_string(summary).unwrap()).unwrap();
+    assert_eq!(summary["topics_unchanged"], 2);
+    assert_eq!(std::fs::read(out.join("Halakha.docx")).unwrap(), halakha);
+}
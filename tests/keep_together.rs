@@ -0,0 +1,20 @@
+mod common;
+
+use common::docx::DocxSnapshot;
+use common::Harness;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn headings_stay_with_the_message_they_introduce() {
+    let harness = Harness::start().await;
+
+    assert!(harness.run(&[]).status.success());
+    let docx = DocxSnapshot::open(&harness.output_dir().join("Halakha.docx"));
+
+    assert!(docx.paragraph("Allumer une bougie avant chabbat").keep_next);
+    let heading = docx.position("Question par YC");
+    assert!(docx.paragraphs[heading].keep_next);
+    // The date, between the heading and the text
+    assert!(docx.paragraphs[heading + 1].text().starts_with("Le "));
+    assert!(docx.paragraphs[heading + 1].keep_next);
+    assert!(!docx.paragraphs[heading + 2].keep_next);
+}
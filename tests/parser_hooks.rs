@@ -0,0 +1,77 @@
+mod common;
+
+use common::message;
+use docx_rust::document::{Run, RunContent};
+use docx_rust::formatting::CharacterProperty;
+use scraper::ElementRef;
+use scrapper::parser::hooks::{clear_hooks, register_hook, ParserHook};
+
+/// Renders `<gematria>` tags, used by some posts for verse numbers, in bold.
+struct Gematria;
+
+impl ParserHook for Gematria {
+    fn handle_element(&self, el: ElementRef, _in_citation: bool) -> Option<Vec<Run<'static>>> {
+        (el.value().name() == "gematria").then(|| {
+            vec![Run::default()
+                .property(CharacterProperty::default().bold(true))
+                .push_text(format!("[{}]", el.text().collect::<String>()))]
+        })
+    }
+}
+
+struct Abbreviations;
+
+impl ParserHook for Abbreviations {
+    fn transform_text(&self, text: String) -> String {
+        text.replace("CH\"A", "Choul'han Aroukh")
+    }
+}
+
+fn texts(html: &str) -> Vec<(String, bool)> {
+    let runs: Vec<Run> = message(html).into();
+
+    runs.iter()
+        .map(|run| {
+            let text = run
+                .content
+                .iter()
+                .filter_map(|content| match content {
+                    RunContent::Text(text) => Some(text.text.to_string()),
+                    _ => None,
+                })
+                .collect::<String>();
+            let bold = run.property.as_ref().is_some_and(|cp| cp.bold.is_some());
+            (text, bold)
+        })
+        .filter(|(text, _)| !text.trim().is_empty())
+        .collect()
+}
+
+// Hooks are process-wide, so everything runs in a single test
+#[test]
+fn hooks_customize_conversion() {
+    let message = "Voir le CH\"A siman <gematria>רסג</gematria>";
+
+    assert_eq!(
+        texts(message),
+        [
+            ("Voir le CH\"A siman ".to_string(), false),
+            ("רסג".to_string(), false)
+        ]
+    );
+
+    register_hook(Gematria);
+    register_hook(Abbreviations);
+
+    assert_eq!(
+        texts(message),
+        [
+            ("Voir le Choul'han Aroukh siman ".to_string(), false),
+            ("[רסג]".to_string(), true)
+        ]
+    );
+
+    clear_hooks();
+    assert_eq!(texts(message).len(), 2);
+    assert_eq!(texts(message)[0].0, "Voir le CH\"A siman ");
+}
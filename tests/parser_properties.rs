@@ -0,0 +1,147 @@
+mod common;
+
+use common::{fixture, message};
+use docx_rust::document::{Run, RunContent};
+use proptest::prelude::*;
+use scraper::Html;
+use scrapper::post::post::parse_messages;
+
+/// Message content the forum can plausibly send us, including markup the parser doesn't know.
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    Br,
+    Anchor(String),
+    Span(String, Vec<Node>),
+    Div(Vec<Node>),
+    Citation(String, Vec<Node>),
+    Unknown(&'static str, Vec<Node>),
+}
+
+impl Node {
+    fn html(&self) -> String {
+        let children = |nodes: &[Node]| nodes.iter().map(Node::html).collect::<String>();
+
+        match self {
+            // Padded so that adjacent text nodes don't merge into a single word
+            Node::Text(text) => format!(" {} ", text),
+            Node::Br => "<br>".to_string(),
+            // Two links with nothing between them read as a single word, as in a browser
+            Node::Anchor(text) => format!(" <a href=\"https://example.org\">{}</a> ", text),
+            Node::Span(style, nodes) => format!("<span style=\"{}\">{}</span>", style, children(nodes)),
+            Node::Div(nodes) => format!("<div class=\"text-sm\">{}</div>", children(nodes)),
+            Node::Citation(author, nodes) => format!(
+                "<div class=\"border-l-4 border-blue-500\"><div>{} a écrit:</div><div>{}</div></div>",
+                author,
+                children(nodes)
+            ),
+            Node::Unknown(tag, nodes) => format!("<{tag}>{}</{tag}>", children(nodes)),
+        }
+    }
+
+    /// Words the export must contain, in order.
+    fn words(&self) -> Vec<String> {
+        let children = |nodes: &[Node]| nodes.iter().flat_map(Node::words).collect::<Vec<_>>();
+
+        match self {
+            Node::Text(text) | Node::Anchor(text) => {
+                text.split_whitespace().map(str::to_string).collect()
+            }
+            Node::Br => Vec::new(),
+            Node::Span(_, nodes) | Node::Div(nodes) | Node::Unknown(_, nodes) => children(nodes),
+            // The quoted author line is replaced by the "Citation:" label
+            Node::Citation(_, nodes) => std::iter::once("Citation:".to_string())
+                .chain(children(nodes))
+                .collect(),
+        }
+    }
+}
+
+fn text() -> impl Strategy<Value = String> {
+    "[a-zA-Z0-9éàç?!,.]{1,8}( [a-zA-Z0-9éàç?!,.]{1,8}){0,3}"
+}
+
+fn style() -> impl Strategy<Value = String> {
+    let declaration = prop_oneof![
+        Just("font-weight:bold".to_string()),
+        Just("font-weight: normal".to_string()),
+        Just("font-style:italics".to_string()),
+        Just("text-decoration:underline".to_string()),
+        Just("text-decoration: underline line-through".to_string()),
+        (0u16..400).prop_map(|size| format!("font-size:{}px", size)),
+        Just("font-size: large".to_string()),
+        Just("color:blue".to_string()),
+        Just("color: #ff0000".to_string()),
+        Just("line-height:normal".to_string()),
+        Just("garbage".to_string()),
+        Just(String::new()),
+    ];
+
+    prop::collection::vec(declaration, 0..4).prop_map(|declarations| declarations.join(";"))
+}
+
+fn node() -> impl Strategy<Value = Node> {
+    let leaf = prop_oneof![
+        text().prop_map(Node::Text),
+        Just(Node::Br),
+        text().prop_map(Node::Anchor),
+    ];
+
+    leaf.prop_recursive(4, 32, 4, |inner| {
+        let children = prop::collection::vec(inner, 0..4);
+        prop_oneof![
+            (style(), children.clone()).prop_map(|(style, nodes)| Node::Span(style, nodes)),
+            children.clone().prop_map(Node::Div),
+            (text(), children.clone()).prop_map(|(author, nodes)| Node::Citation(author, nodes)),
+            (
+                prop_oneof![
+                    Just("font"),
+                    Just("strong"),
+                    Just("em"),
+                    Just("section"),
+                    Just("img")
+                ],
+                children
+            )
+                .prop_map(|(tag, nodes)| Node::Unknown(tag, nodes)),
+        ]
+    })
+}
+
+fn runs_words(runs: &[Run]) -> Vec<String> {
+    runs.iter()
+        .flat_map(|run| &run.content)
+        .filter_map(|content| match content {
+            RunContent::Text(text) => Some(text.text.to_string()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+        .split_whitespace()
+        .map(str::to_string)
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn parser_never_panics_and_keeps_text(nodes in prop::collection::vec(node(), 0..6)) {
+        let html = nodes.iter().map(Node::html).collect::<String>();
+        let runs: Vec<Run> = message(&html).into();
+
+        let expected = nodes.iter().flat_map(Node::words).collect::<Vec<_>>();
+        prop_assert_eq!(runs_words(&runs), expected);
+    }
+}
+
+#[test]
+fn messages_missing_their_author_are_skipped() {
+    let page = fixture("topic_102.html").replacen(
+        "<strong class=\"block mb-2\">Sarah Levy</strong>",
+        "",
+        1,
+    );
+
+    let messages = parse_messages(&Html::parse_document(&page)).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].author, "Rav Binyamin Wattenberg");
+}
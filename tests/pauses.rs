@@ -0,0 +1,70 @@
+use chrono::{DateTime, NaiveDate, TimeZone, Utc};
+use scrapper::config::config::PausesConfig;
+use scrapper::state::pauses::{is_holiday, pause_until};
+
+fn utc(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(year, month, day, hour, minute, 0)
+        .unwrap()
+}
+
+fn chabbat(extra: &str) -> PausesConfig {
+    toml::from_str(&format!(
+        "weekly = [{{ from = \"friday 17:00\", to = \"saturday 22:00\" }}]\n{}",
+        extra
+    ))
+    .unwrap()
+}
+
+#[test]
+fn pauses_during_the_weekly_windows() {
+    let config = chabbat("");
+
+    // Friday 19:00 in Paris, until Saturday 22:00
+    assert_eq!(
+        pause_until(&config, utc(2023, 3, 10, 18, 0)),
+        Some(utc(2023, 3, 11, 21, 0))
+    );
+    // Summer time
+    assert_eq!(
+        pause_until(&config, utc(2023, 6, 17, 12, 0)),
+        Some(utc(2023, 6, 17, 20, 0))
+    );
+    assert_eq!(pause_until(&config, utc(2023, 3, 9, 18, 0)), None);
+    assert_eq!(pause_until(&config, utc(2023, 3, 11, 21, 0)), None);
+}
+
+#[test]
+fn windows_can_wrap_around_the_week() {
+    let config: PausesConfig =
+        toml::from_str("weekly = [{ from = \"saturday 23:00\", to = \"monday 06:00\" }]\n")
+            .unwrap();
+
+    assert_eq!(
+        pause_until(&config, utc(2023, 3, 12, 12, 0)),
+        Some(utc(2023, 3, 13, 5, 0))
+    );
+    assert_eq!(pause_until(&config, utc(2023, 3, 13, 6, 0)), None);
+}
+
+#[test]
+fn pauses_on_yom_tov_following_chabbat() {
+    let config = chabbat("holidays = true\nholiday_end = \"22:30\"\n");
+
+    // Rosh Hachana 5784 on Saturday 16 and Sunday 17 September 2023
+    assert!(is_holiday(NaiveDate::from_ymd_opt(2023, 9, 16).unwrap()));
+    assert!(is_holiday(NaiveDate::from_ymd_opt(2023, 9, 17).unwrap()));
+    assert!(!is_holiday(NaiveDate::from_ymd_opt(2023, 9, 18).unwrap()));
+    assert_eq!(
+        pause_until(&config, utc(2023, 9, 15, 16, 0)),
+        Some(utc(2023, 9, 17, 20, 30))
+    );
+    // Chavouot 5783 on Friday 26 and Saturday 27 May 2023, from Thursday evening
+    assert_eq!(
+        pause_until(&config, utc(2023, 5, 25, 16, 0)),
+        Some(utc(2023, 5, 27, 20, 30))
+    );
+    assert_eq!(
+        pause_until(&chabbat("holidays = false\n"), utc(2023, 5, 25, 16, 0)),
+        None
+    );
+}
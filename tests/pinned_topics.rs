@@ -0,0 +1,63 @@
+mod common;
+
+use common::{fixture, Harness};
+use scraper::Html;
+use scrapper::http::client::get_posts_from_current_page;
+use scrapper::post::post::Pinned;
+
+/// The search results with the Hachkafa topic turned into an announcement.
+fn results_with_announcement() -> String {
+    fixture("search_results.html").replacen(
+        "images/folder.gif\" width=\"19\" height=\"18\" alt=\"\" /></td>\n    <td class=\"row1\"><span class=\"forumlink\"><a href=\"viewforum.php?f=5\"",
+        "images/folder_announce.gif\" width=\"19\" height=\"18\" alt=\"\" /></td>\n    <td class=\"row1\"><span class=\"forumlink\"><a href=\"viewforum.php?f=5\"",
+        1,
+    )
+}
+
+#[tokio::test]
+async fn detects_announcements_and_sticky_topics() {
+    let html = results_with_announcement().replacen(
+        "<span class=\"topictitle\"><a href=\"viewtopic.php?t=101\"",
+        "<span class=\"topictitle\"><b>Post-it:</b> <a href=\"viewtopic.php?t=101\"",
+        1,
+    );
+    let posts = get_posts_from_current_page(&Html::parse_document(&html), "https://forum.test/")
+        .await
+        .unwrap();
+
+    let pinned = |id: u32| posts[&format!("https://forum.test/viewtopic.php?t={}", id)].pinned;
+    assert_eq!(pinned(101), Pinned::Sticky);
+    assert_eq!(pinned(102), Pinned::Announcement);
+    assert_eq!(
+        posts["https://forum.test/viewtopic.php?t=101"].title,
+        "allumer une bougie avant chabbat"
+    );
+}
+
+async fn harness_with_announcement() -> Harness {
+    Harness::with_page(
+        "/search.php",
+        ("search_id", "4242"),
+        results_with_announcement(),
+    )
+    .await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn pinned_topics_can_be_left_out_or_kept_alone() {
+    let harness = harness_with_announcement().await;
+    let output = harness.run_with_config("pinned = \"exclude\"\n", &[]);
+    assert!(output.status.success());
+    assert!(harness.output_dir().join("Halakha.docx").exists());
+    assert!(!harness.output_dir().join("Hachkafa.docx").exists());
+
+    let harness = harness_with_announcement().await;
+    let output = harness.run_with_config("pinned = \"only\"\n", &[]);
+    assert!(output.status.success());
+    assert!(!harness.output_dir().join("Halakha.docx").exists());
+    assert!(harness.output_dir().join("Hachkafa.docx").exists());
+
+    let harness = harness_with_announcement().await;
+    assert!(harness.run(&[]).status.success());
+    assert!(harness.output_dir().join("Hachkafa.docx").exists());
+}
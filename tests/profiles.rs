@@ -0,0 +1,45 @@
+mod common;
+
+use common::fixture;
+use scraper::Html;
+use scrapper::http::profiles::parse_profile;
+
+#[test]
+fn reads_registration_date_and_post_count() {
+    let html = Html::parse_document(&fixture("profile_77.html"));
+    let profile = parse_profile(&html, "https://www.techouvot.com/u77", "Yossef Cohen").unwrap();
+
+    assert_eq!(profile.registered.as_deref(), Some("14 Fév 2021"));
+    assert_eq!(profile.posts, Some(12));
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test(flavor = "multi_thread")]
+async fn stores_the_profiles_of_the_participants() {
+    use common::Harness;
+    use scrapper::store::sqlite::Database;
+    use wiremock::matchers::{path, query_param};
+    use wiremock::{Mock, ResponseTemplate};
+
+    let harness = Harness::start().await;
+    Mock::given(path("/profile.php"))
+        .and(query_param("u", "77"))
+        .respond_with(ResponseTemplate::new(200).set_body_string(fixture("profile_77.html")))
+        .expect(1)
+        .mount(&harness.server)
+        .await;
+
+    assert!(harness
+        .run_with_config("[profiles]\n", &[])
+        .status
+        .success());
+    // Already known, not fetched again
+    assert!(harness
+        .run_with_config("[profiles]\n", &["--full"])
+        .status
+        .success());
+
+    let db = Database::open(harness.output_dir().join("ribav.sqlite")).unwrap();
+    let url = format!("{}/profile.php?mode=viewprofile&u=77", harness.server.uri());
+    assert!(db.has_profile(&url).unwrap());
+}
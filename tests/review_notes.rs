@@ -0,0 +1,46 @@
+mod common;
+
+use common::message;
+use scrapper::parser::parser::MessageItem;
+use scrapper::post::post::PostMessage;
+
+fn review_reasons(html: &str) -> Vec<String> {
+    message(html)
+        .items()
+        .into_iter()
+        .filter_map(|item| match item {
+            MessageItem::ReviewNote(reason) => Some(reason),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn flags_what_the_parser_does_not_understand() {
+    assert!(review_reasons("Bonjour <span style=\"font-weight: bold\">Rav</span>").is_empty());
+
+    let reasons = review_reasons("<marquee>Chabbat Chalom</marquee>");
+    assert_eq!(reasons, ["Balise <marquee> non reconnue, contenu conservé"]);
+
+    let reasons = review_reasons("<span style=\"color: purple; font-size: big\">x</span>");
+    assert_eq!(
+        reasons,
+        ["Taille inconnue : big", "Couleur inconnue : purple"]
+    );
+
+    assert_eq!(review_reasons("La priÃ¨re du matin"), ["Encodage suspect"]);
+}
+
+#[test]
+fn flags_a_message_without_its_content_block() {
+    let message = PostMessage {
+        message: "<p>Restauré d'un ancien état</p>".to_string(),
+        ..message("")
+    };
+
+    assert!(message.runs().is_empty());
+    assert!(matches!(
+        message.items().as_slice(),
+        [MessageItem::ReviewNote(_)]
+    ));
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{docx_xml, fixture, message_by, Harness};
+use scrapper::post::roles::{classify_roles, Role};
+use wiremock::matchers::{path, query_param};
+use wiremock::{Mock, ResponseTemplate};
+
+#[test]
+fn messages_are_labeled_with_their_role() {
+    let mut messages = vec![
+        message_by("Yossef Cohen", "Bonjour Rav, peut-on allumer ?"),
+        message_by("Yossef Cohen", "Merci d'avance"),
+        message_by("Yossef Cohen", "up"),
+        message_by("Rav Binyamin Wattenberg", "Oui, juste avant."),
+        message_by("Yossef Cohen", "Merci beaucoup Rav !"),
+        message_by("Sarah Levy", "🙏🙏 !"),
+        message_by("Sarah Levy", "UP !!"),
+        message_by("Yossef Cohen", "Et pour une femme qui allume en retard ?"),
+        message_by("Yossef Cohen", "Merci, mais si elle a oublié ?"),
+        message_by(
+            "Sarah Levy",
+            "<div class=\"overflow-hidden border-blue-500\"><div>Yossef Cohen a écrit:</div>\
+             <div>Et pour une femme qui allume en retard ? Et si elle est en voyage ?</div></div>\
+             Toda raba, kol hakavod",
+        ),
+        message_by(
+            "Sarah Levy",
+            "Merci pour cette réponse, je voulais ajouter que dans ma famille on allumait \
+             toujours vingt minutes avant le coucher du soleil, comme le veut la coutume de \
+             Jérusalem, et que ma grand-mère y tenait beaucoup.",
+        ),
+    ];
+    classify_roles(&mut messages);
+
+    let roles = messages
+        .iter()
+        .map(|message| message.role.unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        roles,
+        [
+            Role::Question,
+            Role::Question,
+            Role::Bump,
+            Role::Answer,
+            Role::Thanks,
+            Role::Bump,
+            Role::Bump,
+            Role::FollowUp,
+            Role::FollowUp,
+            Role::Thanks,
+            Role::FollowUp,
+        ]
+    );
+}
+
+const THANKS: &str = r#"<div class="flex">
+        <div class="w-1/5 p-2"><strong class="block mb-2">Sarah Levy</strong></div>
+        <div class="w-4/5 p-2">
+          <a class="text-blue-link" href="viewtopic.php?p=6003#6003">Posté le: 02/05/2023 19:05</a>
+          <div class="py-4 postrow-message">Merci beaucoup Rav !</div>
+        </div>
+      </div>
+    </div>
+  </div>"#;
+
+/// The Hachkafa topic thanked for its answer.
+async fn harness_with_thanks() -> Harness {
+    let topic = fixture("topic_102.html").replacen("    </div>\n  </div>", THANKS, 1);
+    Harness::with_page("/viewtopic.php", ("t", "102"), topic).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn thanks_are_dropped_from_the_documents_and_kept_in_the_state() {
+    let harness = harness_with_thanks().await;
+    assert!(harness.run(&[]).status.success());
+    let xml = docx_xml(&harness.output_dir().join("Hachkafa.docx"));
+    assert!(xml.contains("Merci beaucoup Rav"));
+
+    let harness = harness_with_thanks().await;
+    assert!(harness
+        .run_with_config("[export]\ndrop = [\"thanks\"]\n", &[])
+        .status
+        .success());
+    let xml = docx_xml(&harness.output_dir().join("Hachkafa.docx"));
+    assert!(xml.contains("Hilkhot Techouva"));
+    assert!(!xml.contains("Merci beaucoup Rav"));
+    let state = std::fs::read_to_string(harness.output_dir().join("state.json")).unwrap();
+    assert!(state.contains("Merci beaucoup Rav"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn a_delta_of_dropped_messages_only_writes_nothing() {
+    let harness = Harness::start().await;
+    assert!(harness.run(&[]).status.success());
+    let out = harness.output_dir();
+    let hachkafa = std::fs::read(out.join("Hachkafa.docx")).unwrap();
+
+    // Thanked for its answer since the first run
+    let topic = fixture("topic_102.html").replacen("    </div>\n  </div>", THANKS, 1);
+    Mock::given(path("/viewtopic.php"))
+        .and(query_param("t", "102"))
+        .respond_with(ResponseTemplate::new(200).set_body_raw(topic, "text/html; charset=utf-8"))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    assert!(harness
+        .run(&["--full", "--delta", "--drop", "thanks"])
+        .status
+        .success());
+
+    assert!(!out.join("Hachkafa.delta.docx").exists());
+    assert_eq!(std::fs::read(out.join("Hachkafa.docx")).unwrap(), hachkafa);
+    let state = std::fs::read_to_string(out.join("state.json")).unwrap();
+    assert!(state.contains("Merci beaucoup Rav"));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn noise_is_dropped_from_the_command_line() {
+    let harness = harness_with_thanks().await;
+    assert!(harness.run(&["--drop", "thanks,bump"]).status.success());
+    let xml = docx_xml(&harness.output_dir().join("Hachkafa.docx"));
+    assert!(xml.contains("Hilkhot Techouva"));
+    assert!(!xml.contains("Merci beaucoup Rav"));
+
+    let harness = Harness::start().await;
+    assert!(!harness.run(&["--drop", "noise"]).status.success());
+}
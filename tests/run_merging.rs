@@ -0,0 +1,48 @@
+mod common;
+
+use common::message;
+use docx_rust::document::{Run, RunContent};
+use scrapper::parser::parser::MessageItem;
+
+fn runs(html: &str) -> Vec<Run<'static>> {
+    message(html).into()
+}
+
+fn texts(run: &Run) -> Vec<String> {
+    run.content
+        .iter()
+        .filter_map(|content| match content {
+            RunContent::Text(text) => Some(text.text.to_string()),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn adjacent_runs_with_the_same_format_are_merged() {
+    let runs = runs(
+        "Bonjour <span style=\"font-weight: bold\">Rav</span> <span style=\"font-weight: bold\">Wattenberg</span><br>Chalom",
+    );
+
+    assert_eq!(
+        runs.iter().map(texts).collect::<Vec<_>>(),
+        [
+            vec!["Bonjour "],
+            vec!["Rav"],
+            vec![" "],
+            vec!["Wattenberg"],
+            vec!["Chalom"],
+        ]
+    );
+}
+
+#[test]
+fn review_notes_stay_apart() {
+    let items = message("Avant <marquee>dedans</marquee> après").items();
+
+    assert!(matches!(
+        items.as_slice(),
+        [MessageItem::Run(_), MessageItem::ReviewNote(reason), MessageItem::Run(_)]
+            if reason == "Balise <marquee> non reconnue, contenu conservé"
+    ));
+}
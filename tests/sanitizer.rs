@@ -0,0 +1,28 @@
+use scrapper::parser::sanitizer::sanitize_message;
+
+#[test]
+fn strips_scripts_styles_and_event_handlers() {
+    let html = r#"<div class="py-4 postrow-message" onclick="steal()">Chalom<script>alert(1)</script><style>p { color: red }</style><noscript>Activez JavaScript</noscript> <span style="font-weight: bold" onmouseover="x()">Rav</span><!-- pub --></div>"#;
+
+    assert_eq!(
+        sanitize_message(html),
+        r#"<div class="py-4 postrow-message">Chalom <span style="font-weight: bold">Rav</span></div>"#
+    );
+}
+
+#[test]
+fn keeps_the_content_of_unknown_tags() {
+    let html = r#"<div class="py-4 postrow-message"><font color="red">Kavana</font><br/><a href="javascript:void(0)">lien</a> <a href="https://example.org/?a=1&amp;b=2">source</a></div>"#;
+
+    assert_eq!(
+        sanitize_message(html),
+        r#"<div class="py-4 postrow-message">Kavana<br><a>lien</a> <a href="https://example.org/?a=1&amp;b=2">source</a></div>"#
+    );
+}
+
+#[test]
+fn text_is_escaped_again() {
+    let html = r#"<div class="py-4 postrow-message">1 &lt; 2 &amp; &lt;script&gt;</div>"#;
+
+    assert_eq!(sanitize_message(html), html);
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "embeddings")]
+
+mod common;
+
+use common::Harness;
+use serde_json::json;
+use wiremock::matchers::{body_string_contains, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+fn embedding(vector: [f32; 2]) -> ResponseTemplate {
+    ResponseTemplate::new(200).set_body_json(json!({ "data": [{ "embedding": vector }] }))
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn finds_topics_closest_to_the_query() {
+    let harness = Harness::start().await;
+    // Everything about free will points one way, the rest the other way
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .and(body_string_contains("libre arbitre"))
+        .respond_with(embedding([1.0, 0.0]))
+        .with_priority(1)
+        .mount(&harness.server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/v1/embeddings"))
+        .respond_with(embedding([0.0, 1.0]))
+        .mount(&harness.server)
+        .await;
+    let config = format!(
+        "[embeddings]\nendpoint = \"{}/v1\"\nmodel = \"test\"\n",
+        harness.server.uri()
+    );
+
+    assert!(harness.run_with_config(&config, &[]).status.success());
+    assert!(harness.output_dir().join("ribav.sqlite").exists());
+
+    let output = harness.run_with_config(
+        &config,
+        &["semantic-search", "le libre arbitre", "--limit", "1"],
+    );
+    assert!(output.status.success());
+
+    let hits = String::from_utf8(output.stdout)
+        .unwrap()
+        .lines()
+        .filter(|line| line.contains('\t'))
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    assert_eq!(hits.len(), 1);
+    assert!(hits[0].contains("Hachkafa\tLe libre arbitre"));
+}
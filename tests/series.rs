@@ -0,0 +1,139 @@
+mod common;
+
+use common::{docx_xml, fixture, Harness};
+use scrapper::post::post::Post;
+use scrapper::post::series::{continuation_of, link_series, merge_series};
+use std::collections::BTreeMap;
+
+#[test]
+fn recognizes_the_titles_continuing_a_topic() {
+    assert_eq!(
+        continuation_of("Le libre arbitre (suite)"),
+        Some(("Le libre arbitre", 2))
+    );
+    assert_eq!(
+        continuation_of("Le libre arbitre - suite et fin"),
+        Some(("Le libre arbitre", 2))
+    );
+    assert_eq!(
+        continuation_of("Le libre arbitre (suite 2)"),
+        Some(("Le libre arbitre", 3))
+    );
+    assert_eq!(
+        continuation_of("Le libre arbitre 2 ?"),
+        Some(("Le libre arbitre", 2))
+    );
+    assert_eq!(
+        continuation_of("Le libre arbitre, partie 4"),
+        Some(("Le libre arbitre", 4))
+    );
+    assert_eq!(continuation_of("Le libre arbitre"), None);
+    assert_eq!(continuation_of("Poursuite"), None);
+    assert_eq!(continuation_of("Chabbat 2024"), None);
+    assert_eq!(continuation_of("Chabbat 1"), None);
+    assert_eq!(continuation_of("Psaume 119"), None);
+}
+
+fn posts(titles: &[(u64, &str, &str)]) -> BTreeMap<String, Post> {
+    titles
+        .iter()
+        .map(|&(id, category, title)| {
+            let url = format!("https://forum.test/viewtopic.php?t={}", id);
+            let post = Post {
+                url: url.clone(),
+                title: title.to_owned(),
+                category: category.into(),
+                ..Default::default()
+            };
+            (url, post)
+        })
+        .collect()
+}
+
+fn url(id: u64) -> String {
+    format!("https://forum.test/viewtopic.php?t={}", id)
+}
+
+#[test]
+fn links_the_continuations_to_the_first_topic_of_their_category() {
+    let mut posts = posts(&[
+        (10, "Hachkafa", "Le libre arbitre"),
+        (12, "Hachkafa", "LE LIBRE ARBITRE (suite)"),
+        (13, "Hachkafa", "Le libre arbitre (suite 2)"),
+        (14, "Halakha", "Le libre arbitre (suite)"),
+        (9, "Hachkafa", "Bougies 2"),
+        (20, "Hachkafa", "Bougies"),
+    ]);
+    assert_eq!(link_series(&mut posts), 2);
+
+    let series = posts[&url(13)].series.clone().unwrap();
+    assert_eq!(series.first_url, url(10));
+    assert_eq!(series.first_title, "Le libre arbitre");
+    assert_eq!(series.part, 3);
+    assert!(!series.merged);
+    assert!(posts[&url(12)].series.is_some());
+    // Another category, posted before its first part
+    assert!(posts[&url(14)].series.is_none());
+    assert!(posts[&url(9)].series.is_none());
+    assert!(posts[&url(10)].series.is_none());
+}
+
+#[test]
+fn merged_continuations_follow_their_first_topic_in_order() {
+    let mut posts = posts(&[
+        (10, "Hachkafa", "Le libre arbitre"),
+        (11, "Hachkafa", "Les anges"),
+        (12, "Hachkafa", "Le libre arbitre 3"),
+        (13, "Hachkafa", "Le libre arbitre 2"),
+        (14, "Hachkafa", "Les anges (suite)"),
+    ]);
+    link_series(&mut posts);
+    // The first part of "Les anges" is not exported by the run
+    let mut post_urls = vec![url(10), url(12), url(13), url(14)];
+    merge_series(&mut post_urls, &mut posts);
+
+    assert_eq!(post_urls, [url(10), url(13), url(12), url(14)]);
+    assert!(posts[&url(12)].series.as_ref().unwrap().merged);
+    assert!(posts[&url(13)].series.as_ref().unwrap().merged);
+    assert!(!posts[&url(14)].series.as_ref().unwrap().merged);
+}
+
+/// The Hachkafa topic turned into the continuation of the Halakha one.
+async fn harness_with_series() -> Harness {
+    let results = fixture("search_results.html")
+        .replacen(">Hachkafa</a>", ">Halakha</a>", 1)
+        .replacen(
+            ">Le libre arbitre</a>",
+            ">allumer une bougie avant chabbat (suite)</a>",
+            1,
+        );
+    Harness::with_page("/search.php", ("search_id", "4242"), results).await
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn continuations_are_linked_or_merged_into_the_chapter_of_the_first_topic() {
+    let harness = harness_with_series().await;
+    assert!(harness.run(&[]).status.success());
+    let xml = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(xml.contains("Allumer une bougie avant chabbat (suite)"));
+    assert!(xml.contains("Suite de «\u{A0}Allumer une bougie avant chabbat\u{A0}»"));
+
+    let harness = harness_with_series().await;
+    assert!(harness
+        .run_with_config("[export]\nseries = \"merge\"\n", &[])
+        .status
+        .success());
+    let xml = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(!xml.contains("Allumer une bougie avant chabbat (suite)"));
+    assert!(!xml.contains("Suite de"));
+    assert!(xml.contains("(suite)"));
+    assert!(xml.contains("libre arbitre"), "{}", xml);
+
+    let harness = harness_with_series().await;
+    assert!(harness
+        .run_with_config("[export]\nseries = \"off\"\n", &[])
+        .status
+        .success());
+    let xml = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(!xml.contains("Suite de"));
+}
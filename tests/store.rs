@@ -0,0 +1,100 @@
+mod common;
+
+use chrono::{TimeZone, Utc};
+#[cfg(feature = "sqlite")]
+use common::Harness;
+use scrapper::config::config::StoreConfig;
+use scrapper::export::feed::FeedEntry;
+use scrapper::state::state::{State, StoredMessage, StoredTopic};
+use scrapper::store::open_store;
+
+fn state() -> State {
+    let mut state = State {
+        last_successful_run: Some(Utc.with_ymd_and_hms(2023, 3, 14, 10, 0, 0).unwrap()),
+        feed: vec![FeedEntry {
+            id: "https://forum.test/viewtopic.php?t=101#5002".to_owned(),
+            title: "Allumer une bougie avant chabbat".to_owned(),
+            link: "https://forum.test/viewtopic.php?t=101".to_owned(),
+            updated: Utc.with_ymd_and_hms(2023, 3, 13, 7, 30, 0).unwrap(),
+            summary: "Il faut allumer juste avant".to_owned(),
+        }],
+        ..Default::default()
+    };
+    state.topics.insert(
+        "https://forum.test/viewtopic.php?t=101".to_owned(),
+        StoredTopic {
+            title: "Allumer une bougie avant chabbat".to_owned(),
+            category: "Halakha".to_owned(),
+            first_message: None,
+            last_message: None,
+            last_post: Some("14/03/2023 09:02 Rav Binyamin Wattenberg".to_owned()),
+            replies: Some(3),
+            views: Some(154),
+            messages: [(
+                "5002".to_owned(),
+                StoredMessage {
+                    author: "Rav Binyamin Wattenberg".to_owned(),
+                    date: "Posté le: 13/03/2023 08:30".to_owned(),
+                    text: "Il faut allumer juste avant".to_owned(),
+                    deleted: true,
+                },
+            )]
+            .into(),
+        },
+    );
+    state
+        .exported
+        .entry("https://forum.test/viewtopic.php?t=101".to_owned())
+        .or_default()
+        .extend(["5001".to_owned(), "5002".to_owned()]);
+    state
+}
+
+fn assert_round_trips(config: StoreConfig) {
+    let dir = tempfile::tempdir().unwrap();
+    let store = open_store(&config, dir.path()).unwrap();
+    assert!(store.load().unwrap().topics.is_empty());
+
+    let state = state();
+    store.save(&state).unwrap();
+    // Saved again, replaced rather than duplicated
+    store.save(&state).unwrap();
+
+    let loaded = open_store(&config, dir.path()).unwrap().load().unwrap();
+    assert_eq!(loaded.last_successful_run, state.last_successful_run);
+    assert_eq!(loaded.feed, state.feed);
+    assert_eq!(loaded.exported, state.exported);
+    assert_eq!(loaded.topics, state.topics);
+}
+
+#[test]
+fn json_file_round_trips_the_state() {
+    assert_round_trips(StoreConfig::default());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn sqlite_round_trips_the_state() {
+    assert_round_trips(StoreConfig::Sqlite { database: None });
+}
+
+#[cfg(feature = "sqlite")]
+#[tokio::test(flavor = "multi_thread")]
+async fn runs_keep_their_state_in_the_configured_store() {
+    let harness = Harness::start().await;
+
+    let config = "[store]\nkind = \"sqlite\"\n";
+    assert!(harness.run_with_config(config, &[]).status.success());
+    assert!(!harness.output_dir().join("state.json").exists());
+    assert!(harness.output_dir().join("ribav.sqlite").exists());
+
+    let state = open_store(
+        &StoreConfig::Sqlite { database: None },
+        &harness.output_dir(),
+    )
+    .unwrap()
+    .load()
+    .unwrap();
+    assert_eq!(state.topics.len(), 2);
+    assert!(state.last_successful_run.is_some());
+}
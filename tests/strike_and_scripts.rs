@@ -0,0 +1,48 @@
+mod common;
+
+use common::docx::DocxSnapshot;
+use common::message_by;
+use scrapper::config::config::ExportConfig;
+use scrapper::post::post::Post;
+use scrapper::report::report::Report;
+
+fn export(html: &str) -> DocxSnapshot {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("Halakha.docx");
+    let mut post = Post {
+        url: "https://forum.test/viewtopic.php?t=1".to_string(),
+        title: "Mesures".to_string(),
+        messages: Some(vec![message_by("Rav Binyamin Wattenberg", html)]),
+        category: "Halakha".into(),
+        ..Default::default()
+    };
+    post.messages_to_word(&path, &ExportConfig::default(), &mut Report::default())
+        .unwrap();
+    DocxSnapshot::open(&path)
+}
+
+#[test]
+fn strikethrough_is_kept() {
+    let docx = export(
+        "Il faut <s>trois</s> deux kazayit, \
+         <strike>pas</strike> et <span style=\"text-decoration: underline line-through\">jamais</span> moins.",
+    );
+
+    assert!(docx.run("trois").strike);
+    assert!(docx.run("pas").strike);
+    let jamais = docx.run("jamais");
+    assert!(jamais.strike && jamais.underline);
+    assert!(!docx.run("deux kazayit,").strike);
+}
+
+#[test]
+fn superscript_and_subscript_are_kept() {
+    let docx = export("Le 1<sup>er</sup> jour, H<sub>2</sub>O et 2<sup><b>e</b></sup>.");
+
+    assert_eq!(
+        docx.run("er").vertical_align.as_deref(),
+        Some("Superscript")
+    );
+    assert_eq!(docx.run("2").vertical_align.as_deref(), Some("Subscript"));
+    assert_eq!(docx.run("Le 1").vertical_align, None);
+}
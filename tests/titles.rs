@@ -0,0 +1,82 @@
+mod common;
+
+use common::{docx_xml, Harness};
+use scrapper::config::config::TitlesConfig;
+use scrapper::export::titles::heading_title;
+
+#[test]
+fn titles_are_only_capitalized_by_default() {
+    let config = TitlesConfig::default();
+    assert_eq!(
+        heading_title("  allumer les bougies ???", &config),
+        "Allumer les bougies ???"
+    );
+    assert_eq!(heading_title("BOUGIES", &config), "BOUGIES");
+    assert_eq!(heading_title("", &config), "");
+}
+
+#[test]
+fn titles_in_capitals_are_put_in_sentence_case() {
+    let config = TitlesConfig {
+        sentence_case: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        heading_title("POURQUOI ALLUMER 2 BOUGIES ?", &config),
+        "Pourquoi allumer 2 bougies ?"
+    );
+    // Mixed case titles keep their names
+    assert_eq!(
+        heading_title("Le Rav Kook et la TSEDAKA", &config),
+        "Le Rav Kook et la TSEDAKA"
+    );
+    assert_eq!(heading_title("2024 ?", &config), "2024 ?");
+}
+
+#[test]
+fn punctuation_runs_are_trimmed() {
+    let config = TitlesConfig {
+        trim_punctuation: true,
+        ..Default::default()
+    };
+    assert_eq!(heading_title("Vraiment ???!!", &config), "Vraiment ?!");
+    assert_eq!(
+        heading_title("Alors..... on fait quoi ?", &config),
+        "Alors... on fait quoi ?"
+    );
+    assert_eq!(heading_title("Vite !?!?", &config), "Vite !?");
+    assert_eq!(heading_title("Fin ?.", &config), "Fin ?.");
+}
+
+#[test]
+fn long_titles_are_cut_at_a_word() {
+    let config = TitlesConfig {
+        max_length: Some(20),
+        ..Default::default()
+    };
+    assert_eq!(
+        heading_title("Allumer les bougies de Hanouka, combien ?", &config),
+        "Allumer les bougies…"
+    );
+    assert_eq!(
+        heading_title("Allumer les bougies", &config),
+        "Allumer les bougies"
+    );
+    assert_eq!(
+        heading_title("Anticonstitutionnellement", &config),
+        "Anticonstitutionnel…"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn headings_are_normalized_and_the_metadata_keep_the_raw_titles() {
+    let harness = Harness::start().await;
+    let output = harness.run_with_config("[export.titles]\nmax_length = 10\n", &[]);
+    assert!(output.status.success());
+
+    let xml = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(xml.contains("Allumer…"), "{}", xml);
+    assert!(!xml.contains("allumer une bougie avant chabbat"));
+    let report = std::fs::read_to_string(harness.output_dir().join("report.json")).unwrap();
+    assert!(report.contains("\"allumer une bougie avant chabbat\""));
+}
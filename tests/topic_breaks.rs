@@ -0,0 +1,60 @@
+mod common;
+
+use common::{docx_xml, message_by};
+use scrapper::config::config::{ExportConfig, TopicBreak};
+use scrapper::post::post::Post;
+use scrapper::report::report::Report;
+use std::path::Path;
+
+fn export_two_topics(path: &Path, topic_break: TopicBreak) -> String {
+    let options = ExportConfig {
+        topic_break,
+        ..Default::default()
+    };
+    for (id, title) in [(1, "Bougies de chabbat"), (2, "Kiddouch du soir")] {
+        let mut post = Post {
+            url: format!("https://forum.test/viewtopic.php?t={}", id),
+            title: title.to_string(),
+            messages: Some(vec![message_by("Rav Binyamin Wattenberg", "Chalom")]),
+            category: "Halakha".into(),
+            ..Default::default()
+        };
+        post.messages_to_word(path, &options, &mut Report::default())
+            .unwrap();
+    }
+    docx_xml(path)
+}
+
+#[test]
+fn topics_run_together_by_default() {
+    let dir = tempfile::tempdir().unwrap();
+    let xml = export_two_topics(&dir.path().join("Halakha.docx"), TopicBreak::Blank);
+
+    assert!(!xml.contains("w:pageBreakBefore"));
+    assert!(!xml.contains("w:sectPr"));
+}
+
+#[test]
+fn each_topic_after_the_first_starts_a_page() {
+    let dir = tempfile::tempdir().unwrap();
+    let xml = export_two_topics(&dir.path().join("Halakha.docx"), TopicBreak::Page);
+
+    assert_eq!(
+        xml.matches("<w:pageBreakBefore w:val=\"true\"/>").count(),
+        1
+    );
+    let page_break = xml.find("w:pageBreakBefore").unwrap();
+    assert!(xml.find("Bougies de chabbat").unwrap() < page_break);
+    assert!(page_break < xml.find("Kiddouch du soir").unwrap());
+}
+
+#[test]
+fn each_topic_after_the_first_starts_a_section() {
+    let dir = tempfile::tempdir().unwrap();
+    let xml = export_two_topics(&dir.path().join("Halakha.docx"), TopicBreak::Section);
+
+    assert_eq!(xml.matches("<w:type w:val=\"nextPage\"/>").count(), 1);
+    let section_break = xml.find("<w:type w:val=\"nextPage\"/>").unwrap();
+    assert!(xml.find("Bougies de chabbat").unwrap() < section_break);
+    assert!(section_break < xml.find("Kiddouch du soir").unwrap());
+}
@@ -0,0 +1,44 @@
+use scrapper::post::post::Post;
+
+fn post(url: &str) -> Post {
+    Post {
+        url: url.to_owned(),
+        ..Default::default()
+    }
+}
+
+#[test]
+fn reads_the_topic_id_of_plain_and_rewritten_urls() {
+    assert_eq!(
+        post("https://www.techouvot.com/viewtopic.php?t=101").topic_id(),
+        Some(101)
+    );
+    assert_eq!(
+        post("https://www.techouvot.com/viewtopic.php?p=5&t=1010#5").topic_id(),
+        Some(1010)
+    );
+    assert_eq!(
+        post("https://www.techouvot.com/bougie-t99.html").topic_id(),
+        Some(99)
+    );
+    assert_eq!(post("https://www.techouvot.com/index.php").topic_id(), None);
+}
+
+#[test]
+fn orders_topics_as_they_were_posted() {
+    let mut urls = vec![
+        "https://www.techouvot.com/viewtopic.php?t=1010",
+        "https://www.techouvot.com/viewtopic.php?t=102",
+        "https://www.techouvot.com/viewtopic.php?t=99",
+    ];
+    urls.sort_by_key(|url| (post(url).topic_id(), url.to_string()));
+
+    assert_eq!(
+        urls,
+        [
+            "https://www.techouvot.com/viewtopic.php?t=99",
+            "https://www.techouvot.com/viewtopic.php?t=102",
+            "https://www.techouvot.com/viewtopic.php?t=1010",
+        ]
+    );
+}
@@ -0,0 +1,56 @@
+#![cfg(feature = "translate")]
+
+mod common;
+
+use common::{docx_xml, Harness};
+use serde_json::json;
+use wiremock::matchers::{header, method, path};
+use wiremock::{Mock, ResponseTemplate};
+
+async fn translate_calls(harness: &Harness) -> usize {
+    harness
+        .server
+        .received_requests()
+        .await
+        .unwrap()
+        .iter()
+        .filter(|request| request.url.path() == "/v2/translate")
+        .count()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn translations_are_cached_and_printed_next_to_messages() {
+    let harness = Harness::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v2/translate"))
+        .and(header("Authorization", "DeepL-Auth-Key secret"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .set_body_json(json!({ "translations": [{ "text": "Translated message" }] })),
+        )
+        .mount(&harness.server)
+        .await;
+    let config = format!(
+        "[translator]\nservice = \"deepl\"\napi_key = \"secret\"\ntarget_lang = \"EN\"\nendpoint = \"{}\"\n",
+        harness.server.uri()
+    );
+
+    assert!(harness
+        .run_with_config(&config, &["--full"])
+        .status
+        .success());
+    let first_run = translate_calls(&harness).await;
+    assert!(first_run > 0);
+
+    let halakha = docx_xml(&harness.output_dir().join("Halakha.docx"));
+    assert!(halakha.contains("<w:tbl>"));
+    assert!(halakha.contains("Translated message"));
+    assert!(harness.output_dir().join("translations.json").exists());
+
+    // Every message is already in the cache
+    assert!(harness
+        .run_with_config(&config, &["--full"])
+        .status
+        .success());
+    assert_eq!(translate_calls(&harness).await, first_run);
+}
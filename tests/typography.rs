@@ -0,0 +1,25 @@
+use scrapper::export::typography::french_typography;
+
+#[test]
+fn adds_non_breaking_spaces_before_high_punctuation() {
+    assert_eq!(
+        french_typography("Peut-on allumer ? Oui ! Attention; voici la règle: non"),
+        "Peut-on allumer\u{202F}? Oui\u{202F}! Attention\u{202F}; voici la règle\u{A0}: non"
+    );
+    assert_eq!(french_typography("Vraiment ??"), "Vraiment\u{202F}??");
+    assert_eq!(french_typography("fin?"), "fin\u{202F}?");
+}
+
+#[test]
+fn leaves_urls_and_times_alone() {
+    let text = "Voir https://www.techouvot.com/viewtopic.php?t=101 à 10:15";
+    assert_eq!(french_typography(text), text);
+}
+
+#[test]
+fn uses_french_quotes_and_ellipsis() {
+    assert_eq!(
+        french_typography("Il dit \" bonjour \" puis..."),
+        "Il dit «\u{A0}bonjour\u{A0}» puis…"
+    );
+}
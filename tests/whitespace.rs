@@ -0,0 +1,52 @@
+mod common;
+
+use common::message;
+use docx_rust::document::{Run, RunContent};
+
+fn text(html: &str) -> String {
+    let runs: Vec<Run> = message(html).into();
+
+    runs.iter()
+        .flat_map(|run| &run.content)
+        .map(|content| match content {
+            RunContent::Text(text) => text.text.to_string(),
+            RunContent::Break(_) => "\n".to_string(),
+            _ => String::new(),
+        })
+        .collect()
+}
+
+#[test]
+fn formatting_in_the_middle_of_a_word_does_not_split_it() {
+    assert_eq!(
+        text("Te<span style=\"font-weight: bold\">chou</span>va"),
+        "Techouva"
+    );
+    assert_eq!(
+        text("<a href=\"https://example.org\">Rav</a>, merci"),
+        "Rav, merci"
+    );
+}
+
+#[test]
+fn spaces_between_words_are_kept_once() {
+    assert_eq!(
+        text("Bonjour <span style=\"font-weight: bold\">Rav</span> Wattenberg"),
+        "Bonjour Rav Wattenberg"
+    );
+    assert_eq!(
+        text("Bonjour <span style=\"font-weight: bold\"> Rav </span> Wattenberg"),
+        "Bonjour Rav Wattenberg"
+    );
+    assert_eq!(text("Chabbat\n   \tChalom"), "Chabbat Chalom");
+}
+
+#[test]
+fn no_space_at_the_start_or_end_of_a_line() {
+    assert_eq!(
+        text("  Question :  <br>\n  Réponse  <br/>  "),
+        "Question :\nRéponse\n"
+    );
+    // Non-breaking spaces are text, not whitespace
+    assert_eq!(text("Question\u{A0}:"), "Question\u{A0}:");
+}